@@ -0,0 +1,23 @@
+-- Persist the robot route queue and active route so they survive restarts
+CREATE TABLE IF NOT EXISTS route_queue (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    start TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    added_by TEXT NOT NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'dispatched', 'completed', 'cancelled', 'failed')),
+    queue_position BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- rehydration only ever looks at pending work
+CREATE INDEX IF NOT EXISTS idx_route_queue_pending
+    ON route_queue (status, queue_position)
+    WHERE status IN ('queued', 'dispatched');
+
+DROP TRIGGER IF EXISTS route_queue_updated_at ON route_queue;
+CREATE TRIGGER route_queue_updated_at
+BEFORE UPDATE ON route_queue
+FOR EACH ROW
+EXECUTE FUNCTION set_updated_at();
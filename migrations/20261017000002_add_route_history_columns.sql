@@ -0,0 +1,12 @@
+-- Track dispatch/finish times and who cancelled a route for history and analytics
+ALTER TABLE route_queue
+    ADD COLUMN IF NOT EXISTS dispatched_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS finished_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS cancelled_by TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_route_queue_added_at
+    ON route_queue (added_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_route_queue_pair_finished
+    ON route_queue (start, destination, finished_at)
+    WHERE status = 'completed';
@@ -0,0 +1,16 @@
+-- Directed, weighted edges between navigation nodes (cost in expected seconds)
+CREATE TABLE IF NOT EXISTS node_edges (
+    from_node TEXT NOT NULL,
+    to_node TEXT NOT NULL,
+    cost_seconds DOUBLE PRECISION NOT NULL CHECK (cost_seconds >= 0),
+    source TEXT NOT NULL DEFAULT 'manual' CHECK (source IN ('manual', 'learned')),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (from_node, to_node),
+    CHECK (from_node <> to_node)
+);
+
+DROP TRIGGER IF EXISTS node_edges_updated_at ON node_edges;
+CREATE TRIGGER node_edges_updated_at
+BEFORE UPDATE ON node_edges
+FOR EACH ROW
+EXECUTE FUNCTION set_updated_at();
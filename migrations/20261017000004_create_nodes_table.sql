@@ -0,0 +1,30 @@
+-- Navigation nodes managed at runtime (previously a const array in main.rs)
+CREATE TABLE IF NOT EXISTS nodes (
+    id TEXT PRIMARY KEY CHECK (id <> ''),
+    label TEXT NOT NULL CHECK (label <> ''),
+    rfid_uuid TEXT UNIQUE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_nodes_sort_order ON nodes (sort_order, id);
+
+DROP TRIGGER IF EXISTS nodes_updated_at ON nodes;
+CREATE TRIGGER nodes_updated_at
+BEFORE UPDATE ON nodes
+FOR EACH ROW
+EXECUTE FUNCTION set_updated_at();
+
+-- Seed the nodes that used to be hardcoded, in their original order
+INSERT INTO nodes (id, label, sort_order) VALUES
+    ('apotheke', 'Apotheke', 1),
+    ('raum6', 'Raum 6', 2),
+    ('raum5', 'Raum 5', 3),
+    ('raum3', 'Raum 3', 4),
+    ('raum4', 'Raum 4', 5),
+    ('raum2', 'Raum 2', 6),
+    ('raum1', 'Raum 1', 7),
+    ('mensa', 'Mensa', 8)
+ON CONFLICT (id) DO NOTHING;
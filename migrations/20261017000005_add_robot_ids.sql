@@ -0,0 +1,12 @@
+-- Multi-robot fleet: routes and notifications are scoped by robot id.
+-- robot_id pins a route to one robot (NULL = any robot);
+-- dispatched_robot_id records the robot the route was sent to.
+ALTER TABLE route_queue ADD COLUMN IF NOT EXISTS robot_id TEXT;
+ALTER TABLE route_queue ADD COLUMN IF NOT EXISTS dispatched_robot_id TEXT;
+
+-- Routes dispatched before the fleet existed were driven by the single legacy robot
+UPDATE route_queue
+SET dispatched_robot_id = 'teletable'
+WHERE dispatched_robot_id IS NULL AND status <> 'queued';
+
+ALTER TABLE robot_notifications ADD COLUMN IF NOT EXISTS robot_id TEXT;
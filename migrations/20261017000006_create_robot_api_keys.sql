@@ -0,0 +1,17 @@
+-- Robot API keys issued by admins. Only a SHA-256 hash of each key is stored;
+-- a robot may have two active keys at once while its key is being rotated.
+CREATE TABLE IF NOT EXISTS robot_api_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    robot_id TEXT NOT NULL CHECK (robot_id <> ''),
+    key_hash TEXT NOT NULL UNIQUE,
+    key_prefix TEXT NOT NULL,
+    label TEXT,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_robot_api_keys_active
+    ON robot_api_keys (robot_id)
+    WHERE revoked_at IS NULL;
@@ -0,0 +1,39 @@
+-- Raw telemetry samples, written at most once every few seconds per robot.
+-- Samples older than the raw retention window are folded into
+-- robot_telemetry_rollups and deleted by the housekeeping task.
+CREATE TABLE IF NOT EXISTS robot_telemetry (
+    robot_id TEXT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    drive_mode TEXT NOT NULL,
+    battery_level SMALLINT NOT NULL,
+    voltage_v REAL,
+    current_a REAL,
+    power_w REAL,
+    lux REAL,
+    gyro_x_dps REAL,
+    gyro_y_dps REAL,
+    gyro_z_dps REAL,
+    ir_front BOOLEAN,
+    ir_left BOOLEAN,
+    ir_right BOOLEAN,
+    PRIMARY KEY (robot_id, recorded_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_robot_telemetry_recorded_at
+    ON robot_telemetry (recorded_at);
+
+-- Per-minute aggregates of one metric. Keeping sum and count (rather than the
+-- average) lets rollups be merged and re-bucketed without losing precision.
+CREATE TABLE IF NOT EXISTS robot_telemetry_rollups (
+    robot_id TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    samples INTEGER NOT NULL CHECK (samples > 0),
+    value_sum DOUBLE PRECISION NOT NULL,
+    value_min DOUBLE PRECISION NOT NULL,
+    value_max DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (robot_id, metric, bucket_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_robot_telemetry_rollups_bucket_start
+    ON robot_telemetry_rollups (bucket_start);
@@ -0,0 +1,19 @@
+-- Sessions can be revoked (logout, refresh token reuse, password change);
+-- access tokens carry the session id and are rejected once it is revoked.
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS revoked_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS revoked_reason TEXT;
+
+-- Rotating refresh tokens. Each refresh marks the presented token used and
+-- issues a new one for the same session; presenting a used token again
+-- revokes the session. Only a SHA-256 hash of each token is stored.
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_session_id ON refresh_tokens (session_id);
@@ -0,0 +1,40 @@
+-- TOTP two-factor authentication (RFC 6238). A row is created by setup and
+-- only takes effect once `enabled_at` is set by verifying a first code.
+-- `last_used_step` is the 30 second time step of the newest accepted code, so
+-- a code cannot be replayed within its validity window.
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL,
+    enabled_at TIMESTAMPTZ,
+    last_used_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Single-use recovery codes for when the authenticator is lost. Only a
+-- SHA-256 hash of each code is stored.
+CREATE TABLE IF NOT EXISTS recovery_codes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_recovery_codes_user_id ON recovery_codes (user_id);
+
+-- Password checked, second factor pending. The challenge token returned by
+-- the first login step is stored hashed; the challenge is dropped after a few
+-- wrong codes.
+CREATE TABLE IF NOT EXISTS login_challenges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    fingerprint_data JSONB NOT NULL DEFAULT '{}'::jsonb,
+    attempts INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- Whether the session was opened with a second factor; carried into the
+-- access tokens as the `mfa` claim.
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS two_factor_verified BOOLEAN NOT NULL DEFAULT FALSE;
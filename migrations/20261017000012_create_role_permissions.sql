@@ -0,0 +1,31 @@
+-- Named permissions granted to each role. Handlers check permissions rather
+-- than role names; admins edit this mapping through PUT /roles/{role}/permissions.
+-- Roles are free-form text matching `users.role`, so a custom role only needs rows here.
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role TEXT NOT NULL,
+    permission TEXT NOT NULL,
+    PRIMARY KEY (role, permission)
+);
+
+-- Defaults reproduce the previous hardcoded checks.
+INSERT INTO role_permissions (role, permission) VALUES
+    ('Viewer', 'robot.view'),
+    ('Operator', 'robot.view'),
+    ('Operator', 'robot.drive'),
+    ('Operator', 'robot.navigate'),
+    ('Operator', 'diary.write'),
+    ('Admin', 'robot.view'),
+    ('Admin', 'robot.drive'),
+    ('Admin', 'robot.navigate'),
+    ('Admin', 'robot.override'),
+    ('Admin', 'robot.led'),
+    ('Admin', 'robot.audio'),
+    ('Admin', 'queue.manage'),
+    ('Admin', 'users.manage'),
+    ('Admin', 'audit.view'),
+    ('Admin', 'diary.write')
+ON CONFLICT DO NOTHING;
+
+-- Roles are now defined by their permissions; `POST /user` accepts the
+-- built-in roles and any role with rows above.
+ALTER TABLE users DROP CONSTRAINT IF EXISTS check_valid_roles;
@@ -0,0 +1,3 @@
+-- With RETAIN_DIARY_ON_ACCOUNT_DELETION, entries of a deleted account are
+-- kept with owner NULL instead of being removed by the ON DELETE CASCADE.
+ALTER TABLE diary_entries ALTER COLUMN owner DROP NOT NULL;
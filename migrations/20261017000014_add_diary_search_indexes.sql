@@ -0,0 +1,14 @@
+-- Keyset pagination orders by (created_at, id); created_at always had a
+-- default, make it mandatory so the cursor never meets a NULL.
+UPDATE diary_entries SET created_at = COALESCE(updated_at, NOW()) WHERE created_at IS NULL;
+ALTER TABLE diary_entries ALTER COLUMN created_at SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_diary_entries_owner_created_at
+    ON diary_entries (owner, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_diary_entries_created_at_id
+    ON diary_entries (created_at DESC, id DESC);
+
+-- Full-text search over `text`. The `simple` configuration does not stem, so
+-- entries in any language match on whole words (prefixes with `word:*`).
+CREATE INDEX IF NOT EXISTS idx_diary_entries_text_search
+    ON diary_entries USING GIN (to_tsvector('simple', text));
@@ -0,0 +1,5 @@
+-- `diary.report` widens GET /diary/report from the caller's own entries to
+-- every user's.
+INSERT INTO role_permissions (role, permission) VALUES
+    ('Admin', 'diary.report')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,27 @@
+-- Who may read a diary entry besides its owner: nobody (`private`), signed-in
+-- users (`team`) or anyone, including the anonymous feed (`public`).
+-- Existing entries become `team`, so nothing stays on the anonymous feed
+-- until its owner publishes it.
+ALTER TABLE diary_entries
+    ADD COLUMN IF NOT EXISTS visibility TEXT NOT NULL DEFAULT 'team';
+
+ALTER TABLE diary_entries
+    DROP CONSTRAINT IF EXISTS check_diary_visibility;
+ALTER TABLE diary_entries
+    ADD CONSTRAINT check_diary_visibility CHECK (visibility IN ('private', 'team', 'public'));
+
+CREATE INDEX IF NOT EXISTS idx_diary_entries_public
+    ON diary_entries (created_at DESC, id DESC)
+    WHERE visibility = 'public';
+
+-- Server-wide switches that admins change at runtime
+CREATE TABLE IF NOT EXISTS app_settings (
+    key TEXT PRIMARY KEY,
+    value JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL
+);
+
+INSERT INTO app_settings (key, value) VALUES
+    ('diary.public_feed', 'true')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,24 @@
+-- Previous versions of diary entries. Each update stores the version it
+-- replaces; `revision` counts up per entry from 1.
+CREATE TABLE IF NOT EXISTS diary_entry_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    entry_id UUID NOT NULL REFERENCES diary_entries(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    working_minutes INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    visibility TEXT NOT NULL,
+    -- When this version was written (the entry's `updated_at` at the time)
+    valid_from TIMESTAMPTZ NOT NULL,
+    -- When it was replaced, and by whom
+    replaced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    replaced_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    UNIQUE (entry_id, revision)
+);
+
+-- Deleted entries stay for a grace period so they can be restored; the
+-- housekeeping task purges them afterwards.
+ALTER TABLE diary_entries ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_diary_entries_deleted_at
+    ON diary_entries (deleted_at)
+    WHERE deleted_at IS NOT NULL;
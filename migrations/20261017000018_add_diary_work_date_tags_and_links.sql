@@ -0,0 +1,21 @@
+-- Optional structure for diary entries: the day the work was done (when it
+-- differs from `created_at`), free-form tags, and links to the robot routes
+-- and notifications the entry is about. Links are validated on write; routes
+-- are never deleted and notifications only disappear with their history.
+ALTER TABLE diary_entries
+    ADD COLUMN IF NOT EXISTS work_date DATE,
+    ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS route_ids UUID[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS notification_ids UUID[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_diary_entries_tags
+    ON diary_entries USING GIN (tags);
+CREATE INDEX IF NOT EXISTS idx_diary_entries_route_ids
+    ON diary_entries USING GIN (route_ids);
+
+-- Revisions keep the structured fields of the version they store
+ALTER TABLE diary_entry_revisions
+    ADD COLUMN IF NOT EXISTS work_date DATE,
+    ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS route_ids UUID[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS notification_ids UUID[] NOT NULL DEFAULT '{}';
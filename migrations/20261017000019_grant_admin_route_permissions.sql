@@ -0,0 +1,8 @@
+-- Permissions for the endpoints that used to check for the `Admin` role.
+INSERT INTO role_permissions (role, permission) VALUES
+    ('Admin', 'nodes.manage'),
+    ('Admin', 'robot.keys'),
+    ('Admin', 'robot.debug'),
+    ('Admin', 'routes.history'),
+    ('Admin', 'diary.settings')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,16 @@
+-- Emails are compared case-insensitively. Store them trimmed and lowercased,
+-- and stop two accounts from differing only by case.
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT LOWER(TRIM(email)) FROM users GROUP BY 1 HAVING COUNT(*) > 1
+    ) THEN
+        RAISE EXCEPTION 'users contains emails that differ only by case or whitespace; merge them before migrating';
+    END IF;
+END $$;
+
+UPDATE users SET email = LOWER(TRIM(email)) WHERE email <> LOWER(TRIM(email));
+UPDATE account_tokens SET email = LOWER(TRIM(email)) WHERE email <> LOWER(TRIM(email));
+
+DROP INDEX IF EXISTS idx_users_email;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_lower ON users (LOWER(email));
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::{
+    audit::models::{AuditEntry, AuditQuery},
+    auth::permissions::{AuditView, RequirePermission},
+    AppState,
+};
+
+pub async fn get_audit_events(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<AuditView>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, (StatusCode, Json<serde_json::Value>)> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let events = sqlx::query_as::<_, AuditEntry>(
+        r#"
+        SELECT id, occurred_at, actor_id, actor_name, actor_role, action,
+               target_type, target_id, robot_id, before, after, client_ip
+        FROM audit_events
+        WHERE ($1::timestamptz IS NULL OR occurred_at >= $1)
+          AND ($2::timestamptz IS NULL OR occurred_at < $2)
+          AND ($3::uuid IS NULL OR actor_id = $3)
+          AND ($4::text IS NULL OR LOWER(actor_name) = LOWER($4))
+          AND ($5::text IS NULL OR action = $5 OR action LIKE $5 || '.%')
+          AND ($6::text IS NULL OR target_type = $6)
+          AND ($7::text IS NULL OR target_id = $7)
+          AND ($8::text IS NULL OR robot_id = $8)
+          AND ($9::text IS NULL OR client_ip = $9)
+        ORDER BY occurred_at DESC, id
+        LIMIT $10 OFFSET $11
+        "#,
+    )
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.actor_id)
+    .bind(query.actor.as_deref())
+    .bind(query.action.as_deref())
+    .bind(query.target_type.as_deref())
+    .bind(query.target_id.as_deref())
+    .bind(query.robot_id.as_deref())
+    .bind(query.client_ip.as_deref())
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "DB error fetching audit events");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to fetch audit events" })),
+        )
+    })?;
+
+    Ok(Json(events))
+}
@@ -0,0 +1,136 @@
+// Persistent audit trail of privileged and robot-control actions.
+//
+// Handlers describe an action with an `AuditEvent` and `record` it in the
+// append-only `audit_events` table. Like the route queue write-through, a
+// failed insert is logged and never fails the action itself.
+
+pub mod handlers;
+pub mod models;
+
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::models::Claims;
+
+/// Action names stored in `audit_events.action`
+pub mod actions {
+    pub const LOCK_ACQUIRE: &str = "lock.acquire";
+    pub const LOCK_RELEASE: &str = "lock.release";
+    /// An admin took over the manual lock of another user
+    pub const LOCK_REVOKE: &str = "lock.revoke";
+    /// An admin NAVIGATE over the manual socket
+    pub const ROUTE_NAVIGATE: &str = "route.navigate";
+    /// An active route was cancelled and put back in front of the queue
+    pub const ROUTE_PREEMPT: &str = "route.preempt";
+    pub const ROUTE_CANCEL: &str = "route.cancel";
+    pub const ROUTE_DELETE: &str = "route.delete";
+    pub const USER_ROLE_CHANGE: &str = "user.role_change";
+    pub const USER_DELETE: &str = "user.delete";
+    pub const ROLE_PERMISSIONS_CHANGE: &str = "role.permissions_change";
+    pub const ROBOT_LED: &str = "robot.led";
+    pub const ROBOT_AUDIO: &str = "robot.audio";
+    pub const ROBOT_KEY_ISSUE: &str = "robot.key_issue";
+    pub const ROBOT_KEY_REVOKE: &str = "robot.key_revoke";
+    /// An admin switched the anonymous diary feed on or off
+    pub const DIARY_SETTINGS_CHANGE: &str = "diary.settings_change";
+}
+
+/// One action about to be recorded; built from the acting user's claims.
+#[derive(Debug)]
+pub struct AuditEvent {
+    action: &'static str,
+    actor_id: Option<Uuid>,
+    actor_name: String,
+    actor_role: String,
+    target_type: Option<&'static str>,
+    target_id: Option<String>,
+    robot_id: Option<String>,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+    client_ip: Option<String>,
+}
+
+impl AuditEvent {
+    pub fn new(action: &'static str, actor: &Claims) -> Self {
+        Self {
+            action,
+            actor_id: Uuid::parse_str(&actor.sub).ok(),
+            actor_name: actor.name.clone(),
+            actor_role: actor.role.clone(),
+            target_type: None,
+            target_id: None,
+            robot_id: None,
+            before: None,
+            after: None,
+            client_ip: None,
+        }
+    }
+
+    pub fn target(mut self, target_type: &'static str, target_id: impl ToString) -> Self {
+        self.target_type = Some(target_type);
+        self.target_id = Some(target_id.to_string());
+        self
+    }
+
+    pub fn robot(mut self, robot_id: &str) -> Self {
+        self.robot_id = Some(robot_id.to_string());
+        self
+    }
+
+    pub fn before(mut self, value: impl Serialize) -> Self {
+        self.before = serde_json::to_value(value).ok();
+        self
+    }
+
+    pub fn after(mut self, value: impl Serialize) -> Self {
+        self.after = serde_json::to_value(value).ok();
+        self
+    }
+
+    /// The client IP as extracted by `ClientIp`; `"unknown"` is stored as NULL.
+    pub fn client_ip(mut self, client_ip: &str) -> Self {
+        self.client_ip = (client_ip != "unknown").then(|| client_ip.to_string());
+        self
+    }
+
+    /// Record without waiting for the insert, for latency-sensitive paths such
+    /// as the control sockets. Failures are logged as in `record`.
+    pub fn spawn(self, db: &PgPool) {
+        let db = db.clone();
+        tokio::spawn(async move { self.record(&db).await });
+    }
+
+    pub async fn record(self, db: &PgPool) {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO audit_events
+                (actor_id, actor_name, actor_role, action, target_type, target_id,
+                 robot_id, before, after, client_ip)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(self.actor_id)
+        .bind(&self.actor_name)
+        .bind(&self.actor_role)
+        .bind(self.action)
+        .bind(self.target_type)
+        .bind(&self.target_id)
+        .bind(&self.robot_id)
+        .bind(&self.before)
+        .bind(&self.after)
+        .bind(&self.client_ip)
+        .execute(db)
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!(
+                action    = %self.action,
+                actor     = %self.actor_name,
+                target_id = ?self.target_id,
+                error     = %e,
+                "DB error recording audit event"
+            );
+        }
+    }
+}
@@ -0,0 +1,467 @@
+// Password reset and email verification.
+//
+// Both flows mail the user a link with a random single-use token. Only the
+// SHA-256 hash is stored in `account_tokens`, together with the address it was
+// sent to. Issuing a token supersedes any unused token of the same purpose,
+// and redeeming one marks it used in the same statement that checks it, so a
+// token works at most once even under concurrent requests.
+//
+// The anonymous endpoints answer the same way, and in about the same time,
+// whether or not the address is registered: the lookup is the only work done
+// in the request, and issuing and mailing the token runs in the background.
+
+use axum::{extract::State, http::StatusCode, Json};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::{
+    extractor::OptionalUser,
+    login::claims_user_id,
+    models::{
+        ForgotPasswordRequest, ResendVerificationRequest, ResetPasswordRequest, User, UserResponse,
+        VerifyEmailRequest,
+    },
+    security::hash_password,
+    sessions::{self, hash_token},
+};
+use crate::mail::Email;
+use crate::AppState;
+
+/// Lifetime of a password reset link
+const PASSWORD_RESET_TTL_MINUTES: i32 = 60;
+/// Lifetime of an email verification link
+const EMAIL_VERIFICATION_TTL_MINUTES: i32 = 48 * 60;
+/// Minimum gap between reset or verification mails to one account, so the
+/// endpoints cannot be used to flood someone's inbox
+const MAIL_COOLDOWN_SECS: i32 = 60;
+
+#[derive(Debug, Clone, Copy)]
+enum Purpose {
+    PasswordReset,
+    EmailVerification,
+}
+
+impl Purpose {
+    fn as_str(self) -> &'static str {
+        match self {
+            Purpose::PasswordReset => "password_reset",
+            Purpose::EmailVerification => "email_verification",
+        }
+    }
+
+    /// Token prefix, so a pasted token shows what it is for
+    fn tag(self) -> &'static str {
+        match self {
+            Purpose::PasswordReset => "pr_",
+            Purpose::EmailVerification => "ev_",
+        }
+    }
+
+    fn ttl_minutes(self) -> i32 {
+        match self {
+            Purpose::PasswordReset => PASSWORD_RESET_TTL_MINUTES,
+            Purpose::EmailVerification => EMAIL_VERIFICATION_TTL_MINUTES,
+        }
+    }
+}
+
+fn invalid_token() -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({"error": "Invalid or expired token"})),
+    )
+}
+
+fn database_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+    )
+}
+
+/// Store a new token for `user_id`, superseding unused ones of the same purpose.
+async fn issue_token(
+    db: &PgPool,
+    user_id: Uuid,
+    email: &str,
+    purpose: Purpose,
+) -> Result<String, sqlx::Error> {
+    let token = sessions::generate_token(purpose.tag());
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE account_tokens
+        SET used_at = NOW()
+        WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(purpose.as_str())
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO account_tokens (user_id, purpose, email, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4, NOW() + make_interval(mins => $5))
+        "#,
+    )
+    .bind(user_id)
+    .bind(purpose.as_str())
+    .bind(email)
+    .bind(hash_token(&token))
+    .bind(purpose.ttl_minutes())
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(token)
+}
+
+/// Mark a valid token used and return its user id and address.
+async fn redeem_token(
+    tx: &mut Transaction<'_, Postgres>,
+    token: &str,
+    purpose: Purpose,
+) -> Result<Option<(Uuid, String)>, sqlx::Error> {
+    sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        UPDATE account_tokens
+        SET used_at = NOW()
+        WHERE token_hash = $1
+          AND purpose = $2
+          AND used_at IS NULL
+          AND expires_at > NOW()
+        RETURNING user_id, email
+        "#,
+    )
+    .bind(hash_token(token))
+    .bind(purpose.as_str())
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// Whether a token of `purpose` was issued to `user_id` within the cooldown
+async fn sent_recently(db: &PgPool, user_id: Uuid, purpose: Purpose) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM account_tokens
+            WHERE user_id = $1
+              AND purpose = $2
+              AND created_at > NOW() - make_interval(secs => $3)
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(purpose.as_str())
+    .bind(MAIL_COOLDOWN_SECS as f64)
+    .fetch_one(db)
+    .await
+}
+
+/// The form emails are stored and compared in: trimmed and lowercased
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Look up an account by address, however its case was typed
+pub(crate) async fn find_by_email(db: &PgPool, email: &str) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(email) = $1")
+        .bind(normalize_email(email))
+        .fetch_optional(db)
+        .await
+}
+
+/// Whether a users write failed because another account has the address
+pub(crate) fn is_email_taken(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .is_some_and(|db_err| db_err.is_unique_violation())
+}
+
+/// 400 for an address another account already uses
+pub(crate) fn email_taken() -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({"error": "User with this email already exists"})),
+    )
+}
+
+/// Mail `user` a link to verify their address. Failures are logged, not
+/// returned: the caller's own change has already been made.
+pub async fn send_verification_email(state: &AppState, user: &User) {
+    let token = match issue_token(&state.db, user.id, &user.email, Purpose::EmailVerification).await
+    {
+        Ok(token) => token,
+        Err(e) => {
+            tracing::error!(error = %e, user_id = %user.id, "Failed to issue email verification token");
+            return;
+        }
+    };
+
+    let email = Email {
+        to: user.email.clone(),
+        subject: "Verify your email address".to_string(),
+        body: format!(
+            "Hi {},\n\nplease confirm your email address by opening this link:\n\n{}/verify-email?token={}\n\nThe link is valid for {} hours.\n",
+            user.name,
+            state.config.app_base_url,
+            token,
+            EMAIL_VERIFICATION_TTL_MINUTES / 60
+        ),
+    };
+
+    if let Err(e) = state.mailer.send(email).await {
+        tracing::error!(error = %e, user_id = %user.id, "Failed to send verification email");
+    }
+}
+
+pub async fn forgot_password(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, Json<serde_json::Value>)> {
+    // The response is the same whether or not the address is registered.
+    let accepted = (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "message": "If the address belongs to an account, a reset link has been sent"
+        })),
+    );
+
+    let user = find_by_email(&state.db, &payload.email)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "DB error looking up user for password reset");
+            database_error(e)
+        })?;
+
+    let Some(user) = user else {
+        tracing::info!("Password reset requested for unknown email");
+        return Ok(accepted);
+    };
+
+    tokio::spawn(send_password_reset(state, user));
+
+    Ok(accepted)
+}
+
+/// Issue and mail a reset link unless one was sent within the cooldown.
+async fn send_password_reset(state: Arc<AppState>, user: User) {
+    match sent_recently(&state.db, user.id, Purpose::PasswordReset).await {
+        Ok(false) => {}
+        Ok(true) => {
+            tracing::warn!(user_id = %user.id, "Password reset requested again within cooldown - not sent");
+            return;
+        }
+        Err(e) => {
+            tracing::error!(error = %e, user_id = %user.id, "DB error checking password reset cooldown");
+            return;
+        }
+    }
+
+    let token = match issue_token(&state.db, user.id, &user.email, Purpose::PasswordReset).await {
+        Ok(token) => token,
+        Err(e) => {
+            tracing::error!(error = %e, user_id = %user.id, "Failed to issue password reset token");
+            return;
+        }
+    };
+
+    let email = Email {
+        to: user.email.clone(),
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "Hi {},\n\nsomeone asked to reset the password of your account. To choose a new password, open this link:\n\n{}/reset-password?token={}\n\nThe link is valid for {} minutes. If you did not ask for this, you can ignore this email.\n",
+            user.name, state.config.app_base_url, token, PASSWORD_RESET_TTL_MINUTES
+        ),
+    };
+
+    if let Err(e) = state.mailer.send(email).await {
+        tracing::error!(error = %e, user_id = %user.id, "Failed to send password reset email");
+    } else {
+        tracing::info!(user_id = %user.id, "Password reset email sent");
+    }
+}
+
+/// Send a fresh verification link. Signed-in callers get one for their own
+/// address; without a token the address comes from the body, since accounts
+/// that must verify before logging in have no token yet. The answer is the
+/// same either way.
+pub async fn resend_verification_email(
+    State(state): State<Arc<AppState>>,
+    OptionalUser(claims): OptionalUser,
+    Json(payload): Json<ResendVerificationRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, Json<serde_json::Value>)> {
+    let accepted = (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "message": "If the address belongs to an unverified account, a verification link has been sent"
+        })),
+    );
+
+    let user = match (&claims, payload.email.as_deref()) {
+        (Some(claims), _) => {
+            let user_id = claims_user_id(&claims.sub)?;
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&state.db)
+                .await
+        }
+        (None, Some(email)) => find_by_email(&state.db, email).await,
+        (None, None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": "Email address required"})),
+            ))
+        }
+    }
+    .map_err(|e| {
+        tracing::error!(error = %e, "DB error looking up user for verification email");
+        database_error(e)
+    })?;
+
+    match user {
+        Some(user) if user.email_verified_at.is_none() => {
+            tokio::spawn(resend_verification(state, user));
+        }
+        _ => tracing::info!("Verification email requested for unknown or verified account"),
+    }
+
+    Ok(accepted)
+}
+
+async fn resend_verification(state: Arc<AppState>, user: User) {
+    match sent_recently(&state.db, user.id, Purpose::EmailVerification).await {
+        Ok(false) => send_verification_email(&state, &user).await,
+        Ok(true) => {
+            tracing::warn!(user_id = %user.id, "Verification email requested again within cooldown - not sent")
+        }
+        Err(e) => {
+            tracing::error!(error = %e, user_id = %user.id, "DB error checking verification email cooldown")
+        }
+    }
+}
+
+pub async fn reset_password(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    if payload.password.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Password must not be empty"})),
+        ));
+    }
+
+    let password_hash = hash_password(&payload.password).await.map_err(|e| {
+        tracing::error!(error = %e, "Password hashing failed during password reset");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Password hashing error: {}", e)})),
+        )
+    })?;
+
+    let mut tx = state.db.begin().await.map_err(database_error)?;
+
+    let Some((user_id, email)) = redeem_token(&mut tx, &payload.token, Purpose::PasswordReset)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "DB error redeeming password reset token");
+            database_error(e)
+        })?
+    else {
+        tracing::warn!("Password reset rejected - invalid or expired token");
+        return Err(invalid_token());
+    };
+
+    // Following the link proves the user controls the address.
+    let updated = sqlx::query(
+        r#"
+        UPDATE users
+        SET password_hash = $1, email_verified_at = COALESCE(email_verified_at, NOW())
+        WHERE id = $2 AND email = $3
+        "#,
+    )
+    .bind(&password_hash)
+    .bind(user_id)
+    .bind(&email)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, user_id = %user_id, "DB error resetting password");
+        database_error(e)
+    })?
+    .rows_affected();
+
+    // The address changed after the link was sent.
+    if updated == 0 {
+        tracing::warn!(user_id = %user_id, "Password reset rejected - email changed since token was issued");
+        return Err(invalid_token());
+    }
+
+    tx.commit().await.map_err(database_error)?;
+
+    if let Err(e) = sessions::revoke_user_sessions(&state, user_id, "password_reset", None).await {
+        tracing::error!(error = %e, user_id = %user_id, "Failed to revoke sessions after password reset");
+    }
+
+    let mut redis = state.redis.clone();
+    let _ = crate::cache::CacheService::invalidate_user(&mut redis, &user_id.to_string()).await;
+    let _ =
+        crate::cache::CacheService::invalidate_user_jwts(&mut redis, &user_id.to_string()).await;
+
+    tracing::info!(user_id = %user_id, "Password reset");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn verify_email(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let mut tx = state.db.begin().await.map_err(database_error)?;
+
+    let Some((user_id, email)) = redeem_token(&mut tx, &payload.token, Purpose::EmailVerification)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "DB error redeeming email verification token");
+            database_error(e)
+        })?
+    else {
+        tracing::warn!("Email verification rejected - invalid or expired token");
+        return Err(invalid_token());
+    };
+
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET email_verified_at = COALESCE(email_verified_at, NOW())
+        WHERE id = $1 AND email = $2
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(&email)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, user_id = %user_id, "DB error verifying email");
+        database_error(e)
+    })?;
+
+    let Some(user) = user else {
+        tracing::warn!(user_id = %user_id, "Email verification rejected - email changed since token was issued");
+        return Err(invalid_token());
+    };
+
+    tx.commit().await.map_err(database_error)?;
+
+    let mut redis = state.redis.clone();
+    let _ = crate::cache::CacheService::invalidate_user(&mut redis, &user_id.to_string()).await;
+
+    tracing::info!(user_id = %user_id, "Email address verified");
+
+    Ok(Json(user.into()))
+}
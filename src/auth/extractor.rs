@@ -1,11 +1,16 @@
 use axum::{
-    extract::FromRequestParts,
+    extract::{ConnectInfo, FromRequestParts},
     http::{request::Parts, StatusCode},
     Json,
 };
+use std::convert::Infallible;
 use std::future::{ready, Future};
+use std::net::SocketAddr;
+use std::sync::Arc;
 
+use crate::auth::login::extract_client_ip;
 use crate::auth::models::Claims;
+use crate::AppState;
 
 // Wrapper type for Claims that implements FromRequestParts
 pub struct AuthenticatedUser(pub Claims);
@@ -34,3 +39,43 @@ where
         ready(result)
     }
 }
+
+/// Claims if the request was authenticated, for routes behind
+/// `optional_auth_middleware`
+pub struct OptionalUser(pub Option<Claims>);
+
+impl<S> FromRequestParts<S> for OptionalUser
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> impl Future<Output = Result<Self, Self::Rejection>> + Send {
+        ready(Ok(OptionalUser(parts.extensions.get::<Claims>().cloned())))
+    }
+}
+
+/// Client IP of the request, as `extract_client_ip` determines it
+pub struct ClientIp(pub String);
+
+impl FromRequestParts<Arc<AppState>> for ClientIp {
+    type Rejection = Infallible;
+
+    fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> impl Future<Output = Result<Self, Self::Rejection>> + Send {
+        let client_addr = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| *addr);
+        ready(Ok(ClientIp(extract_client_ip(
+            &parts.headers,
+            client_addr,
+            &state.config.trusted_proxies,
+        ))))
+    }
+}
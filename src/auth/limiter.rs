@@ -0,0 +1,165 @@
+// Redis-backed attempt limiter shared by signup and login.
+//
+// Each limiter has a scope (`register`, `login`) and counts attempts per
+// subject (`ip:<addr>`, `account:<email>`) in a sliding window. Callers decide
+// what a count means and may block a subject for a while:
+//   - `ratelimit:<scope>:<subject>`          sorted set of attempt timestamps
+//   - `ratelimit:<scope>:blocked:<subject>`  block reason, expires with the block
+//   - `ratelimit:<scope>:blocked`            set of blocked subjects, for admins
+//
+// Callers treat Redis errors as "not limited" (fail open) so an outage of the
+// cache does not lock everybody out.
+
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+
+use crate::auth::{account, models::Lockout};
+
+pub struct Limiter {
+    scope: &'static str,
+    window_seconds: u64,
+}
+
+impl Limiter {
+    pub const fn new(scope: &'static str, window_seconds: u64) -> Self {
+        Self {
+            scope,
+            window_seconds,
+        }
+    }
+
+    pub fn scope(&self) -> &'static str {
+        self.scope
+    }
+
+    fn attempts_key(&self, subject: &str) -> String {
+        format!("ratelimit:{}:{subject}", self.scope)
+    }
+
+    fn blocked_key(&self, subject: &str) -> String {
+        format!("ratelimit:{}:blocked:{subject}", self.scope)
+    }
+
+    fn index_key(&self) -> String {
+        format!("ratelimit:{}:blocked", self.scope)
+    }
+
+    /// Record an attempt and return the number of attempts in the window,
+    /// including this one.
+    pub async fn record(
+        &self,
+        redis: &mut ConnectionManager,
+        subject: &str,
+    ) -> Result<u64, redis::RedisError> {
+        let key = self.attempts_key(subject);
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let window_start = now_ms - (self.window_seconds * 1000) as i64;
+        // Unique member, so attempts in the same millisecond all count.
+        let member = format!("{now_ms}-{}", uuid::Uuid::new_v4().simple());
+
+        let (count,): (u64,) = redis::pipe()
+            .atomic()
+            .zrembyscore(&key, "-inf", window_start)
+            .ignore()
+            .zadd(&key, member, now_ms)
+            .ignore()
+            .zcard(&key)
+            .expire(&key, self.window_seconds as i64)
+            .ignore()
+            .query_async(redis)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Forget the attempts of `subject`.
+    pub async fn reset(
+        &self,
+        redis: &mut ConnectionManager,
+        subject: &str,
+    ) -> Result<(), redis::RedisError> {
+        redis.del(self.attempts_key(subject)).await
+    }
+
+    /// Seconds left on the block of `subject`, if it is blocked.
+    pub async fn blocked_for(
+        &self,
+        redis: &mut ConnectionManager,
+        subject: &str,
+    ) -> Result<Option<i64>, redis::RedisError> {
+        let ttl: i64 = redis.ttl(self.blocked_key(subject)).await?;
+        Ok(match ttl {
+            -2 => None,
+            // A block without expiry should not exist; report a full window.
+            -1 => Some(self.window_seconds as i64),
+            ttl => Some(ttl),
+        })
+    }
+
+    /// Block `subject` for `seconds`. Attempts keep counting.
+    pub async fn block(
+        &self,
+        redis: &mut ConnectionManager,
+        subject: &str,
+        seconds: u64,
+        reason: &str,
+    ) -> Result<(), redis::RedisError> {
+        redis
+            .set_ex::<_, _, ()>(self.blocked_key(subject), reason, seconds)
+            .await?;
+        redis.sadd(self.index_key(), subject).await
+    }
+
+    /// Lift the block of `subject` and forget its attempts. Returns false if it
+    /// was not blocked.
+    pub async fn unblock(
+        &self,
+        redis: &mut ConnectionManager,
+        subject: &str,
+    ) -> Result<bool, redis::RedisError> {
+        let removed: u64 = redis.del(self.blocked_key(subject)).await?;
+        let _: () = redis.del(self.attempts_key(subject)).await?;
+        let _: () = redis.srem(self.index_key(), subject).await?;
+        Ok(removed > 0)
+    }
+
+    /// Subjects currently blocked, pruning expired ones from the index.
+    pub async fn lockouts(
+        &self,
+        redis: &mut ConnectionManager,
+    ) -> Result<Vec<Lockout>, redis::RedisError> {
+        let subjects: Vec<String> = redis.smembers(self.index_key()).await?;
+        let mut lockouts = Vec::new();
+
+        for subject in subjects {
+            let reason: Option<String> = redis.get(self.blocked_key(&subject)).await?;
+            let retry_after = self.blocked_for(redis, &subject).await?;
+
+            match (reason, retry_after) {
+                (Some(reason), Some(retry_after_seconds)) => lockouts.push(Lockout {
+                    scope: self.scope.to_string(),
+                    subject,
+                    reason,
+                    retry_after_seconds,
+                }),
+                _ => {
+                    let _: () = redis.srem(self.index_key(), &subject).await?;
+                }
+            }
+        }
+
+        lockouts.sort_by(|a, b| a.subject.cmp(&b.subject));
+        Ok(lockouts)
+    }
+}
+
+/// Subject for limits per client IP
+pub fn ip_subject(client_ip: &str) -> String {
+    format!("ip:{client_ip}")
+}
+
+/// Subject for limits per account, keyed by the normalized email so that
+/// unknown addresses are limited the same way as registered ones.
+pub fn account_subject(email: &str) -> String {
+    format!("account:{}", account::normalize_email(email))
+}
@@ -3,27 +3,45 @@ use axum::{
     http::{request::Parts, HeaderMap, StatusCode},
     Json,
 };
-use redis::AsyncCommands;
 use std::convert::Infallible;
 use std::future::{ready, Future};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::audit::{actions, AuditEvent};
 use crate::auth::{
-    extractor::AuthenticatedUser,
+    account,
+    extractor::{AuthenticatedUser, ClientIp},
+    limiter::{self, Limiter},
     models::{
-        DeleteUserRequest, LoginRequest, LoginResponse, RegisterRequest, Session,
+        ActiveSession, Claims, ClearLockoutRequest, DeleteUserRequest, Lockout, LoginOutcome,
+        LoginRequest, LoginResponse, RefreshTokenRequest, RegisterRequest, Session,
         UpdateUserRequest, User, UserQuery, UserResponse,
     },
-    roles,
-    security::{create_jwt, hash_password, verify_password},
+    permissions::{self, RequirePermission, UsersManage},
+    profile, roles,
+    security::{hash_password, verify_password},
+    sessions::{self, RefreshOutcome},
+    two_factor,
 };
+use crate::config::TrustedProxy;
 use crate::AppState;
 
 const REGISTER_RATE_LIMIT_MAX_ATTEMPTS: u64 = 5;
 const REGISTER_RATE_LIMIT_WINDOW_SECONDS: u64 = 600;
 const REGISTER_SWIFTSHADER_TIMEOUT_SECONDS: u64 = 86_400;
+const REGISTER_LIMITER: Limiter = Limiter::new("register", REGISTER_RATE_LIMIT_WINDOW_SECONDS);
+
+/// Failed logins are counted per IP and per account over this window
+const LOGIN_RATE_LIMIT_WINDOW_SECONDS: u64 = 900;
+const LOGIN_IP_MAX_FAILURES: u64 = 20;
+/// Failures per account before each further one adds a growing delay
+const LOGIN_ACCOUNT_FREE_FAILURES: u64 = 3;
+const LOGIN_BACKOFF_MAX_SECONDS: u64 = 60;
+const LOGIN_ACCOUNT_MAX_FAILURES: u64 = 10;
+const LOGIN_ACCOUNT_LOCKOUT_SECONDS: u64 = 900;
+const LOGIN_LIMITER: Limiter = Limiter::new("login", LOGIN_RATE_LIMIT_WINDOW_SECONDS);
 
 pub struct MaybeConnectInfo(pub Option<SocketAddr>);
 
@@ -45,24 +63,44 @@ where
     }
 }
 
-/// Extract the real client IP from proxy-forwarded headers.
-/// Falls back to the socket address (ConnectInfo), then returns "unknown".
-fn extract_client_ip(headers: &HeaderMap, client_addr: Option<SocketAddr>) -> String {
-    if let Some(ip) = headers.get("X-Real-IP").and_then(|v| v.to_str().ok()) {
+/// Extract the real client IP. `X-Real-IP` and `X-Forwarded-For` are only
+/// believed when the socket peer (ConnectInfo) is one of `trusted_proxies`;
+/// otherwise anyone could pick their own IP. Returns "unknown" without a peer.
+pub(crate) fn extract_client_ip(
+    headers: &HeaderMap,
+    client_addr: Option<SocketAddr>,
+    trusted_proxies: &[TrustedProxy],
+) -> String {
+    let Some(peer) = client_addr.map(|addr| addr.ip().to_canonical()) else {
+        return "unknown".to_string();
+    };
+    let trusted = |ip: IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+    if !trusted(peer) {
+        return peer.to_string();
+    }
+
+    if let Some(ip) = headers
+        .get("X-Real-IP")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<IpAddr>().ok())
+    {
         return ip.to_string();
     }
     if let Some(fwd) = headers.get("X-Forwarded-For").and_then(|v| v.to_str().ok()) {
-        if let Some(first) = fwd.split(',').next() {
-            return first.trim().to_string();
+        // Each proxy appends the address it received from, so the client is
+        // the last entry that is not one of our own proxies.
+        let hops: Vec<IpAddr> = fwd
+            .split(',')
+            .filter_map(|hop| hop.trim().parse().ok())
+            .collect();
+        if let Some(ip) = hops.iter().rev().find(|ip| !trusted(**ip)).or(hops.first()) {
+            return ip.to_string();
         }
     }
-    if let Some(addr) = client_addr {
-        return addr.ip().to_string();
-    }
-    "unknown".to_string()
+    peer.to_string()
 }
 
-fn extract_user_agent(headers: &HeaderMap) -> Option<String> {
+pub(crate) fn extract_user_agent(headers: &HeaderMap) -> Option<String> {
     headers
         .get("User-Agent")
         .and_then(|v| v.to_str().ok())
@@ -98,13 +136,11 @@ async fn enforce_registration_rate_limit(
         return Ok(());
     }
 
-    let key = format!("ratelimit:register:ip:{client_ip}");
-    let blocked_key = format!("ratelimit:register:blocked:ip:{client_ip}");
+    let subject = limiter::ip_subject(client_ip);
     let mut redis = state.redis.clone();
 
-    match redis.exists::<_, bool>(&blocked_key).await {
-        Ok(true) => {
-            let ttl_seconds = redis.ttl(&blocked_key).await.unwrap_or(-1);
+    match REGISTER_LIMITER.blocked_for(&mut redis, &subject).await {
+        Ok(Some(ttl_seconds)) => {
             tracing::warn!(
                 ip = %client_ip,
                 ttl_seconds,
@@ -114,11 +150,11 @@ async fn enforce_registration_rate_limit(
                 StatusCode::TOO_MANY_REQUESTS,
                 Json(serde_json::json!({
                     "error": "Account creation is temporarily blocked for this IP.",
-                    "retry_after_seconds": if ttl_seconds > 0 { ttl_seconds } else { REGISTER_RATE_LIMIT_WINDOW_SECONDS as i64 }
+                    "retry_after_seconds": ttl_seconds
                 })),
             ));
         }
-        Ok(false) => {}
+        Ok(None) => {}
         Err(e) => {
             tracing::error!(error = %e, ip = %client_ip, "Failed to check signup IP timeout state");
             // Fail open to avoid signup outage if Redis is temporarily unavailable.
@@ -126,7 +162,7 @@ async fn enforce_registration_rate_limit(
         }
     }
 
-    let attempt_count: u64 = match redis.incr(&key, 1_u64).await {
+    let attempt_count = match REGISTER_LIMITER.record(&mut redis, &subject).await {
         Ok(v) => v,
         Err(e) => {
             tracing::error!(error = %e, ip = %client_ip, "Failed to apply register rate limit");
@@ -135,12 +171,6 @@ async fn enforce_registration_rate_limit(
         }
     };
 
-    if attempt_count == 1 {
-        let _: Result<bool, redis::RedisError> = redis
-            .expire(&key, REGISTER_RATE_LIMIT_WINDOW_SECONDS as i64)
-            .await;
-    }
-
     if attempt_count > REGISTER_RATE_LIMIT_MAX_ATTEMPTS {
         tracing::warn!(
             ip = %client_ip,
@@ -171,12 +201,11 @@ async fn timeout_registration_ip(
         return;
     }
 
-    let blocked_key = format!("ratelimit:register:blocked:ip:{client_ip}");
-    let attempts_key = format!("ratelimit:register:ip:{client_ip}");
+    let subject = limiter::ip_subject(client_ip);
     let mut redis = state.redis.clone();
 
-    if let Err(e) = redis
-        .set_ex::<_, _, ()>(&blocked_key, reason, timeout_seconds)
+    if let Err(e) = REGISTER_LIMITER
+        .block(&mut redis, &subject, timeout_seconds, reason)
         .await
     {
         tracing::error!(
@@ -189,7 +218,148 @@ async fn timeout_registration_ip(
         return;
     }
 
-    let _: Result<u64, redis::RedisError> = redis.del(attempts_key).await;
+    let _ = REGISTER_LIMITER.reset(&mut redis, &subject).await;
+}
+
+/// Refuse a login while the client IP or the account is blocked, before any
+/// password or second factor is checked.
+pub(crate) async fn enforce_login_limits(
+    state: &AppState,
+    client_ip: &str,
+    email: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let mut redis = state.redis.clone();
+
+    if client_ip != "unknown" {
+        match LOGIN_LIMITER
+            .blocked_for(&mut redis, &limiter::ip_subject(client_ip))
+            .await
+        {
+            Ok(Some(ttl_seconds)) => {
+                tracing::warn!(ip = %client_ip, ttl_seconds, "Blocked login attempt from timed-out IP");
+                return Err((
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Json(serde_json::json!({
+                        "error": "Too many failed login attempts from this IP. Please try again later.",
+                        "retry_after_seconds": ttl_seconds
+                    })),
+                ));
+            }
+            Ok(None) => {}
+            // Fail open to avoid login outage if Redis is temporarily unavailable.
+            Err(e) => {
+                tracing::error!(error = %e, ip = %client_ip, "Failed to check login IP timeout state");
+            }
+        }
+    }
+
+    match LOGIN_LIMITER
+        .blocked_for(&mut redis, &limiter::account_subject(email))
+        .await
+    {
+        Ok(Some(ttl_seconds)) => {
+            tracing::warn!(
+                email = %email,
+                ip    = %client_ip,
+                ttl_seconds,
+                "Blocked login attempt for locked account"
+            );
+            Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(serde_json::json!({
+                    "error": "Too many failed login attempts for this account. Please try again later.",
+                    "retry_after_seconds": ttl_seconds
+                })),
+            ))
+        }
+        Ok(None) => Ok(()),
+        Err(e) => {
+            tracing::error!(error = %e, email = %email, "Failed to check account lockout state");
+            // Fail open to avoid login outage if Redis is temporarily unavailable.
+            Ok(())
+        }
+    }
+}
+
+/// Seconds an account must wait after its `failures`-th failed login
+fn login_backoff_seconds(failures: u64) -> Option<u64> {
+    let over = failures
+        .checked_sub(LOGIN_ACCOUNT_FREE_FAILURES)?
+        .checked_sub(1)?;
+    Some(
+        2_u64
+            .saturating_pow(over.min(32) as u32)
+            .min(LOGIN_BACKOFF_MAX_SECONDS),
+    )
+}
+
+/// Count a failed login against the client IP and the account, blocking
+/// either once it has failed too often. Wrong second factors count as well.
+pub(crate) async fn record_login_failure(state: &AppState, client_ip: &str, email: &str) {
+    let mut redis = state.redis.clone();
+
+    if client_ip != "unknown" {
+        let subject = limiter::ip_subject(client_ip);
+        match LOGIN_LIMITER.record(&mut redis, &subject).await {
+            Ok(failures) if failures >= LOGIN_IP_MAX_FAILURES => {
+                tracing::warn!(
+                    ip       = %client_ip,
+                    failures,
+                    "Login IP timed out after too many failed attempts"
+                );
+                let _ = LOGIN_LIMITER
+                    .block(
+                        &mut redis,
+                        &subject,
+                        LOGIN_RATE_LIMIT_WINDOW_SECONDS,
+                        "too_many_failed_logins",
+                    )
+                    .await;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::error!(error = %e, ip = %client_ip, "Failed to record failed login for IP");
+            }
+        }
+    }
+
+    let subject = limiter::account_subject(email);
+    let failures = match LOGIN_LIMITER.record(&mut redis, &subject).await {
+        Ok(failures) => failures,
+        Err(e) => {
+            tracing::error!(error = %e, email = %email, "Failed to record failed login for account");
+            return;
+        }
+    };
+
+    let (seconds, reason) = if failures >= LOGIN_ACCOUNT_MAX_FAILURES {
+        tracing::warn!(
+            email    = %email,
+            failures,
+            lockout_seconds = LOGIN_ACCOUNT_LOCKOUT_SECONDS,
+            "Account locked after too many failed login attempts"
+        );
+        (LOGIN_ACCOUNT_LOCKOUT_SECONDS, "account_locked")
+    } else if let Some(delay) = login_backoff_seconds(failures) {
+        (delay, "backoff")
+    } else {
+        return;
+    };
+
+    if let Err(e) = LOGIN_LIMITER
+        .block(&mut redis, &subject, seconds, reason)
+        .await
+    {
+        tracing::error!(error = %e, email = %email, "Failed to block account after failed logins");
+    }
+}
+
+/// Forget the account's failed logins once a login has fully succeeded
+pub(crate) async fn reset_login_failures(state: &AppState, email: &str) {
+    let mut redis = state.redis.clone();
+    let _ = LOGIN_LIMITER
+        .reset(&mut redis, &limiter::account_subject(email))
+        .await;
 }
 
 pub async fn register(
@@ -198,7 +368,7 @@ pub async fn register(
     MaybeConnectInfo(client_addr): MaybeConnectInfo,
     Json(payload): Json<RegisterRequest>,
 ) -> Result<(StatusCode, Json<UserResponse>), (StatusCode, Json<serde_json::Value>)> {
-    let client_ip = extract_client_ip(&headers, client_addr);
+    let client_ip = extract_client_ip(&headers, client_addr, &state.config.trusted_proxies);
     let user_agent = extract_user_agent(&headers);
     let fingerprint_data = payload
         .fingerprint_data
@@ -243,13 +413,12 @@ pub async fn register(
         ));
     }
 
-    let existing_user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
-        .bind(&payload.email)
-        .fetch_optional(&state.db)
+    let email = account::normalize_email(&payload.email);
+    let existing_user = account::find_by_email(&state.db, &email)
         .await
         .map_err(|e| {
             tracing::error!(
-                query   = "SELECT * FROM users WHERE email = ?",
+                query   = "SELECT * FROM users WHERE LOWER(email) = ?",
                 error   = %e,
                 ip      = %client_ip,
                 "DB error during registration email check"
@@ -262,14 +431,11 @@ pub async fn register(
 
     if existing_user.is_some() {
         tracing::warn!(
-            email = %payload.email,
+            email = %email,
             ip    = %client_ip,
             "Registration failed - email already exists"
         );
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({"error": "User with this email already exists"})),
-        ));
+        return Err(account::email_taken());
     }
 
     let password_hash = hash_password(&payload.password).await.map_err(|e| {
@@ -294,16 +460,20 @@ pub async fn register(
     )
     .bind(user_id)
     .bind(&payload.name)
-    .bind(&payload.email)
+    .bind(&email)
     .bind(&password_hash)
     .bind(roles::VIEWER)
     .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
+        if account::is_email_taken(&e) {
+            tracing::warn!(email = %email, ip = %client_ip, "Registration failed - email already exists");
+            return account::email_taken();
+        }
         tracing::error!(
             query = "INSERT INTO users ... RETURNING *",
             error = %e,
-            email = %payload.email,
+            email = %email,
             "DB error while creating user"
         );
         (
@@ -351,6 +521,8 @@ pub async fn register(
         "New user registered"
     );
 
+    account::send_verification_email(&state, &user).await;
+
     Ok((StatusCode::CREATED, Json(user.into())))
 }
 
@@ -359,21 +531,21 @@ pub async fn login(
     headers: HeaderMap,
     MaybeConnectInfo(client_addr): MaybeConnectInfo,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, (StatusCode, Json<serde_json::Value>)> {
-    let client_ip = extract_client_ip(&headers, client_addr);
+) -> Result<Json<LoginOutcome>, (StatusCode, Json<serde_json::Value>)> {
+    let client_ip = extract_client_ip(&headers, client_addr, &state.config.trusted_proxies);
     let user_agent = extract_user_agent(&headers);
     let fingerprint_data = payload
         .fingerprint_data
         .clone()
         .unwrap_or_else(|| serde_json::json!({}));
 
-    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
-        .bind(&payload.email)
-        .fetch_optional(&state.db)
+    enforce_login_limits(&state, &client_ip, &payload.email).await?;
+
+    let user = account::find_by_email(&state.db, &payload.email)
         .await
         .map_err(|e| {
             tracing::error!(
-                query = "SELECT * FROM users WHERE email = ?",
+                query = "SELECT * FROM users WHERE LOWER(email) = ?",
                 error = %e,
                 "DB error during login lookup"
             );
@@ -381,20 +553,21 @@ pub async fn login(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(serde_json::json!({"error": format!("Database error: {}", e)})),
             )
-        })?
-        .ok_or_else(|| {
-            tracing::warn!(
-                email             = %payload.email,
-                ip                = %client_ip,
-                attempted_password = %payload.password,
-                "Failed login attempt - user not found"
-            );
-            (
-                StatusCode::UNAUTHORIZED,
-                Json(serde_json::json!({"error": "Invalid credentials"})),
-            )
         })?;
 
+    let Some(user) = user else {
+        tracing::warn!(
+            email = %payload.email,
+            ip    = %client_ip,
+            "Failed login attempt - user not found"
+        );
+        record_login_failure(&state, &client_ip, &payload.email).await;
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Invalid credentials"})),
+        ));
+    };
+
     let valid = verify_password(&payload.password, &user.password_hash)
         .await
         .map_err(|e| {
@@ -407,19 +580,100 @@ pub async fn login(
 
     if !valid {
         tracing::warn!(
-            user_id           = %user.id,
-            name              = %user.name,
-            email             = %payload.email,
-            ip                = %client_ip,
-            attempted_password = %payload.password,
+            user_id = %user.id,
+            name    = %user.name,
+            email   = %payload.email,
+            ip      = %client_ip,
             "Failed login attempt - wrong password"
         );
+        record_login_failure(&state, &client_ip, &payload.email).await;
         return Err((
             StatusCode::UNAUTHORIZED,
             Json(serde_json::json!({"error": "Invalid credentials"})),
         ));
     }
 
+    if state.config.require_email_verification && user.email_verified_at.is_none() {
+        tracing::warn!(
+            user_id = %user.id,
+            ip      = %client_ip,
+            "Login refused - email address not verified"
+        );
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": "Email address not verified"})),
+        ));
+    }
+
+    let two_factor_enabled = two_factor::is_enabled(&state.db, user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user.id, "DB error checking two-factor status");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    if two_factor_enabled {
+        let challenge = two_factor::create_login_challenge(&state.db, user.id, &fingerprint_data)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, user_id = %user.id, "Failed to create login challenge");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+                )
+            })?;
+
+        tracing::info!(
+            user_id = %user.id,
+            ip      = %client_ip,
+            "Password accepted - awaiting second factor"
+        );
+        return Ok(Json(LoginOutcome::TwoFactor(challenge)));
+    }
+
+    // The login succeeded, so earlier misses were not an attack on it. With
+    // 2FA this waits for the second factor (see `login_two_factor`).
+    reset_login_failures(&state, &payload.email).await;
+
+    let (session_id, response) = start_session(
+        &state,
+        &user,
+        &client_ip,
+        user_agent.as_deref(),
+        &fingerprint_data,
+        false,
+    )
+    .await?;
+
+    tracing::info!(
+        user_id    = %user.id,
+        name       = %user.name,
+        role       = %user.role,
+        session_id = %session_id,
+        ip         = %client_ip,
+        "Successful login"
+    );
+
+    // Cache user data for faster subsequent requests.
+    let mut redis = state.redis.clone();
+    let _ = crate::cache::CacheService::cache_user(&mut redis, &user.id.to_string(), &user).await;
+
+    Ok(Json(LoginOutcome::Session(response)))
+}
+
+/// Open a session for `user` and issue its first token pair. `two_factor`
+/// records whether the login passed a second factor.
+pub(crate) async fn start_session(
+    state: &AppState,
+    user: &User,
+    client_ip: &str,
+    user_agent: Option<&str>,
+    fingerprint_data: &serde_json::Value,
+    two_factor: bool,
+) -> Result<(Uuid, LoginResponse), (StatusCode, Json<serde_json::Value>)> {
     let mut tx = state.db.begin().await.map_err(|e| {
         tracing::error!(error = %e, "Failed to begin login transaction");
         (
@@ -428,14 +682,19 @@ pub async fn login(
         )
     })?;
 
+    let session_id = Uuid::new_v4();
     sqlx::query(
-        "INSERT INTO sessions (id, user_id, ip_address, fingerprint_data, user_agent) VALUES ($1, $2, $3, $4, $5)",
+        r#"
+        INSERT INTO sessions (id, user_id, ip_address, fingerprint_data, user_agent, two_factor_verified)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
     )
-    .bind(Uuid::new_v4())
+    .bind(session_id)
     .bind(user.id)
-    .bind(&client_ip)
-    .bind(&fingerprint_data)
-    .bind(&user_agent)
+    .bind(client_ip)
+    .bind(fingerprint_data)
+    .bind(user_agent)
+    .bind(two_factor)
     .execute(&mut *tx)
     .await
     .map_err(|e| {
@@ -446,6 +705,17 @@ pub async fn login(
         )
     })?;
 
+    let refresh_token =
+        sessions::insert_refresh_token(&mut tx, session_id, state.config.refresh_token_ttl_days)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, user_id = %user.id, "Failed to insert refresh token");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+                )
+            })?;
+
     tx.commit().await.map_err(|e| {
         tracing::error!(error = %e, user_id = %user.id, "Failed to commit login transaction");
         (
@@ -454,14 +724,56 @@ pub async fn login(
         )
     })?;
 
-    let token = create_jwt(
-        &user.id.to_string(),
-        &user.name,
-        &user.role,
-        &state.config.jwt_secret,
-        state.config.jwt_expiry_hours,
-    )
-    .map_err(|e| {
+    let response = sessions::token_response(state, user, session_id, two_factor, refresh_token)
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user.id, "JWT generation failed");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Token generation error: {}", e)})),
+            )
+        })?;
+
+    Ok((session_id, response))
+}
+
+pub async fn refresh_token(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> Result<Json<LoginResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let outcome = sessions::rotate(&state, &payload.refresh_token)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "DB error rotating refresh token");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    let (user, session_id, two_factor, refresh_token) = match outcome {
+        RefreshOutcome::Rotated {
+            user,
+            session_id,
+            two_factor,
+            refresh_token,
+        } => (user, session_id, two_factor, refresh_token),
+        RefreshOutcome::Invalid => {
+            tracing::warn!("Token refresh rejected - invalid, expired or revoked refresh token");
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({"error": "Invalid refresh token"})),
+            ));
+        }
+        RefreshOutcome::Reused => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({"error": "Refresh token already used; session revoked"})),
+            ));
+        }
+    };
+
+    let response = sessions::token_response(&state, &user, session_id, two_factor, refresh_token)
+        .map_err(|e| {
         tracing::error!(error = %e, user_id = %user.id, "JWT generation failed");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -469,19 +781,94 @@ pub async fn login(
         )
     })?;
 
-    tracing::info!(
-        user_id = %user.id,
-        name    = %user.name,
-        role    = %user.role,
-        ip      = %client_ip,
-        "Successful login"
-    );
+    tracing::debug!(user_id = %user.id, session_id = %session_id, "Access token refreshed");
 
-    // Cache user data for faster subsequent requests.
-    let mut redis = state.redis.clone();
-    let _ = crate::cache::CacheService::cache_user(&mut redis, &user.id.to_string(), &user).await;
+    Ok(Json(response))
+}
+
+pub async fn logout(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let session_id = Uuid::parse_str(&claims.sid).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Invalid session ID"})),
+        )
+    })?;
+
+    sessions::revoke_session(&state, session_id, "logout")
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, session_id = %session_id, "DB error revoking session");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    tracing::info!(user_id = %claims.sub, session_id = %session_id, "User logged out");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) fn claims_user_id(sub: &str) -> Result<Uuid, (StatusCode, Json<serde_json::Value>)> {
+    Uuid::parse_str(sub).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Invalid user ID"})),
+        )
+    })
+}
+
+pub async fn get_my_sessions(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+) -> Result<Json<Vec<ActiveSession>>, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = claims_user_id(&claims.sub)?;
+    let current = Uuid::parse_str(&claims.sid).ok();
+
+    let active = sessions::list_active(&state.db, user_id, current)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user_id, "DB error listing active sessions");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    Ok(Json(active))
+}
+
+pub async fn delete_my_session(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = claims_user_id(&claims.sub)?;
+
+    let revoked = sessions::revoke_user_session(&state, user_id, session_id, "terminated")
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, session_id = %session_id, "DB error terminating session");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    // Other users' sessions are reported as missing rather than forbidden.
+    if !revoked {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Session not found"})),
+        ));
+    }
 
-    Ok(Json(LoginResponse { token }))
+    tracing::info!(user_id = %user_id, session_id = %session_id, "User terminated session");
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 pub async fn get_me(
@@ -538,6 +925,7 @@ pub async fn get_me(
 
 pub async fn get_user(
     State(state): State<Arc<AppState>>,
+    _: RequirePermission<UsersManage>,
     Query(query): Query<UserQuery>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
     if let Some(id) = query.id {
@@ -601,6 +989,7 @@ pub async fn get_user(
 
 pub async fn get_users(
     State(state): State<Arc<AppState>>,
+    _: RequirePermission<UsersManage>,
 ) -> Result<Json<Vec<UserResponse>>, (StatusCode, Json<serde_json::Value>)> {
     let users = sqlx::query_as::<_, User>(
         r#"
@@ -629,6 +1018,7 @@ pub async fn get_users(
 
 pub async fn get_user_sessions(
     State(state): State<Arc<AppState>>,
+    _: RequirePermission<UsersManage>,
     Path(user_id): Path<Uuid>,
 ) -> Result<Json<Vec<Session>>, (StatusCode, Json<serde_json::Value>)> {
     let user_exists = sqlx::query_scalar::<_, i64>("SELECT COUNT(1) FROM users WHERE id = $1")
@@ -677,8 +1067,141 @@ pub async fn get_user_sessions(
     Ok(Json(sessions))
 }
 
+pub async fn delete_user_sessions(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { claims, .. }: RequirePermission<UsersManage>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let user_exists = sqlx::query_scalar::<_, i64>("SELECT COUNT(1) FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                query = "SELECT COUNT(1) FROM users WHERE id = ?",
+                error = %e,
+                user_id = %user_id,
+                "DB error checking user for session termination"
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    if user_exists == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "User not found"})),
+        ));
+    }
+
+    let revoked = sessions::revoke_user_sessions(&state, user_id, "admin_terminated", None)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user_id, "DB error terminating user sessions");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    tracing::info!(
+        user_id  = %user_id,
+        admin_id = %claims.sub,
+        revoked  = revoked.len(),
+        "Admin terminated user sessions"
+    );
+
+    Ok(Json(serde_json::json!({ "revoked": revoked.len() })))
+}
+
+pub async fn get_lockouts(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<UsersManage>,
+) -> Result<Json<Vec<Lockout>>, (StatusCode, Json<serde_json::Value>)> {
+    let mut redis = state.redis.clone();
+    let mut lockouts = Vec::new();
+
+    for limiter in [&LOGIN_LIMITER, &REGISTER_LIMITER] {
+        lockouts.extend(limiter.lockouts(&mut redis).await.map_err(|e| {
+            tracing::error!(error = %e, scope = limiter.scope(), "Failed to list lockouts");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Redis error: {}", e)})),
+            )
+        })?);
+    }
+
+    Ok(Json(lockouts))
+}
+
+pub async fn clear_lockout(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { claims, .. }: RequirePermission<UsersManage>,
+    Json(payload): Json<ClearLockoutRequest>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let limiter = match payload.scope.as_str() {
+        "login" => &LOGIN_LIMITER,
+        "register" => &REGISTER_LIMITER,
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": "scope must be login or register"})),
+            ));
+        }
+    };
+
+    let mut redis = state.redis.clone();
+    let cleared = limiter
+        .unblock(&mut redis, &payload.subject)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, subject = %payload.subject, "Failed to clear lockout");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Redis error: {}", e)})),
+            )
+        })?;
+
+    if !cleared {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Lockout not found"})),
+        ));
+    }
+
+    tracing::info!(
+        admin_id = %claims.sub,
+        scope    = %payload.scope,
+        subject  = %payload.subject,
+        "Admin cleared lockout"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `users.manage` alone does not reach admin accounts: only an admin may
+/// grant the `Admin` role or change and delete an admin.
+fn require_admin_caller(claims: &Claims) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if roles::is_admin(&claims.role) {
+        return Ok(());
+    }
+    tracing::warn!(
+        user_id = %claims.sub,
+        role    = %claims.role,
+        "Permission denied - admin accounts need an admin (403)"
+    );
+    Err((
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({"error": "Admin access required"})),
+    ))
+}
+
 pub async fn update_user(
     State(state): State<Arc<AppState>>,
+    RequirePermission { claims, .. }: RequirePermission<UsersManage>,
+    ClientIp(client_ip): ClientIp,
     Json(payload): Json<UpdateUserRequest>,
 ) -> Result<Json<UserResponse>, (StatusCode, Json<serde_json::Value>)> {
     let mut user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
@@ -704,13 +1227,40 @@ pub async fn update_user(
             )
         })?;
 
+    if roles::is_admin(&user.role) || payload.role.as_deref().is_some_and(roles::is_admin) {
+        require_admin_caller(&claims)?;
+    }
+
     if let Some(name) = payload.name {
         user.name = name;
     }
     if let Some(email) = payload.email {
-        user.email = email;
+        if email.trim().is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": "Email cannot be empty"})),
+            ));
+        }
+        user.email = account::normalize_email(&email);
     }
+    let old_role = user.role.clone();
     if let Some(ref role) = payload.role {
+        let known = permissions::role_exists(&state.db, role)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, role = %role, "DB error checking role");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+                )
+            })?;
+        if !known {
+            tracing::warn!(user_id = %payload.id, role = %role, "Update rejected - unknown role");
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": format!("Unknown role: {role}")})),
+            ));
+        }
         tracing::info!(
             user_id  = %payload.id,
             old_role = %user.role,
@@ -720,6 +1270,7 @@ pub async fn update_user(
         user.role = role.clone();
     }
 
+    let password_changed = payload.password.is_some();
     if let Some(password) = payload.password {
         if password.trim().is_empty() {
             tracing::warn!(user_id = %payload.id, "Update rejected - empty password provided");
@@ -751,6 +1302,10 @@ pub async fn update_user(
     .fetch_one(&state.db)
     .await
     .map_err(|e| {
+        if account::is_email_taken(&e) {
+            tracing::warn!(user_id = %payload.id, "Update rejected - email already in use");
+            return account::email_taken();
+        }
         tracing::error!(
             query   = "UPDATE users SET ... WHERE id = ?",
             error   = %e,
@@ -770,6 +1325,25 @@ pub async fn update_user(
         "User updated"
     );
 
+    if updated_user.role != old_role {
+        AuditEvent::new(actions::USER_ROLE_CHANGE, &claims)
+            .target("user", payload.id)
+            .before(serde_json::json!({ "role": old_role }))
+            .after(serde_json::json!({ "role": updated_user.role }))
+            .client_ip(&client_ip)
+            .record(&state.db)
+            .await;
+    }
+
+    // A new password must lock out anyone holding the old one.
+    if password_changed {
+        if let Err(e) =
+            sessions::revoke_user_sessions(&state, payload.id, "password_change", None).await
+        {
+            tracing::error!(error = %e, user_id = %payload.id, "Failed to revoke sessions after password change");
+        }
+    }
+
     // Invalidate user cache and all JWT caches for this user after update.
     let mut redis = state.redis.clone();
     let _ = crate::cache::CacheService::invalidate_user(&mut redis, &payload.id.to_string()).await;
@@ -781,11 +1355,26 @@ pub async fn update_user(
 
 pub async fn delete_user(
     State(state): State<Arc<AppState>>,
+    RequirePermission { claims, .. }: RequirePermission<UsersManage>,
+    ClientIp(client_ip): ClientIp,
     Json(payload): Json<DeleteUserRequest>,
 ) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
-    let result = sqlx::query("DELETE FROM users WHERE id = $1")
+    let target_role = sqlx::query_scalar::<_, String>("SELECT role FROM users WHERE id = $1")
         .bind(payload.id)
-        .execute(&state.db)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %payload.id, "DB error fetching user for delete");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+    if target_role.as_deref().is_some_and(roles::is_admin) {
+        require_admin_caller(&claims)?;
+    }
+
+    let deleted = profile::delete_account(&state, payload.id, "user_deleted")
         .await
         .map_err(|e| {
             tracing::error!(
@@ -800,14 +1389,21 @@ pub async fn delete_user(
             )
         })?;
 
-    if result.rows_affected() == 0 {
+    let Some((name, email, role)) = deleted else {
         return Err((
             StatusCode::NOT_FOUND,
             Json(serde_json::json!({"error": "User not found"})),
         ));
-    }
+    };
 
     tracing::info!(user_id = %payload.id, "User deleted");
 
+    AuditEvent::new(actions::USER_DELETE, &claims)
+        .target("user", payload.id)
+        .before(serde_json::json!({ "name": name, "email": email, "role": role }))
+        .client_ip(&client_ip)
+        .record(&state.db)
+        .await;
+
     Ok(StatusCode::NO_CONTENT)
 }
@@ -1,5 +1,11 @@
+pub mod account;
 pub mod extractor;
+pub mod limiter;
 pub mod login;
 pub mod models;
+pub mod permissions;
+pub mod profile;
 pub mod roles;
 pub mod security;
+pub mod sessions;
+pub mod two_factor;
@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::FromRow;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -12,6 +13,8 @@ pub struct User {
     pub password_hash: String,
     pub role: String,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub email_verified_at: Option<DateTime<Utc>>,
     #[sqlx(default)]
     pub last_sign_on: Option<DateTime<Utc>>,
 }
@@ -22,6 +25,7 @@ pub struct UserResponse {
     pub name: String,
     pub email: String,
     pub role: String,
+    pub email_verified: bool,
     pub created_at: DateTime<Utc>,
     pub last_sign_on: Option<DateTime<Utc>>,
 }
@@ -33,6 +37,7 @@ impl From<User> for UserResponse {
             name: user.name,
             email: user.email,
             role: user.role,
+            email_verified: user.email_verified_at.is_some(),
             created_at: user.created_at,
             last_sign_on: user.last_sign_on,
         }
@@ -44,7 +49,11 @@ pub struct Claims {
     pub sub: String, // User ID
     pub name: String,
     pub role: String,
-    pub exp: usize, // Expiration time
+    pub sid: String, // Session ID, checked against the revocation list
+    pub iat: usize,  // Issued at
+    pub exp: usize,  // Expiration time
+    #[serde(default)]
+    pub mfa: bool, // Session passed two-factor authentication
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -66,6 +75,81 @@ pub struct LoginRequest {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginResponse {
+    /// Short-lived access token for `Authorization: Bearer`
+    pub token: String,
+    pub refresh_token: String,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
+}
+
+/// First login step for an account with 2FA: exchange `challenge_token` and a
+/// code at `/login/2fa` for a session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorChallenge {
+    pub two_factor_required: bool,
+    pub challenge_token: String,
+    /// Challenge lifetime in seconds
+    pub expires_in: i64,
+}
+
+/// Response of `/login`: a session, or a 2FA challenge
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LoginOutcome {
+    Session(LoginResponse),
+    TwoFactor(TwoFactorChallenge),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    /// A TOTP code or an unused recovery code
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorSetupResponse {
+    /// Base32 secret, for entering into an authenticator by hand
+    pub secret: String,
+    pub otpauth_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoveryCodesResponse {
+    /// Shown once; each code replaces one TOTP code, once
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ResendVerificationRequest {
+    /// Required without a token; ignored for signed-in callers
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct VerifyEmailRequest {
     pub token: String,
 }
 
@@ -88,6 +172,63 @@ pub struct DeleteUserRequest {
     pub id: Uuid,
 }
 
+/// Changes to the caller's own profile; omitted fields are kept
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateProfileRequest {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    /// Required when `email` changes
+    #[serde(default)]
+    pub current_password: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+}
+
+/// A client IP or account currently refused by a rate limiter
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Lockout {
+    /// Which limiter: `login` or `register`
+    pub scope: String,
+    /// `ip:<address>` or `account:<email>`
+    pub subject: String,
+    pub reason: String,
+    pub retry_after_seconds: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ClearLockoutRequest {
+    pub scope: String,
+    pub subject: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PermissionInfo {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PermissionsResponse {
+    /// Every known permission
+    pub permissions: Vec<PermissionInfo>,
+    /// Role name to granted permissions; roles without any are omitted
+    pub roles: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SetRolePermissionsRequest {
+    pub permissions: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Session {
     pub id: Uuid,
@@ -97,3 +238,18 @@ pub struct Session {
     pub user_agent: Option<String>,
     pub created_at: DateTime<Utc>,
 }
+
+/// A session that can still issue tokens, as listed to its owner
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActiveSession {
+    pub id: Uuid,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Last login or token refresh
+    pub last_active_at: DateTime<Utc>,
+    /// Expiry of the session's newest refresh token
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session of the requesting access token
+    pub current: bool,
+}
@@ -0,0 +1,382 @@
+// Named permissions and the role -> permission mapping.
+//
+// Handlers ask for a permission instead of comparing role names, either with
+// the `RequirePermission<P>` extractor or by looking up `for_role`. Which role
+// holds which permission lives in `role_permissions` and is edited by admins;
+// lookups are cached in Redis per role and fall back to the database.
+//
+// Editing the mapping itself (`/permissions`, `/roles/*`) is still gated by
+// the `Admin` role (see `admin_middleware`), so admins can always repair it.
+
+use axum::{
+    extract::{FromRequestParts, Path, State},
+    http::{request::Parts, StatusCode},
+    Json,
+};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashSet};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::audit::{actions, AuditEvent};
+use crate::auth::extractor::ClientIp;
+use crate::auth::models::{Claims, PermissionInfo, PermissionsResponse, SetRolePermissionsRequest};
+use crate::auth::roles;
+use crate::robot::models::RobotCommand;
+use crate::AppState;
+
+pub const ROBOT_VIEW: &str = "robot.view";
+pub const ROBOT_DRIVE: &str = "robot.drive";
+pub const ROBOT_NAVIGATE: &str = "robot.navigate";
+pub const ROBOT_OVERRIDE: &str = "robot.override";
+pub const ROBOT_LED: &str = "robot.led";
+pub const ROBOT_AUDIO: &str = "robot.audio";
+pub const QUEUE_MANAGE: &str = "queue.manage";
+pub const USERS_MANAGE: &str = "users.manage";
+pub const AUDIT_VIEW: &str = "audit.view";
+pub const DIARY_WRITE: &str = "diary.write";
+pub const DIARY_REPORT: &str = "diary.report";
+pub const DIARY_SETTINGS: &str = "diary.settings";
+pub const NODES_MANAGE: &str = "nodes.manage";
+pub const ROBOT_KEYS: &str = "robot.keys";
+pub const ROBOT_DEBUG: &str = "robot.debug";
+pub const ROUTES_HISTORY: &str = "routes.history";
+
+/// Every permission with what it allows, in display order
+pub const ALL: &[(&str, &str)] = &[
+    (
+        ROBOT_VIEW,
+        "Watch robot status and notifications (/ws/robot/events, /robot/notifications)",
+    ),
+    (
+        ROBOT_DRIVE,
+        "Take the manual drive lock and send drive commands while holding it",
+    ),
+    (ROBOT_NAVIGATE, "Queue routes with POST /routes/select"),
+    (
+        ROBOT_OVERRIDE,
+        "NAVIGATE/CANCEL over the manual socket, take over other users' locks, drive without a lock",
+    ),
+    (ROBOT_LED, "Send LED and LED_AUTO commands"),
+    (ROBOT_AUDIO, "Send audio commands and stream audio"),
+    (
+        QUEUE_MANAGE,
+        "Add, delete and reorder queued routes (/routes, /routes/optimize)",
+    ),
+    (USERS_MANAGE, "List, update and delete users and their sessions"),
+    (AUDIT_VIEW, "Search the audit log (GET /audit)"),
+    (DIARY_WRITE, "Create, update and delete own diary entries"),
+    (
+        DIARY_REPORT,
+        "Working-time reports across all users (GET /diary/report)",
+    ),
+    (
+        DIARY_SETTINGS,
+        "Turn the anonymous diary feed on or off (/diary/settings)",
+    ),
+    (
+        NODES_MANAGE,
+        "Edit navigation nodes and the route graph (/nodes/*, /graph/*)",
+    ),
+    (ROBOT_KEYS, "Issue, list and revoke robot API keys"),
+    (
+        ROBOT_DEBUG,
+        "Inspect the robot's internal state (GET /robot/debug)",
+    ),
+    (
+        ROUTES_HISTORY,
+        "Past routes and delivery statistics (/routes/history, /routes/stats)",
+    ),
+];
+
+/// Permissions that, with `REQUIRE_ADMIN_2FA`, also need a session that
+/// passed two-factor authentication, like the admin route group
+pub const TWO_FACTOR: &[&str] = &[
+    USERS_MANAGE,
+    AUDIT_VIEW,
+    DIARY_SETTINGS,
+    NODES_MANAGE,
+    ROBOT_KEYS,
+    ROBOT_DEBUG,
+    ROUTES_HISTORY,
+];
+
+/// Whether `name` is a known permission
+pub fn is_known(name: &str) -> bool {
+    ALL.iter().any(|(known, _)| *known == name)
+}
+
+/// The permissions of one role
+#[derive(Debug, Clone, Default)]
+pub struct Permissions(HashSet<String>);
+
+impl Permissions {
+    pub fn contains(&self, permission: &str) -> bool {
+        self.0.contains(permission)
+    }
+}
+
+/// Permissions granted to `role`. A lookup failure grants nothing.
+pub async fn for_role(state: &AppState, role: &str) -> Permissions {
+    let mut redis = state.redis.clone();
+    if let Ok(Some(cached)) =
+        crate::cache::CacheService::get_role_permissions::<Vec<String>>(&mut redis, role).await
+    {
+        return Permissions(cached.into_iter().collect());
+    }
+
+    let granted = match sqlx::query_scalar::<_, String>(
+        "SELECT permission FROM role_permissions WHERE role = $1",
+    )
+    .bind(role)
+    .fetch_all(&state.db)
+    .await
+    {
+        Ok(granted) => granted,
+        Err(e) => {
+            tracing::error!(role = %role, error = %e, "DB error loading role permissions");
+            return Permissions::default();
+        }
+    };
+
+    let _ = crate::cache::CacheService::cache_role_permissions(&mut redis, role, &granted).await;
+    Permissions(granted.into_iter().collect())
+}
+
+/// Whether `role` can be assigned to users: a built-in role or one holding
+/// any permission.
+pub async fn role_exists(db: &PgPool, role: &str) -> Result<bool, sqlx::Error> {
+    if [roles::ADMIN, roles::OPERATOR, roles::VIEWER].contains(&role) {
+        return Ok(true);
+    }
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM role_permissions WHERE role = $1)")
+        .bind(role)
+        .fetch_one(db)
+        .await
+}
+
+/// A permission checked by [`RequirePermission`]
+pub trait Permission: Send + Sync + 'static {
+    const NAME: &'static str;
+}
+
+macro_rules! permission_markers {
+    ($($marker:ident => $name:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Marker for `", stringify!($name), "`")]
+            pub struct $marker;
+
+            impl Permission for $marker {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+permission_markers! {
+    RobotView => ROBOT_VIEW,
+    RobotDrive => ROBOT_DRIVE,
+    RobotNavigate => ROBOT_NAVIGATE,
+    QueueManage => QUEUE_MANAGE,
+    UsersManage => USERS_MANAGE,
+    AuditView => AUDIT_VIEW,
+    DiaryWrite => DIARY_WRITE,
+    DiarySettings => DIARY_SETTINGS,
+    NodesManage => NODES_MANAGE,
+    RobotKeys => ROBOT_KEYS,
+    RobotDebug => ROBOT_DEBUG,
+    RoutesHistory => ROUTES_HISTORY,
+}
+
+/// Claims of an authenticated user whose role holds `P`; responds `403`
+/// otherwise. Requires the auth middleware to have run.
+pub struct RequirePermission<P: Permission> {
+    pub claims: Claims,
+    /// Everything the user's role holds, for finer checks in the handler
+    pub permissions: Permissions,
+    _permission: PhantomData<P>,
+}
+
+impl<P: Permission> FromRequestParts<Arc<AppState>> for RequirePermission<P> {
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = parts.extensions.get::<Claims>().cloned().ok_or_else(|| {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({"error": "No authentication information"})),
+            )
+        })?;
+
+        let permissions = for_role(state, &claims.role).await;
+        if !permissions.contains(P::NAME) {
+            tracing::warn!(
+                user_id    = %claims.sub,
+                name       = %claims.name,
+                role       = %claims.role,
+                permission = P::NAME,
+                method     = %parts.method,
+                path       = %parts.uri.path(),
+                "Permission denied (403)"
+            );
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "error": "Insufficient permissions",
+                    "permission": P::NAME
+                })),
+            ));
+        }
+
+        if state.config.require_admin_2fa && TWO_FACTOR.contains(&P::NAME) && !claims.mfa {
+            tracing::warn!(
+                user_id    = %claims.sub,
+                name       = %claims.name,
+                permission = P::NAME,
+                method     = %parts.method,
+                path       = %parts.uri.path(),
+                "Permission denied - session without two-factor authentication (403)"
+            );
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "error": "Two-factor authentication required for admin access",
+                    "permission": P::NAME
+                })),
+            ));
+        }
+
+        Ok(Self {
+            claims,
+            permissions,
+            _permission: PhantomData,
+        })
+    }
+}
+
+async fn role_mapping(
+    state: &AppState,
+) -> Result<BTreeMap<String, Vec<String>>, (StatusCode, Json<serde_json::Value>)> {
+    let rows = sqlx::query_as::<_, (String, String)>(
+        "SELECT role, permission FROM role_permissions ORDER BY role, permission",
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "DB error listing role permissions");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to load role permissions"})),
+        )
+    })?;
+
+    let mut roles: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (role, permission) in rows {
+        roles.entry(role).or_default().push(permission);
+    }
+    Ok(roles)
+}
+
+/// Known permissions and the current role mapping.
+pub async fn list_permissions(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PermissionsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    Ok(Json(PermissionsResponse {
+        permissions: ALL
+            .iter()
+            .map(|(name, description)| PermissionInfo {
+                name: name.to_string(),
+                description: description.to_string(),
+            })
+            .collect(),
+        roles: role_mapping(&state).await?,
+    }))
+}
+
+/// Replace the permissions of a role.
+pub async fn set_role_permissions(
+    State(state): State<Arc<AppState>>,
+    Path(role): Path<String>,
+    crate::auth::extractor::AuthenticatedUser(claims): crate::auth::extractor::AuthenticatedUser,
+    ClientIp(client_ip): ClientIp,
+    Json(payload): Json<SetRolePermissionsRequest>,
+) -> Result<Json<Vec<String>>, (StatusCode, Json<serde_json::Value>)> {
+    let role = role.trim().to_string();
+    if role.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Role cannot be empty"})),
+        ));
+    }
+    if let Some(unknown) = payload.permissions.iter().find(|p| !is_known(p)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("Unknown permission: {unknown}")})),
+        ));
+    }
+
+    let mut granted = payload.permissions;
+    granted.sort();
+    granted.dedup();
+
+    let db_error = |e: sqlx::Error| {
+        tracing::error!(role = %role, error = %e, "DB error updating role permissions");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to update role permissions"})),
+        )
+    };
+
+    let mut tx = state.db.begin().await.map_err(db_error)?;
+    let previous = sqlx::query_scalar::<_, String>(
+        "DELETE FROM role_permissions WHERE role = $1 RETURNING permission",
+    )
+    .bind(&role)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    sqlx::query("INSERT INTO role_permissions (role, permission) SELECT $1, UNNEST($2::text[])")
+        .bind(&role)
+        .bind(&granted)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    let mut redis = state.redis.clone();
+    let _ = crate::cache::CacheService::invalidate_role_permissions(&mut redis, &role).await;
+
+    let mut previous = previous;
+    previous.sort();
+    tracing::info!(
+        role        = %role,
+        permissions = ?granted,
+        changed_by  = %claims.name,
+        "Role permissions updated"
+    );
+    AuditEvent::new(actions::ROLE_PERMISSIONS_CHANGE, &claims)
+        .target("role", &role)
+        .before(&previous)
+        .after(&granted)
+        .client_ip(&client_ip)
+        .record(&state.db)
+        .await;
+
+    Ok(Json(granted))
+}
+
+/// The permission needed to send `cmd` over the manual control socket
+pub fn for_command(cmd: &RobotCommand) -> &'static str {
+    match cmd {
+        RobotCommand::Navigate { .. } | RobotCommand::Cancel => ROBOT_OVERRIDE,
+        RobotCommand::DriveCommand { .. } | RobotCommand::SetManualSpeedCap { .. } => ROBOT_DRIVE,
+        RobotCommand::Led { .. } | RobotCommand::LedAuto { .. } => ROBOT_LED,
+        RobotCommand::AudioBeep { .. }
+        | RobotCommand::AudioVolume { .. }
+        | RobotCommand::AudioStreamStart { .. }
+        | RobotCommand::AudioStreamStop => ROBOT_AUDIO,
+    }
+}
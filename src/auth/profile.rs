@@ -0,0 +1,341 @@
+// Self-service account management for the authenticated user.
+//
+// `PATCH /me` edits name and email; a new email address starts unverified and
+// gets a fresh verification link, and the old one is told about the change.
+// Changing the email, `POST /me/password` and `DELETE /me` ask for the current
+// password again, so a stolen access token alone cannot lock the owner out,
+// take the account over through a password reset, or erase it.
+
+use axum::{extract::State, http::StatusCode, Json};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::audit::{actions, AuditEvent};
+use crate::auth::{
+    account,
+    extractor::{AuthenticatedUser, ClientIp},
+    login::claims_user_id,
+    models::{
+        ChangePasswordRequest, DeleteAccountRequest, UpdateProfileRequest, User, UserResponse,
+    },
+    roles,
+    security::{hash_password, verify_password},
+    sessions,
+};
+use crate::mail::Email;
+use crate::AppState;
+
+fn database_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+    )
+}
+
+async fn load_user(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<User, (StatusCode, Json<serde_json::Value>)> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user_id, "DB error loading own account");
+            database_error(e)
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": "User not found"})),
+            )
+        })
+}
+
+/// Check `password` against the stored hash; `403` if it does not match.
+async fn confirm_password(
+    user: &User,
+    password: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let matches = verify_password(password, &user.password_hash)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user.id, "Password verification failed");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Password verification error"})),
+            )
+        })?;
+
+    if !matches {
+        tracing::warn!(user_id = %user.id, "Account change rejected - wrong current password (403)");
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": "Current password is incorrect"})),
+        ));
+    }
+    Ok(())
+}
+
+async fn invalidate_user_caches(state: &AppState, user_id: Uuid) {
+    let mut redis = state.redis.clone();
+    let _ = crate::cache::CacheService::invalidate_user(&mut redis, &user_id.to_string()).await;
+    let _ =
+        crate::cache::CacheService::invalidate_user_jwts(&mut redis, &user_id.to_string()).await;
+}
+
+/// Delete an account, revoking its sessions first so their access tokens land
+/// on the revocation list. Diary entries are deleted with it, or kept without
+/// an owner under `RETAIN_DIARY_ON_ACCOUNT_DELETION`. Returns the deleted
+/// name, email and role, or `None` if there was no such user.
+pub(crate) async fn delete_account(
+    state: &AppState,
+    user_id: Uuid,
+    reason: &str,
+) -> Result<Option<(String, String, String)>, sqlx::Error> {
+    if let Err(e) = sessions::revoke_user_sessions(state, user_id, reason, None).await {
+        tracing::error!(error = %e, user_id = %user_id, "Failed to revoke sessions of deleted user");
+    }
+
+    let mut tx = state.db.begin().await?;
+    if state.config.retain_diary_on_account_deletion {
+        sqlx::query("UPDATE diary_entries SET owner = NULL WHERE owner = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let deleted = sqlx::query_as::<_, (String, String, String)>(
+        "DELETE FROM users WHERE id = $1 RETURNING name, email, role",
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    if deleted.is_some() {
+        invalidate_user_caches(state, user_id).await;
+        let mut redis = state.redis.clone();
+        let _ =
+            crate::cache::CacheService::invalidate_user_diaries(&mut redis, &user_id.to_string())
+                .await;
+    }
+    Ok(deleted)
+}
+
+pub async fn update_me(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Json(payload): Json<UpdateProfileRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = claims_user_id(&claims.sub)?;
+    let mut user = load_user(&state, user_id).await?;
+
+    if let Some(name) = payload.name {
+        if name.trim().is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": "Name cannot be empty"})),
+            ));
+        }
+        user.name = name;
+    }
+
+    let previous_email = user.email.clone();
+    let email_changed = match payload.email {
+        Some(email) if account::normalize_email(&email) != user.email => {
+            if email.trim().is_empty() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": "Email cannot be empty"})),
+                ));
+            }
+            let Some(current_password) = payload.current_password.as_deref() else {
+                tracing::warn!(user_id = %user_id, "Email change rejected - current password missing (403)");
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    Json(serde_json::json!({
+                        "error": "Current password is required to change the email address"
+                    })),
+                ));
+            };
+            confirm_password(&user, current_password).await?;
+            let taken = sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM users WHERE LOWER(email) = $1 AND id <> $2)",
+            )
+            .bind(account::normalize_email(&email))
+            .bind(user_id)
+            .fetch_one(&state.db)
+            .await
+            .map_err(database_error)?;
+            if taken {
+                tracing::warn!(user_id = %user_id, "Profile update rejected - email already in use");
+                return Err(account::email_taken());
+            }
+            user.email = account::normalize_email(&email);
+            true
+        }
+        _ => false,
+    };
+
+    // A new address is unverified until its link is followed; pending reset
+    // and verification links stay bound to the old one.
+    let updated = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET name = $1,
+            email = $2,
+            email_verified_at = CASE WHEN $3 THEN NULL ELSE email_verified_at END
+        WHERE id = $4
+        RETURNING *
+        "#,
+    )
+    .bind(&user.name)
+    .bind(&user.email)
+    .bind(email_changed)
+    .bind(user_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| {
+        if account::is_email_taken(&e) {
+            tracing::warn!(user_id = %user_id, "Profile update rejected - email already in use");
+            return account::email_taken();
+        }
+        tracing::error!(error = %e, user_id = %user_id, "DB error updating own profile");
+        database_error(e)
+    })?;
+
+    invalidate_user_caches(&state, user_id).await;
+    tracing::info!(user_id = %user_id, email_changed, "Profile updated");
+
+    if email_changed {
+        account::send_verification_email(&state, &updated).await;
+        notify_email_change(&state, &updated, &previous_email).await;
+    }
+
+    Ok(Json(updated.into()))
+}
+
+/// Tell the previous address that the account moved, so an owner whose token
+/// was misused finds out. Failures are logged; the change is already made.
+async fn notify_email_change(state: &AppState, user: &User, previous_email: &str) {
+    let email = Email {
+        to: previous_email.to_string(),
+        subject: "Your email address was changed".to_string(),
+        body: format!(
+            "Hi {},\n\nthe email address of your account was changed from {} to {}.\n\nIf you did not make this change, contact an administrator right away.\n",
+            user.name, previous_email, user.email
+        ),
+    };
+
+    if let Err(e) = state.mailer.send(email).await {
+        tracing::error!(error = %e, user_id = %user.id, "Failed to send email change notice");
+    }
+}
+
+pub async fn change_password(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = claims_user_id(&claims.sub)?;
+    if payload.new_password.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Password must not be empty"})),
+        ));
+    }
+
+    let user = load_user(&state, user_id).await?;
+    confirm_password(&user, &payload.current_password).await?;
+
+    let password_hash = hash_password(&payload.new_password).await.map_err(|e| {
+        tracing::error!(error = %e, user_id = %user_id, "Password hashing failed during password change");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Password hashing error: {}", e)})),
+        )
+    })?;
+
+    sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+        .bind(&password_hash)
+        .bind(user_id)
+        .execute(&state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user_id, "DB error changing password");
+            database_error(e)
+        })?;
+
+    // Everyone holding the old password is signed out; this session stays.
+    let current = Uuid::parse_str(&claims.sid).ok();
+    let revoked = sessions::revoke_user_sessions(&state, user_id, "password_change", current)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user_id, "Failed to revoke sessions after password change");
+            database_error(e)
+        })?;
+
+    invalidate_user_caches(&state, user_id).await;
+    tracing::info!(
+        user_id          = %user_id,
+        revoked_sessions = revoked.len(),
+        "Password changed"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn delete_me(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    ClientIp(client_ip): ClientIp,
+    Json(payload): Json<DeleteAccountRequest>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = claims_user_id(&claims.sub)?;
+    let user = load_user(&state, user_id).await?;
+    confirm_password(&user, &payload.password).await?;
+
+    if roles::is_admin(&user.role) {
+        let other_admins = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM users WHERE role = $1 AND id <> $2)",
+        )
+        .bind(roles::ADMIN)
+        .bind(user_id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(database_error)?;
+        if !other_admins {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(serde_json::json!({"error": "The last admin cannot delete their account"})),
+            ));
+        }
+    }
+
+    let deleted = delete_account(&state, user_id, "account_deleted")
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user_id, "DB error deleting own account");
+            database_error(e)
+        })?;
+    let Some((name, email, role)) = deleted else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "User not found"})),
+        ));
+    };
+
+    tracing::info!(
+        user_id      = %user_id,
+        diary_policy = if state.config.retain_diary_on_account_deletion { "retain" } else { "delete" },
+        "Account deleted by its owner"
+    );
+    AuditEvent::new(actions::USER_DELETE, &claims)
+        .target("user", user_id)
+        .before(serde_json::json!({ "name": name, "email": email, "role": role }))
+        .client_ip(&client_ip)
+        .record(&state.db)
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
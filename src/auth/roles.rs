@@ -1,3 +1,6 @@
+// Built-in role names. What each role may do is stored in `role_permissions`
+// (see `permissions`); only the admin route group still checks the name.
+
 pub const ADMIN: &str = "Admin";
 pub const OPERATOR: &str = "Operator";
 pub const VIEWER: &str = "Viewer";
@@ -5,15 +8,3 @@ pub const VIEWER: &str = "Viewer";
 pub fn is_admin(role: &str) -> bool {
     role == ADMIN
 }
-
-pub fn is_operator(role: &str) -> bool {
-    role == OPERATOR
-}
-
-pub fn can_operate(role: &str) -> bool {
-    role == ADMIN || role == OPERATOR
-}
-
-pub fn can_view(role: &str) -> bool {
-    role == ADMIN || role == OPERATOR || role == VIEWER
-}
@@ -41,15 +41,18 @@ pub async fn verify_password(password: &str, hash: &str) -> Result<bool, bcrypt:
     }
 }
 
-pub fn create_jwt(
+pub fn create_access_token(
     user_id: &str,
     name: &str,
     role: &str,
+    session_id: &str,
+    two_factor: bool,
     secret: &str,
-    expiry_hours: i64,
+    ttl: chrono::Duration,
 ) -> Result<String, jsonwebtoken::errors::Error> {
-    let expiration = chrono::Utc::now()
-        .checked_add_signed(chrono::Duration::hours(expiry_hours))
+    let now = chrono::Utc::now();
+    let expiration = now
+        .checked_add_signed(ttl)
         .expect("valid timestamp")
         .timestamp() as usize;
 
@@ -57,7 +60,10 @@ pub fn create_jwt(
         sub: user_id.to_string(),
         name: name.to_string(),
         role: role.to_string(),
+        sid: session_id.to_string(),
+        iat: now.timestamp() as usize,
         exp: expiration,
+        mfa: two_factor,
     };
 
     encode(
@@ -119,12 +125,23 @@ pub async fn auth_middleware(
     let mut claims = if let Ok(Some(cached_claims)) =
         crate::cache::CacheService::get_jwt_validation(&mut redis, &token_hash).await
     {
-        serde_json::from_str(&cached_claims).map_err(|_| {
+        let claims: Claims = serde_json::from_str(&cached_claims).map_err(|_| {
             (
                 StatusCode::UNAUTHORIZED,
                 Json(json!({"error": "Invalid cached token"})),
             )
-        })?
+        })?;
+
+        // The cache outlives short access tokens, so expiry is checked here too.
+        if claims.exp < chrono::Utc::now().timestamp() as usize {
+            tracing::debug!(method = %method, path = %path, "Expired cached JWT token (401)");
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(json!({"error": "Invalid or expired token"})),
+            ));
+        }
+
+        claims
     } else {
         let claims = decode_jwt(token, &state.config.jwt_secret).map_err(|e| {
             tracing::warn!(
@@ -153,6 +170,20 @@ pub async fn auth_middleware(
         claims
     };
 
+    if crate::auth::sessions::is_revoked(&state, &claims.sid).await {
+        tracing::warn!(
+            user_id    = %claims.sub,
+            session_id = %claims.sid,
+            method     = %method,
+            path       = %path,
+            "Token of revoked session rejected (401)"
+        );
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "Session has been revoked"})),
+        ));
+    }
+
     // Always fetch the current role from the database to ensure role changes
     // take effect immediately, even if the JWT still contains the old role.
     if let Ok(user_id) = uuid::Uuid::parse_str(&claims.sub) {
@@ -193,18 +224,43 @@ pub async fn auth_middleware(
     Ok(next.run(req).await)
 }
 
-pub async fn admin_middleware(req: Request, next: Next) -> Result<Response, impl IntoResponse> {
+/// Like `auth_middleware` for routes that also serve anonymous callers: a
+/// request without an `Authorization` header passes through without claims,
+/// while a presented token must be valid.
+pub async fn optional_auth_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !req.headers().contains_key(header::AUTHORIZATION) {
+        return next.run(req).await;
+    }
+    auth_middleware(State(state), req, next)
+        .await
+        .into_response()
+}
+
+pub async fn admin_middleware(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, impl IntoResponse> {
     let path = req.uri().path().to_string();
     let method = req.method().to_string();
 
-    let (user_id, name, role) = {
+    let (user_id, name, role, two_factor) = {
         let claims = req.extensions().get::<Claims>().ok_or_else(|| {
             (
                 StatusCode::UNAUTHORIZED,
                 Json(json!({"error": "No authentication information found"})),
             )
         })?;
-        (claims.sub.clone(), claims.name.clone(), claims.role.clone())
+        (
+            claims.sub.clone(),
+            claims.name.clone(),
+            claims.role.clone(),
+            claims.mfa,
+        )
     };
 
     if !roles::is_admin(&role) {
@@ -222,6 +278,20 @@ pub async fn admin_middleware(req: Request, next: Next) -> Result<Response, impl
         ));
     }
 
+    if state.config.require_admin_2fa && !two_factor {
+        tracing::warn!(
+            user_id = %user_id,
+            name    = %name,
+            method  = %method,
+            path    = %path,
+            "Permission denied - admin session without two-factor authentication (403)"
+        );
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": "Two-factor authentication required for admin access"})),
+        ));
+    }
+
     Ok(next.run(req).await)
 }
 
@@ -249,14 +319,24 @@ mod tests {
         let user_id = "123-456";
         let name = "Test User";
         let role = "Viewer";
-        let expiry = 1;
-
-        let token = create_jwt(user_id, name, role, secret, expiry).expect("creation failed");
+        let sid = uuid::Uuid::new_v4().to_string();
+
+        let token = create_access_token(
+            user_id,
+            name,
+            role,
+            &sid,
+            false,
+            secret,
+            chrono::Duration::hours(1),
+        )
+        .expect("creation failed");
         let claims = decode_jwt(&token, secret).expect("decoding failed");
 
         assert_eq!(claims.sub, user_id);
         assert_eq!(claims.name, name);
         assert_eq!(claims.role, role);
+        assert_eq!(claims.sid, sid);
     }
 
     #[test]
@@ -267,7 +347,10 @@ mod tests {
             sub: "123".to_string(),
             name: "test".to_string(),
             role: "Viewer".to_string(),
+            sid: uuid::Uuid::new_v4().to_string(),
+            iat: (chrono::Utc::now().timestamp() - 7200) as usize,
             exp: (chrono::Utc::now().timestamp() - 3600) as usize, // 1 hour ago
+            mfa: false,
         };
 
         let token = encode(
@@ -0,0 +1,335 @@
+// Login sessions: rotating refresh tokens and revocation.
+//
+// Every login creates a `sessions` row. Access tokens are short-lived JWTs
+// carrying the session id (`sid`); refresh tokens are random strings stored as
+// SHA-256 hashes in `refresh_tokens`. Revoking a session marks the row in the
+// database and adds its id to a Redis revocation list, which the auth
+// middleware and the WebSocket token checks consult on every request. Entries
+// only need to outlive the access tokens issued for the session.
+
+use crate::auth::models::{ActiveSession, LoginResponse, User};
+use crate::auth::security::create_access_token;
+use crate::AppState;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// Marks refresh tokens so they are recognisable in logs and bug reports
+const REFRESH_TOKEN_TAG: &str = "rt_";
+/// Clock skew tolerated by `jsonwebtoken` when validating `exp`
+const JWT_LEEWAY_SECS: i64 = 60;
+
+/// Result of presenting a refresh token
+pub enum RefreshOutcome {
+    /// The token was valid and has been replaced by `refresh_token`
+    Rotated {
+        user: User,
+        session_id: Uuid,
+        two_factor: bool,
+        refresh_token: String,
+    },
+    /// Unknown, expired, or belonging to a revoked session
+    Invalid,
+    /// The token had already been used; its session has been revoked
+    Reused,
+}
+
+/// SHA-256 of a refresh or account token, hex encoded
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// 256 random bits, hex encoded behind `tag`
+pub(crate) fn generate_token(tag: &str) -> String {
+    let bytes: [u8; 32] = rand::random();
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!("{tag}{hex}")
+}
+
+/// Store a new refresh token for `session_id` and return it.
+pub async fn insert_refresh_token(
+    tx: &mut Transaction<'_, Postgres>,
+    session_id: Uuid,
+    ttl_days: i64,
+) -> Result<String, sqlx::Error> {
+    let token = generate_token(REFRESH_TOKEN_TAG);
+
+    sqlx::query(
+        r#"
+        INSERT INTO refresh_tokens (session_id, token_hash, expires_at)
+        VALUES ($1, $2, NOW() + make_interval(days => $3))
+        "#,
+    )
+    .bind(session_id)
+    .bind(hash_token(&token))
+    .bind(ttl_days as i32)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(token)
+}
+
+/// Build the login/refresh response: a fresh access token for `user` in
+/// `session_id`, plus the given refresh token. `two_factor` records whether the
+/// session passed two-factor authentication.
+pub fn token_response(
+    state: &AppState,
+    user: &User,
+    session_id: Uuid,
+    two_factor: bool,
+    refresh_token: String,
+) -> Result<LoginResponse, jsonwebtoken::errors::Error> {
+    let ttl = chrono::Duration::minutes(state.config.access_token_ttl_minutes);
+    let token = create_access_token(
+        &user.id.to_string(),
+        &user.name,
+        &user.role,
+        &session_id.to_string(),
+        two_factor,
+        &state.config.jwt_secret,
+        ttl,
+    )?;
+
+    Ok(LoginResponse {
+        token,
+        refresh_token,
+        expires_in: ttl.num_seconds(),
+    })
+}
+
+/// Exchange a refresh token for a new one in the same session.
+pub async fn rotate(state: &AppState, refresh_token: &str) -> Result<RefreshOutcome, sqlx::Error> {
+    let mut tx = state.db.begin().await?;
+
+    // Lock the token row so two concurrent refreshes cannot both rotate it.
+    let row = sqlx::query_as::<_, (Uuid, Uuid, bool, bool, bool, bool)>(
+        r#"
+        SELECT
+            rt.id,
+            rt.session_id,
+            rt.used_at IS NOT NULL,
+            rt.expires_at <= NOW(),
+            s.revoked_at IS NOT NULL,
+            s.two_factor_verified
+        FROM refresh_tokens rt
+        JOIN sessions s ON s.id = rt.session_id
+        WHERE rt.token_hash = $1
+        FOR UPDATE OF rt
+        "#,
+    )
+    .bind(hash_token(refresh_token))
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some((token_id, session_id, used, expired, revoked, two_factor)) = row else {
+        return Ok(RefreshOutcome::Invalid);
+    };
+
+    if revoked || expired {
+        return Ok(RefreshOutcome::Invalid);
+    }
+
+    if used {
+        // A rotated-out token came back: either the client or an attacker
+        // holds a stolen copy, so neither may keep the session.
+        tx.rollback().await?;
+        revoke_session(state, session_id, "refresh_token_reuse").await?;
+        tracing::warn!(session_id = %session_id, "Refresh token reuse detected - session revoked");
+        return Ok(RefreshOutcome::Reused);
+    }
+
+    sqlx::query("UPDATE refresh_tokens SET used_at = NOW() WHERE id = $1")
+        .bind(token_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let user = sqlx::query_as::<_, User>(
+        "SELECT u.* FROM users u JOIN sessions s ON s.user_id = u.id WHERE s.id = $1",
+    )
+    .bind(session_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let refresh_token =
+        insert_refresh_token(&mut tx, session_id, state.config.refresh_token_ttl_days).await?;
+    tx.commit().await?;
+
+    Ok(RefreshOutcome::Rotated {
+        user,
+        session_id,
+        two_factor,
+        refresh_token,
+    })
+}
+
+/// Revoke one session. Returns false if it did not exist or was already revoked.
+pub async fn revoke_session(
+    state: &AppState,
+    session_id: Uuid,
+    reason: &str,
+) -> Result<bool, sqlx::Error> {
+    let revoked = sqlx::query(
+        r#"
+        UPDATE sessions
+        SET revoked_at = NOW(), revoked_reason = $2
+        WHERE id = $1 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(session_id)
+    .bind(reason)
+    .execute(&state.db)
+    .await?
+    .rows_affected()
+        > 0;
+
+    deny_sessions(state, &[session_id]).await;
+    Ok(revoked)
+}
+
+/// Revoke one session of `user_id`. Returns false if the user has no such
+/// unrevoked session.
+pub async fn revoke_user_session(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Uuid,
+    reason: &str,
+) -> Result<bool, sqlx::Error> {
+    let revoked = sqlx::query(
+        r#"
+        UPDATE sessions
+        SET revoked_at = NOW(), revoked_reason = $3
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .bind(reason)
+    .execute(&state.db)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if revoked {
+        deny_sessions(state, &[session_id]).await;
+    }
+    Ok(revoked)
+}
+
+/// Revoke every active session of a user, except `keep` if given. Returns the
+/// revoked session ids.
+pub async fn revoke_user_sessions(
+    state: &AppState,
+    user_id: Uuid,
+    reason: &str,
+    keep: Option<Uuid>,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let revoked = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        UPDATE sessions
+        SET revoked_at = NOW(), revoked_reason = $2
+        WHERE user_id = $1
+          AND revoked_at IS NULL
+          AND ($3::uuid IS NULL OR id <> $3)
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(reason)
+    .bind(keep)
+    .fetch_all(&state.db)
+    .await?;
+
+    deny_sessions(state, &revoked).await;
+    Ok(revoked)
+}
+
+/// Sessions of `user_id` that are not revoked and hold an unexpired refresh
+/// token, most recently active first. `current` marks the caller's session.
+pub async fn list_active(
+    db: &PgPool,
+    user_id: Uuid,
+    current: Option<Uuid>,
+) -> Result<Vec<ActiveSession>, sqlx::Error> {
+    sqlx::query_as::<_, ActiveSession>(
+        r#"
+        SELECT
+            s.id,
+            s.ip_address,
+            s.user_agent,
+            s.created_at,
+            MAX(rt.created_at) AS last_active_at,
+            MAX(rt.expires_at) AS expires_at,
+            COALESCE(s.id = $2, FALSE) AS current
+        FROM sessions s
+        JOIN refresh_tokens rt ON rt.session_id = s.id
+        WHERE s.user_id = $1 AND s.revoked_at IS NULL
+        GROUP BY s.id
+        HAVING MAX(rt.expires_at) > NOW()
+        ORDER BY last_active_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(current)
+    .fetch_all(db)
+    .await
+}
+
+/// Add sessions to the Redis revocation list for as long as their access
+/// tokens can still be valid, and close their open WebSockets.
+async fn deny_sessions(state: &AppState, session_ids: &[Uuid]) {
+    for session_id in session_ids {
+        // No receivers just means no sockets are open.
+        let _ = state.robot_state.session_revoked_sender.send(*session_id);
+    }
+
+    let ttl_secs = (state.config.access_token_ttl_minutes * 60 + JWT_LEEWAY_SECS) as u64;
+    let mut redis = state.redis.clone();
+
+    for session_id in session_ids {
+        if let Err(e) = crate::cache::CacheService::revoke_session(
+            &mut redis,
+            &session_id.to_string(),
+            ttl_secs,
+        )
+        .await
+        {
+            tracing::error!(
+                session_id = %session_id,
+                error      = %e,
+                "Failed to add session to revocation list"
+            );
+        }
+    }
+}
+
+/// Whether access tokens of session `sid` must be rejected. Checks the Redis
+/// revocation list and falls back to the database if Redis is unavailable.
+pub async fn is_revoked(state: &AppState, sid: &str) -> bool {
+    let Ok(session_id) = Uuid::parse_str(sid) else {
+        return true;
+    };
+
+    let mut redis = state.redis.clone();
+    match crate::cache::CacheService::is_session_revoked(&mut redis, sid).await {
+        Ok(revoked) => revoked,
+        Err(e) => {
+            tracing::warn!(error = %e, "Revocation list unavailable - checking database");
+            is_revoked_in_db(&state.db, session_id).await
+        }
+    }
+}
+
+async fn is_revoked_in_db(db: &PgPool, session_id: Uuid) -> bool {
+    match sqlx::query_scalar::<_, bool>("SELECT revoked_at IS NOT NULL FROM sessions WHERE id = $1")
+        .bind(session_id)
+        .fetch_optional(db)
+        .await
+    {
+        Ok(revoked) => revoked.unwrap_or(false),
+        Err(e) => {
+            // Neither store can vouch for the session; fail closed.
+            tracing::error!(error = %e, session_id = %session_id, "Failed to check session revocation");
+            true
+        }
+    }
+}
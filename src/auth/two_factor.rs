@@ -0,0 +1,534 @@
+// TOTP two-factor authentication (RFC 6238) and recovery codes.
+//
+// Enrollment is two steps: `setup` stores a fresh secret and returns it as an
+// otpauth:// URL for the authenticator app, and `verify` turns 2FA on once the
+// app produces a matching code, returning a batch of single-use recovery
+// codes. Only SHA-256 hashes of recovery codes are stored.
+//
+// With 2FA enabled, `/login` stops after the password check and returns a
+// short-lived challenge token; `/login/2fa` exchanges it plus a TOTP or
+// recovery code for a session. Sessions remember whether they passed the
+// second factor, and access tokens carry that as the `mfa` claim so admin
+// routes and privileged permissions can insist on it (`REQUIRE_ADMIN_2FA`).
+
+use axum::{extract::State, http::HeaderMap, http::StatusCode, Json};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::sync::Arc;
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+use crate::auth::{
+    extractor::AuthenticatedUser,
+    login::{
+        self, claims_user_id, extract_client_ip, extract_user_agent, start_session,
+        MaybeConnectInfo,
+    },
+    models::{
+        LoginResponse, RecoveryCodesResponse, TwoFactorChallenge, TwoFactorCodeRequest,
+        TwoFactorLoginRequest, TwoFactorSetupResponse, User,
+    },
+    sessions::{self, hash_token},
+};
+use crate::AppState;
+
+const TOTP_ISSUER: &str = "TeleTable";
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP_SECS: u64 = 30;
+/// Codes from one step either side of now are accepted, for clock drift
+const TOTP_SKEW_STEPS: u64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+/// Lifetime of the challenge between password and second factor
+const LOGIN_CHALLENGE_TTL_SECS: i64 = 300;
+/// Wrong codes allowed per challenge before the password must be entered again
+const LOGIN_CHALLENGE_MAX_ATTEMPTS: i32 = 5;
+/// Marks challenge tokens so they are recognisable in logs and bug reports
+const LOGIN_CHALLENGE_TAG: &str = "mfa_";
+
+fn database_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+    )
+}
+
+fn invalid_code() -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(serde_json::json!({"error": "Invalid two-factor code"})),
+    )
+}
+
+fn totp(secret: &str, account: &str) -> Option<TOTP> {
+    let secret = Secret::Encoded(secret.to_string()).to_bytes().ok()?;
+    // The otpauth label uses ':' to separate issuer and account.
+    TOTP::new(
+        Algorithm::SHA1,
+        TOTP_DIGITS,
+        TOTP_SKEW_STEPS as u8,
+        TOTP_STEP_SECS,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        account.replace(':', "_"),
+    )
+    .ok()
+}
+
+/// The time step `code` was generated for, if it matches one near now
+fn matching_step(totp: &TOTP, code: &str) -> Option<i64> {
+    let now = chrono::Utc::now().timestamp() as u64;
+    let current = now / TOTP_STEP_SECS;
+
+    (current.saturating_sub(TOTP_SKEW_STEPS)..=current + TOTP_SKEW_STEPS)
+        .find(|step| {
+            let expected = totp.generate(step * TOTP_STEP_SECS);
+            constant_time_eq(expected.as_bytes(), code.as_bytes())
+        })
+        .map(|step| step as i64)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn is_totp_code(code: &str) -> bool {
+    code.len() == TOTP_DIGITS && code.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Recovery codes are compared case-insensitively and without separators.
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn hash_recovery_code(code: &str) -> String {
+    format!(
+        "{:x}",
+        Sha256::digest(normalize_recovery_code(code).as_bytes())
+    )
+}
+
+/// 80 random bits as 16 base32 characters, grouped `xxxx-xxxx-xxxx-xxxx`
+fn generate_recovery_code() -> String {
+    let bytes: [u8; 10] = rand::random();
+    let Secret::Encoded(encoded) = Secret::Raw(bytes.to_vec()).to_encoded() else {
+        unreachable!("to_encoded always returns an encoded secret");
+    };
+    encoded
+        .to_ascii_lowercase()
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Whether `user_id` has finished enrolling in 2FA.
+pub async fn is_enabled(db: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM user_totp WHERE user_id = $1 AND enabled_at IS NOT NULL)",
+    )
+    .bind(user_id)
+    .fetch_one(db)
+    .await
+}
+
+/// Check a TOTP code against the user's secret. `enabled` selects whether the
+/// secret must already be active (login, disable) or still pending (verify).
+/// An accepted code's time step is recorded, so it cannot be used twice.
+async fn check_totp(
+    db: &PgPool,
+    user: &User,
+    code: &str,
+    enabled: bool,
+) -> Result<bool, sqlx::Error> {
+    let secret = sqlx::query_scalar::<_, String>(
+        "SELECT secret FROM user_totp WHERE user_id = $1 AND (enabled_at IS NOT NULL) = $2",
+    )
+    .bind(user.id)
+    .bind(enabled)
+    .fetch_optional(db)
+    .await?;
+
+    let Some(step) = secret
+        .and_then(|secret| totp(&secret, &user.email))
+        .and_then(|totp| matching_step(&totp, code))
+    else {
+        return Ok(false);
+    };
+
+    let accepted = sqlx::query(
+        r#"
+        UPDATE user_totp
+        SET last_used_step = $2
+        WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+    )
+    .bind(user.id)
+    .bind(step)
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0;
+
+    Ok(accepted)
+}
+
+/// Mark a recovery code used. Returns false if it is unknown or already used.
+async fn redeem_recovery_code(db: &PgPool, user_id: Uuid, code: &str) -> Result<bool, sqlx::Error> {
+    let redeemed = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        UPDATE recovery_codes
+        SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(hash_recovery_code(code))
+    .fetch_optional(db)
+    .await?;
+
+    Ok(redeemed.is_some())
+}
+
+/// Accept either a current TOTP code or an unused recovery code.
+async fn check_second_factor(db: &PgPool, user: &User, code: &str) -> Result<bool, sqlx::Error> {
+    let code = code.trim();
+    if is_totp_code(code) {
+        check_totp(db, user, code, true).await
+    } else {
+        redeem_recovery_code(db, user.id, code).await
+    }
+}
+
+async fn load_user(
+    db: &PgPool,
+    user_id: Uuid,
+) -> Result<User, (StatusCode, Json<serde_json::Value>)> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user_id, "DB error loading user for 2FA");
+            database_error(e)
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": "User not found"})),
+            )
+        })
+}
+
+/// Start a login challenge for a user whose password has been checked.
+pub(crate) async fn create_login_challenge(
+    db: &PgPool,
+    user_id: Uuid,
+    fingerprint_data: &serde_json::Value,
+) -> Result<TwoFactorChallenge, sqlx::Error> {
+    let token = sessions::generate_token(LOGIN_CHALLENGE_TAG);
+
+    sqlx::query(
+        r#"
+        INSERT INTO login_challenges (user_id, token_hash, fingerprint_data, expires_at)
+        VALUES ($1, $2, $3, NOW() + make_interval(secs => $4))
+        "#,
+    )
+    .bind(user_id)
+    .bind(hash_token(&token))
+    .bind(fingerprint_data)
+    .bind(LOGIN_CHALLENGE_TTL_SECS as f64)
+    .execute(db)
+    .await?;
+
+    Ok(TwoFactorChallenge {
+        two_factor_required: true,
+        challenge_token: token,
+        expires_in: LOGIN_CHALLENGE_TTL_SECS,
+    })
+}
+
+pub async fn setup(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+) -> Result<Json<TwoFactorSetupResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let user = load_user(&state.db, claims_user_id(&claims.sub)?).await?;
+
+    let Secret::Encoded(secret) = Secret::Raw(rand::random::<[u8; 20]>().to_vec()).to_encoded()
+    else {
+        unreachable!("to_encoded always returns an encoded secret");
+    };
+    let otpauth_url = totp(&secret, &user.email)
+        .map(|totp| totp.get_url())
+        .ok_or_else(|| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to create TOTP secret"})),
+            )
+        })?;
+
+    // A pending secret is replaced; an active one has to be disabled first.
+    let stored = sqlx::query(
+        r#"
+        INSERT INTO user_totp (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+        WHERE user_totp.enabled_at IS NULL
+        "#,
+    )
+    .bind(user.id)
+    .bind(&secret)
+    .execute(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, user_id = %user.id, "DB error storing TOTP secret");
+        database_error(e)
+    })?
+    .rows_affected();
+
+    if stored == 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({"error": "Two-factor authentication is already enabled"})),
+        ));
+    }
+
+    tracing::info!(user_id = %user.id, "Two-factor setup started");
+
+    Ok(Json(TwoFactorSetupResponse {
+        secret,
+        otpauth_url,
+    }))
+}
+
+pub async fn verify(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let user = load_user(&state.db, claims_user_id(&claims.sub)?).await?;
+
+    let valid = check_totp(&state.db, &user, payload.code.trim(), false)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user.id, "DB error verifying TOTP code");
+            database_error(e)
+        })?;
+
+    if !valid {
+        tracing::warn!(user_id = %user.id, "Two-factor enrollment rejected - wrong code or no pending setup");
+        return Err(invalid_code());
+    }
+
+    let recovery_codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+
+    let mut tx = state.db.begin().await.map_err(database_error)?;
+
+    sqlx::query("UPDATE user_totp SET enabled_at = NOW() WHERE user_id = $1")
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+    sqlx::query("DELETE FROM recovery_codes WHERE user_id = $1")
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+    for code in &recovery_codes {
+        sqlx::query("INSERT INTO recovery_codes (user_id, code_hash) VALUES ($1, $2)")
+            .bind(user.id)
+            .bind(hash_recovery_code(code))
+            .execute(&mut *tx)
+            .await
+            .map_err(database_error)?;
+    }
+
+    // The session enrolling has just shown the second factor; its next
+    // refresh yields access tokens with the `mfa` claim.
+    if let Ok(session_id) = Uuid::parse_str(&claims.sid) {
+        sqlx::query("UPDATE sessions SET two_factor_verified = TRUE WHERE id = $1")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(database_error)?;
+    }
+
+    tx.commit().await.map_err(database_error)?;
+
+    tracing::info!(user_id = %user.id, "Two-factor authentication enabled");
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+pub async fn disable(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let user = load_user(&state.db, claims_user_id(&claims.sub)?).await?;
+
+    let enabled = is_enabled(&state.db, user.id)
+        .await
+        .map_err(database_error)?;
+    if !enabled {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({"error": "Two-factor authentication is not enabled"})),
+        ));
+    }
+
+    let valid = check_second_factor(&state.db, &user, &payload.code)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user.id, "DB error checking second factor");
+            database_error(e)
+        })?;
+
+    if !valid {
+        tracing::warn!(user_id = %user.id, "Two-factor disable rejected - wrong code");
+        return Err(invalid_code());
+    }
+
+    let mut tx = state.db.begin().await.map_err(database_error)?;
+
+    sqlx::query("DELETE FROM user_totp WHERE user_id = $1")
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+    sqlx::query("DELETE FROM recovery_codes WHERE user_id = $1")
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+    sqlx::query("UPDATE sessions SET two_factor_verified = FALSE WHERE user_id = $1")
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+    tx.commit().await.map_err(database_error)?;
+
+    tracing::info!(user_id = %user.id, "Two-factor authentication disabled");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn login_two_factor(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    MaybeConnectInfo(client_addr): MaybeConnectInfo,
+    Json(payload): Json<TwoFactorLoginRequest>,
+) -> Result<Json<LoginResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let client_ip = extract_client_ip(&headers, client_addr, &state.config.trusted_proxies);
+    let user_agent = extract_user_agent(&headers);
+
+    let challenge = sqlx::query_as::<_, (Uuid, Uuid, serde_json::Value)>(
+        r#"
+        SELECT id, user_id, fingerprint_data
+        FROM login_challenges
+        WHERE token_hash = $1 AND expires_at > NOW()
+        "#,
+    )
+    .bind(hash_token(&payload.challenge_token))
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "DB error loading login challenge");
+        database_error(e)
+    })?;
+
+    let Some((challenge_id, user_id, fingerprint_data)) = challenge else {
+        tracing::warn!(ip = %client_ip, "Two-factor login rejected - invalid or expired challenge");
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Invalid or expired login challenge"})),
+        ));
+    };
+
+    let user = load_user(&state.db, user_id).await?;
+    login::enforce_login_limits(&state, &client_ip, &user.email).await?;
+
+    let valid = check_second_factor(&state.db, &user, &payload.code)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = %user.id, "DB error checking second factor");
+            database_error(e)
+        })?;
+
+    if !valid {
+        // Count the miss; the challenge is gone once the limit is reached.
+        let attempts = sqlx::query_scalar::<_, i32>(
+            "UPDATE login_challenges SET attempts = attempts + 1 WHERE id = $1 RETURNING attempts",
+        )
+        .bind(challenge_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(database_error)?;
+
+        if attempts.is_some_and(|attempts| attempts >= LOGIN_CHALLENGE_MAX_ATTEMPTS) {
+            sqlx::query("DELETE FROM login_challenges WHERE id = $1")
+                .bind(challenge_id)
+                .execute(&state.db)
+                .await
+                .map_err(database_error)?;
+        }
+
+        tracing::warn!(
+            user_id = %user.id,
+            ip      = %client_ip,
+            "Failed login attempt - wrong two-factor code"
+        );
+        login::record_login_failure(&state, &client_ip, &user.email).await;
+        return Err(invalid_code());
+    }
+
+    // Single use: whoever deletes the challenge gets the session.
+    let claimed = sqlx::query("DELETE FROM login_challenges WHERE id = $1")
+        .bind(challenge_id)
+        .execute(&state.db)
+        .await
+        .map_err(database_error)?
+        .rows_affected()
+        > 0;
+
+    if !claimed {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({"error": "Invalid or expired login challenge"})),
+        ));
+    }
+    login::reset_login_failures(&state, &user.email).await;
+
+    let (session_id, response) = start_session(
+        &state,
+        &user,
+        &client_ip,
+        user_agent.as_deref(),
+        &fingerprint_data,
+        true,
+    )
+    .await?;
+
+    tracing::info!(
+        user_id    = %user.id,
+        name       = %user.name,
+        role       = %user.role,
+        session_id = %session_id,
+        ip         = %client_ip,
+        "Successful login with two-factor authentication"
+    );
+
+    Ok(Json(response))
+}
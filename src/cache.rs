@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 const USER_CACHE_TTL: u64 = 300; // 5 minutes
 const JWT_CACHE_TTL: u64 = 3600; // 1 hour
 const DIARY_CACHE_TTL: u64 = 60; // 1 minute
+const ROLE_PERMISSIONS_CACHE_TTL: u64 = 300; // 5 minutes
 
 pub struct CacheService;
 
@@ -81,6 +82,25 @@ impl CacheService {
         Ok(())
     }
 
+    /// Add a session to the revocation list for `ttl_secs`
+    pub async fn revoke_session(
+        redis: &mut ConnectionManager,
+        session_id: &str,
+        ttl_secs: u64,
+    ) -> Result<(), redis::RedisError> {
+        let key = format!("revoked_session:{session_id}");
+        redis.set_ex(key, 1, ttl_secs).await
+    }
+
+    /// Check whether a session is on the revocation list
+    pub async fn is_session_revoked(
+        redis: &mut ConnectionManager,
+        session_id: &str,
+    ) -> Result<bool, redis::RedisError> {
+        let key = format!("revoked_session:{session_id}");
+        redis.exists(key).await
+    }
+
     /// Cache diary entry
     pub async fn cache_diary<T: Serialize>(
         redis: &mut ConnectionManager,
@@ -123,4 +143,45 @@ impl CacheService {
         }
         Ok(())
     }
+
+    /// Cache the permissions granted to a role
+    pub async fn cache_role_permissions<T: Serialize>(
+        redis: &mut ConnectionManager,
+        role: &str,
+        permissions: &T,
+    ) -> Result<(), redis::RedisError> {
+        let key = format!("role_permissions:{role}");
+        let value = serde_json::to_string(permissions).unwrap_or_default();
+        redis.set_ex(key, value, ROLE_PERMISSIONS_CACHE_TTL).await
+    }
+
+    /// Get the cached permissions of a role
+    pub async fn get_role_permissions<T: for<'de> Deserialize<'de>>(
+        redis: &mut ConnectionManager,
+        role: &str,
+    ) -> Result<Option<T>, redis::RedisError> {
+        let key = format!("role_permissions:{role}");
+        let value: Option<String> = redis.get(key).await?;
+        Ok(value.and_then(|v| serde_json::from_str(&v).ok()))
+    }
+
+    /// Invalidate the cached permissions of a role
+    pub async fn invalidate_role_permissions(
+        redis: &mut ConnectionManager,
+        role: &str,
+    ) -> Result<(), redis::RedisError> {
+        let key = format!("role_permissions:{role}");
+        redis.del(key).await
+    }
+
+    /// Invalidate the cached permissions of every role
+    pub async fn invalidate_all_role_permissions(
+        redis: &mut ConnectionManager,
+    ) -> Result<(), redis::RedisError> {
+        let keys: Vec<String> = redis.keys("role_permissions:*").await?;
+        if !keys.is_empty() {
+            redis.del::<_, ()>(keys).await?;
+        }
+        Ok(())
+    }
 }
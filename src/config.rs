@@ -1,13 +1,52 @@
 use std::env;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use crate::rate_limit::{self, Budget, RouteBudget};
+
+/// Robot key the project used to ship with; only accepted with `ALLOW_DEFAULT_ROBOT_KEY`
+pub const DEFAULT_ROBOT_API_KEY: &str = "secret-robot-key";
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub database_url: String,
     pub redis_url: String,
     pub jwt_secret: String,
-    pub jwt_expiry_hours: i64,
+    /// Lifetime of access tokens, from `ACCESS_TOKEN_TTL_MINUTES`
+    pub access_token_ttl_minutes: i64,
+    /// Lifetime of each refresh token, from `REFRESH_TOKEN_TTL_DAYS`
+    pub refresh_token_ttl_days: i64,
     pub server_address: String,
-    pub robot_api_key: String,
+    /// Bootstrap key of the default robot, from `ROBOT_API_KEY`
+    pub robot_api_key: Option<String>,
+    /// Per-robot bootstrap keys as `(robot_id, key)` pairs, from `ROBOT_API_KEYS`
+    pub robot_api_keys: Vec<(String, String)>,
+    /// Development escape hatch for starting with `DEFAULT_ROBOT_API_KEY`
+    pub allow_default_robot_key: bool,
+    /// Require new routes to start where the previous one ends (or at the robot)
+    pub enforce_route_chaining: bool,
+    /// Frontend origin used in links sent by mail, from `APP_BASE_URL`
+    pub app_base_url: String,
+    /// `smtp` or `file`, from `MAIL_TRANSPORT`
+    pub mail_transport: String,
+    /// SMTP connection URL, from `SMTP_URL`; required for the `smtp` transport
+    pub smtp_url: Option<String>,
+    /// Sender of outgoing mail, from `MAIL_FROM`
+    pub mail_from: String,
+    /// Where the `file` transport writes messages, from `MAIL_DIR`
+    pub mail_dir: String,
+    /// Refuse logins until the email address is verified
+    pub require_email_verification: bool,
+    /// Keep admins out of admin routes unless their session passed 2FA
+    pub require_admin_2fa: bool,
+    /// Keep diary entries of deleted accounts (ownerless) instead of deleting them
+    pub retain_diary_on_account_deletion: bool,
+    /// Request budgets per route, from `RATE_LIMIT_ROUTES` (`off` for none)
+    pub rate_limit_routes: Vec<RouteBudget>,
+    /// Request budgets per role (and `anonymous`), from `RATE_LIMIT_ROLES` (`off` for none)
+    pub rate_limit_roles: Vec<(String, Budget)>,
+    /// Peers whose `X-Real-IP` / `X-Forwarded-For` are believed, from `TRUSTED_PROXIES`
+    pub trusted_proxies: Vec<TrustedProxy>,
 }
 
 impl Config {
@@ -16,14 +55,170 @@ impl Config {
             database_url: env::var("DATABASE_URL")?,
             redis_url: env::var("REDIS_URL")?,
             jwt_secret: env::var("JWT_SECRET")?,
-            jwt_expiry_hours: env::var("JWT_EXPIRY_HOURS")
-                .unwrap_or_else(|_| "24".to_string())
+            access_token_ttl_minutes: env::var("ACCESS_TOKEN_TTL_MINUTES")
+                .unwrap_or_else(|_| "15".to_string())
                 .parse()
-                .unwrap_or(24),
+                .unwrap_or(15),
+            refresh_token_ttl_days: env::var("REFRESH_TOKEN_TTL_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
             server_address: env::var("SERVER_ADDRESS")
                 .unwrap_or_else(|_| "0.0.0.0:3003".to_string()),
-            robot_api_key: env::var("ROBOT_API_KEY")
-                .unwrap_or_else(|_| "secret-robot-key".to_string()),
+            robot_api_key: env::var("ROBOT_API_KEY").ok().filter(|k| !k.is_empty()),
+            robot_api_keys: env::var("ROBOT_API_KEYS")
+                .map(|v| parse_robot_api_keys(&v))
+                .unwrap_or_default(),
+            allow_default_robot_key: env::var("ALLOW_DEFAULT_ROBOT_KEY")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            enforce_route_chaining: env::var("ENFORCE_ROUTE_CHAINING")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            app_base_url: env::var("APP_BASE_URL")
+                .map(|v| v.trim_end_matches('/').to_string())
+                .unwrap_or_else(|_| "http://localhost".to_string()),
+            mail_transport: env::var("MAIL_TRANSPORT").unwrap_or_else(|_| "file".to_string()),
+            smtp_url: env::var("SMTP_URL").ok().filter(|v| !v.is_empty()),
+            mail_from: env::var("MAIL_FROM")
+                .unwrap_or_else(|_| "TeleTable <no-reply@localhost>".to_string()),
+            mail_dir: env::var("MAIL_DIR").unwrap_or_else(|_| "./mail".to_string()),
+            require_email_verification: env::var("REQUIRE_EMAIL_VERIFICATION")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            require_admin_2fa: env::var("REQUIRE_ADMIN_2FA")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            retain_diary_on_account_deletion: env::var("RETAIN_DIARY_ON_ACCOUNT_DELETION")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            rate_limit_routes: env::var("RATE_LIMIT_ROUTES")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .map_or_else(rate_limit::default_route_budgets, |v| {
+                    rate_limit::parse_route_budgets(&v)
+                }),
+            rate_limit_roles: env::var("RATE_LIMIT_ROLES")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .map_or_else(rate_limit::default_role_budgets, |v| {
+                    rate_limit::parse_role_budgets(&v)
+                }),
+            trusted_proxies: env::var("TRUSTED_PROXIES")
+                .map(|v| parse_trusted_proxies(&v))
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Robots and keys configured through the environment. Without
+    /// `ROBOT_API_KEYS` this is the default robot with `ROBOT_API_KEY`, if set.
+    /// Keys issued through the admin API live in `robot_api_keys` instead.
+    pub fn robot_keys(&self) -> Vec<(String, String)> {
+        if !self.robot_api_keys.is_empty() {
+            return self.robot_api_keys.clone();
+        }
+
+        self.robot_api_key
+            .iter()
+            .map(|key| {
+                (
+                    crate::robot::state::DEFAULT_ROBOT_ID.to_string(),
+                    key.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Refuse the well-known default robot key unless explicitly allowed.
+    pub fn check_robot_keys(&self) -> Result<(), String> {
+        let uses_default = self
+            .robot_keys()
+            .iter()
+            .any(|(_, key)| key == DEFAULT_ROBOT_API_KEY);
+
+        if uses_default && !self.allow_default_robot_key {
+            return Err(format!(
+                "Robot API key \"{DEFAULT_ROBOT_API_KEY}\" is the public default; \
+                 issue per-robot keys or set ALLOW_DEFAULT_ROBOT_KEY=true for development"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse `id:key,id2:key2`. Malformed entries are skipped.
+fn parse_robot_api_keys(raw: &str) -> Vec<(String, String)> {
+    raw.split(',')
+        .filter_map(|entry| {
+            let (id, key) = entry.trim().split_once(':')?;
+            let (id, key) = (id.trim(), key.trim());
+            (!id.is_empty() && !key.is_empty()).then(|| (id.to_string(), key.to_string()))
+        })
+        .collect()
+}
+
+/// Parse `addr,addr/prefix,...`. Malformed entries are skipped.
+fn parse_trusted_proxies(raw: &str) -> Vec<TrustedProxy> {
+    raw.split(',')
+        .filter_map(|entry| entry.trim().parse().ok())
+        .collect()
+}
+
+/// A reverse proxy address or CIDR range from `TRUSTED_PROXIES`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrustedProxy {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl TrustedProxy {
+    /// Whether `ip` is this address or lies in this range. IPv4-mapped IPv6
+    /// peers match their IPv4 form.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for TrustedProxy {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match raw.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (raw, None),
+        };
+        let network: IpAddr = addr
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid proxy address: {raw}"))?;
+        let max_len = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix {
+            Some(prefix) => prefix
+                .trim()
+                .parse()
+                .ok()
+                .filter(|len| *len <= max_len)
+                .ok_or_else(|| format!("invalid proxy prefix length: {raw}"))?,
+            None => max_len,
+        };
+        Ok(Self {
+            network,
+            prefix_len,
         })
     }
 }
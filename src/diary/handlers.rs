@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -7,17 +7,23 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
-    auth::{extractor::AuthenticatedUser, roles},
+    auth::{
+        extractor::{AuthenticatedUser, OptionalUser},
+        login::claims_user_id,
+        permissions::{DiaryWrite, RequirePermission},
+    },
     diary::models::{
-        CreateDiaryRequest, DeleteDiaryRequest, DiaryEntry, DiaryEntryWithUser, DiaryQuery,
-        DiaryResponse, DiaryResponseWithUser,
+        AllDiariesQuery, CreateDiaryRequest, DeleteDiaryRequest, DiaryCursor, DiaryEntry,
+        DiaryEntryWithUser, DiaryPage, DiaryQuery, DiaryResponse, DiaryResponseWithUser,
+        DiaryRevision, PublicDiaryEntry,
     },
+    diary::{links, settings, visibility},
     AppState,
 };
 
 pub async fn create_or_update_diary(
     State(state): State<Arc<AppState>>,
-    AuthenticatedUser(claims): AuthenticatedUser,
+    RequirePermission { claims, .. }: RequirePermission<DiaryWrite>,
     Json(payload): Json<CreateDiaryRequest>,
 ) -> Result<(StatusCode, Json<DiaryResponse>), (StatusCode, Json<serde_json::Value>)> {
     if payload.text.chars().count() > 5000 {
@@ -29,6 +35,37 @@ pub async fn create_or_update_diary(
         ));
     }
 
+    if let Some(unknown) = payload
+        .visibility
+        .as_deref()
+        .filter(|v| !visibility::is_valid(v))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("Unknown visibility: {unknown}")})),
+        ));
+    }
+
+    let tags = payload
+        .tags
+        .as_deref()
+        .map(links::normalize_tags)
+        .transpose()
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": e})),
+            )
+        })?;
+    let route_ids = payload.route_ids.as_deref().map(links::dedup_ids);
+    let notification_ids = payload.notification_ids.as_deref().map(links::dedup_ids);
+    links::check_links(
+        &state.db,
+        route_ids.as_deref().unwrap_or_default(),
+        notification_ids.as_deref().unwrap_or_default(),
+    )
+    .await?;
+
     let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
@@ -36,56 +73,95 @@ pub async fn create_or_update_diary(
         )
     })?;
 
-    // Trust JWT claims for role check (already validated by middleware)
-    if !roles::can_operate(&claims.role) {
-        tracing::warn!(
-            user_id = %user_id,
-            role    = %claims.role,
-            "Permission denied - diary write requires operator or above (403)"
-        );
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({ "error": "Insufficient permissions" })),
-        ));
-    }
-
     let entry = if let Some(id) = payload.id {
-        sqlx::query_as::<_, DiaryEntry>(
-            r#"
-            UPDATE diary_entries
-            SET working_minutes = $1,
-                text = $2
-            WHERE id = $3 AND owner = $4
-            RETURNING *
-            "#,
-        )
-        .bind(payload.working_minutes)
-        .bind(&payload.text)
-        .bind(id)
-        .bind(user_id)
-        .fetch_optional(&state.db)
-        .await
-        .map_err(|e| {
+        let db_error = |e: sqlx::Error| {
             tracing::error!(
-                query   = "UPDATE diary_entries SET ... WHERE id = ? AND owner = ?",
-                error   = %e,
-                user_id = %user_id,
+                query    = "UPDATE diary_entries SET ... WHERE id = ? AND owner = ?",
+                error    = %e,
+                user_id  = %user_id,
+                entry_id = %id,
                 "DB error updating diary entry"
             );
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(serde_json::json!({ "error": e.to_string() })),
             )
-        })?
+        };
+
+        // Keep the version being replaced; the row lock orders concurrent
+        // updates so revision numbers stay unique.
+        let mut tx = state.db.begin().await.map_err(db_error)?;
+        let previous = sqlx::query_as::<_, DiaryEntry>(
+            "SELECT * FROM diary_entries WHERE id = $1 AND owner = $2 AND deleted_at IS NULL FOR UPDATE",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?
         .ok_or((
             StatusCode::NOT_FOUND,
             Json(serde_json::json!({ "error": "Diary entry not found" })),
-        ))?
+        ))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO diary_entry_revisions
+                (entry_id, revision, working_minutes, text, visibility, valid_from, replaced_by,
+                 work_date, tags, route_ids, notification_ids)
+            SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3, $4, $5, $6, $7, $8, $9, $10
+            FROM diary_entry_revisions
+            WHERE entry_id = $1
+            "#,
+        )
+        .bind(previous.id)
+        .bind(previous.working_minutes)
+        .bind(&previous.text)
+        .bind(&previous.visibility)
+        .bind(previous.updated_at)
+        .bind(user_id)
+        .bind(previous.work_date)
+        .bind(&previous.tags)
+        .bind(&previous.route_ids)
+        .bind(&previous.notification_ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        let entry = sqlx::query_as::<_, DiaryEntry>(
+            r#"
+            UPDATE diary_entries
+            SET working_minutes = $1,
+                text = $2,
+                visibility = COALESCE($4, visibility),
+                work_date = CASE WHEN $5 THEN $6 ELSE work_date END,
+                tags = COALESCE($7, tags),
+                route_ids = COALESCE($8, route_ids),
+                notification_ids = COALESCE($9, notification_ids)
+            WHERE id = $3
+            RETURNING *
+            "#,
+        )
+        .bind(payload.working_minutes)
+        .bind(&payload.text)
+        .bind(id)
+        .bind(payload.visibility.as_deref())
+        .bind(payload.work_date.is_some())
+        .bind(payload.work_date.flatten())
+        .bind(&tags)
+        .bind(&route_ids)
+        .bind(&notification_ids)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+        entry
     } else {
         sqlx::query_as::<_, DiaryEntry>(
             r#"
-            INSERT INTO diary_entries (id, owner, working_minutes, text)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO diary_entries
+                (id, owner, working_minutes, text, visibility, work_date, tags, route_ids, notification_ids)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             RETURNING *
             "#,
         )
@@ -93,6 +169,11 @@ pub async fn create_or_update_diary(
         .bind(user_id)
         .bind(payload.working_minutes)
         .bind(&payload.text)
+        .bind(payload.visibility.as_deref().unwrap_or(visibility::DEFAULT))
+        .bind(payload.work_date.flatten())
+        .bind(tags.unwrap_or_default())
+        .bind(route_ids.unwrap_or_default())
+        .bind(notification_ids.unwrap_or_default())
         .fetch_one(&state.db)
         .await
         .map_err(|e| {
@@ -111,13 +192,14 @@ pub async fn create_or_update_diary(
     // Invalidate diary cache
     let mut redis = state.redis.clone();
     let _ = crate::cache::CacheService::invalidate_diary(&mut redis, &entry.id.to_string()).await;
+    let found = links::linked_notifications(&state, &claims.role, &entry.notification_ids).await?;
     Ok((
         if payload.id.is_some() {
             StatusCode::OK
         } else {
             StatusCode::CREATED
         },
-        Json(entry.into()),
+        Json(DiaryResponse::from(entry).with_notifications(&found)),
     ))
 }
 
@@ -135,7 +217,7 @@ pub async fn get_diary(
 
     if let Some(id) = query.id {
         let entry = sqlx::query_as::<_, DiaryEntry>(
-            "SELECT * FROM diary_entries WHERE id = $1 AND owner = $2",
+            "SELECT * FROM diary_entries WHERE id = $1 AND owner = $2 AND deleted_at IS NULL",
         )
         .bind(id)
         .bind(user_id)
@@ -154,64 +236,290 @@ pub async fn get_diary(
             )
         })?;
 
-        Ok(Json(serde_json::json!(DiaryResponse::from(entry))))
+        let found =
+            links::linked_notifications(&state, &claims.role, &entry.notification_ids).await?;
+        Ok(Json(serde_json::json!(
+            DiaryResponse::from(entry).with_notifications(&found)
+        )))
     } else {
+        let (cursor, limit) = page_params(query.cursor.as_deref(), query.limit)?;
+        let q = search_terms(query.q.as_deref());
+        let tag = query.tag.as_deref().and_then(links::normalize_tag);
+
         let entries = sqlx::query_as::<_, DiaryEntry>(
-            "SELECT * FROM diary_entries WHERE owner = $1 ORDER BY created_at DESC",
+            r#"
+            SELECT * FROM diary_entries
+            WHERE owner = $1
+              AND (deleted_at IS NOT NULL) = $8
+              AND ($2::timestamptz IS NULL OR created_at >= $2)
+              AND ($3::timestamptz IS NULL OR created_at < $3)
+              AND ($4::text IS NULL OR to_tsvector('simple', text) @@ websearch_to_tsquery('simple', $4))
+              AND ($5::timestamptz IS NULL OR (created_at, id) < ($5, $6))
+              AND ($9::text IS NULL OR tags @> ARRAY[$9])
+              AND ($10::uuid IS NULL OR route_ids @> ARRAY[$10])
+            ORDER BY created_at DESC, id DESC
+            LIMIT $7
+            "#,
         )
         .bind(user_id)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(q)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id))
+        .bind(limit + 1)
+        .bind(query.deleted)
+        .bind(&tag)
+        .bind(query.route_id)
         .fetch_all(&state.db)
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
-            )
-        })?;
+        .map_err(database_error)?;
+
+        let total = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM diary_entries
+            WHERE owner = $1
+              AND (deleted_at IS NOT NULL) = $5
+              AND ($2::timestamptz IS NULL OR created_at >= $2)
+              AND ($3::timestamptz IS NULL OR created_at < $3)
+              AND ($4::text IS NULL OR to_tsvector('simple', text) @@ websearch_to_tsquery('simple', $4))
+              AND ($6::text IS NULL OR tags @> ARRAY[$6])
+              AND ($7::uuid IS NULL OR route_ids @> ARRAY[$7])
+            "#,
+        )
+        .bind(user_id)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(q)
+        .bind(query.deleted)
+        .bind(&tag)
+        .bind(query.route_id)
+        .fetch_one(&state.db)
+        .await
+        .map_err(database_error)?;
 
-        let diary_responses: Vec<DiaryResponse> = entries.into_iter().map(|e| e.into()).collect();
-        Ok(Json(serde_json::json!(diary_responses)))
+        let page = into_page(entries, total, limit, |e| DiaryCursor {
+            created_at: e.created_at,
+            id: e.id,
+        });
+        let found = links::linked_notifications(
+            &state,
+            &claims.role,
+            page.entries.iter().flat_map(|e| &e.notification_ids),
+        )
+        .await?;
+        Ok(Json(serde_json::json!(DiaryPage {
+            entries: page
+                .entries
+                .into_iter()
+                .map(|e| DiaryResponse::from(e).with_notifications(&found))
+                .collect(),
+            total: page.total,
+            next_cursor: page.next_cursor,
+        })))
     }
 }
 
+/// Anonymous callers get the public entries in a sanitized projection, and
+/// only while the public feed is switched on. Signed-in users also see team
+/// entries and their own private ones.
 pub async fn get_all_diaries(
     State(state): State<Arc<AppState>>,
+    OptionalUser(claims): OptionalUser,
+    Query(query): Query<AllDiariesQuery>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let (cursor, limit) = page_params(query.cursor.as_deref(), query.limit)?;
+    let q = search_terms(query.q.as_deref());
+    let tag = query.tag.as_deref().and_then(links::normalize_tag);
+    let owner = query
+        .owner
+        .as_deref()
+        .map(str::trim)
+        .filter(|o| !o.is_empty());
+
+    let (viewer, visible) = match &claims {
+        Some(claims) => (
+            Some(claims_user_id(&claims.sub)?),
+            vec![visibility::TEAM, visibility::PUBLIC],
+        ),
+        None => {
+            if !settings::public_feed_enabled(&state).await {
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(serde_json::json!({"error": "The public diary feed is disabled"})),
+                ));
+            }
+            if owner.is_some() || query.owner_id.is_some() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": "Owner filters require authentication"})),
+                ));
+            }
+            (None, vec![visibility::PUBLIC])
+        }
+    };
+
     let entries = sqlx::query_as::<_, DiaryEntryWithUser>(
         r#"
         SELECT 
             d.id, 
-            u.name AS owner, 
+            COALESCE(u.name, 'Deleted user') AS owner, 
             d.working_minutes, 
             d.text, 
+            d.visibility,
             d.created_at, 
-            d.updated_at
+            d.updated_at,
+            d.work_date,
+            d.tags,
+            d.route_ids,
+            d.notification_ids
         FROM diary_entries d
-        INNER JOIN users u ON d.owner = u.id
-        ORDER BY d.created_at DESC
+        LEFT JOIN users u ON d.owner = u.id
+        WHERE d.deleted_at IS NULL
+          AND (d.visibility = ANY($1) OR d.owner = $2)
+          AND ($3::text IS NULL OR LOWER(u.name) = LOWER($3))
+          AND ($4::uuid IS NULL OR d.owner = $4)
+          AND ($5::timestamptz IS NULL OR d.created_at >= $5)
+          AND ($6::timestamptz IS NULL OR d.created_at < $6)
+          AND ($7::text IS NULL OR to_tsvector('simple', d.text) @@ websearch_to_tsquery('simple', $7))
+          AND ($8::timestamptz IS NULL OR (d.created_at, d.id) < ($8, $9))
+          AND ($11::text IS NULL OR d.tags @> ARRAY[$11])
+          AND ($12::uuid IS NULL OR d.route_ids @> ARRAY[$12])
+        ORDER BY d.created_at DESC, d.id DESC
+        LIMIT $10
         "#,
     )
+    .bind(&visible)
+    .bind(viewer)
+    .bind(owner)
+    .bind(query.owner_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(q)
+    .bind(cursor.map(|c| c.created_at))
+    .bind(cursor.map(|c| c.id))
+    .bind(limit + 1)
+    .bind(&tag)
+    .bind(query.route_id)
     .fetch_all(&state.db)
     .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+    .map_err(database_error)?;
+
+    let total = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*)
+        FROM diary_entries d
+        LEFT JOIN users u ON d.owner = u.id
+        WHERE d.deleted_at IS NULL
+          AND (d.visibility = ANY($1) OR d.owner = $2)
+          AND ($3::text IS NULL OR LOWER(u.name) = LOWER($3))
+          AND ($4::uuid IS NULL OR d.owner = $4)
+          AND ($5::timestamptz IS NULL OR d.created_at >= $5)
+          AND ($6::timestamptz IS NULL OR d.created_at < $6)
+          AND ($7::text IS NULL OR to_tsvector('simple', d.text) @@ websearch_to_tsquery('simple', $7))
+          AND ($8::text IS NULL OR d.tags @> ARRAY[$8])
+          AND ($9::uuid IS NULL OR d.route_ids @> ARRAY[$9])
+        "#,
+    )
+    .bind(&visible)
+    .bind(viewer)
+    .bind(owner)
+    .bind(query.owner_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(q)
+    .bind(&tag)
+    .bind(query.route_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(database_error)?;
+
+    let page = into_page(entries, total, limit, |e| DiaryCursor {
+        created_at: e.created_at,
+        id: e.id,
+    });
+    Ok(Json(if let Some(claims) = &claims {
+        let found = links::linked_notifications(
+            &state,
+            &claims.role,
+            page.entries.iter().flat_map(|e| &e.notification_ids),
         )
-    })?;
+        .await?;
+        serde_json::json!(DiaryPage {
+            entries: page
+                .entries
+                .into_iter()
+                .map(|e| DiaryResponseWithUser::from(e).with_notifications(&found))
+                .collect(),
+            total: page.total,
+            next_cursor: page.next_cursor,
+        })
+    } else {
+        serde_json::json!(DiaryPage {
+            entries: page
+                .entries
+                .into_iter()
+                .map(PublicDiaryEntry::from)
+                .collect(),
+            total: page.total,
+            next_cursor: page.next_cursor,
+        })
+    }))
+}
+
+fn database_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    tracing::error!(error = %e, "DB error listing diary entries");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+    )
+}
 
-    // Map the results to your response DTO
-    let response: Vec<DiaryResponseWithUser> = entries
-        .into_iter()
-        .map(DiaryResponseWithUser::from)
-        .collect();
+/// Decode the cursor and clamp the page size (default 50, at most 200).
+fn page_params(
+    cursor: Option<&str>,
+    limit: Option<i64>,
+) -> Result<(Option<DiaryCursor>, i64), (StatusCode, Json<serde_json::Value>)> {
+    let cursor = match cursor.filter(|c| !c.is_empty()) {
+        Some(raw) => Some(DiaryCursor::decode(raw).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({"error": "Invalid cursor"})),
+            )
+        })?),
+        None => None,
+    };
+    Ok((cursor, limit.unwrap_or(50).clamp(1, 200)))
+}
 
-    Ok(Json(serde_json::json!(response)))
+fn search_terms(q: Option<&str>) -> Option<&str> {
+    q.map(str::trim).filter(|q| !q.is_empty())
+}
+
+/// Cut the `limit + 1` rows fetched down to a page; the extra row only tells
+/// whether another page follows.
+fn into_page<T>(
+    mut entries: Vec<T>,
+    total: i64,
+    limit: i64,
+    cursor_of: impl Fn(&T) -> DiaryCursor,
+) -> DiaryPage<T> {
+    let next_cursor = if entries.len() as i64 > limit {
+        entries.truncate(limit as usize);
+        entries.last().map(|e| cursor_of(e).encode())
+    } else {
+        None
+    };
+    DiaryPage {
+        entries,
+        total,
+        next_cursor,
+    }
 }
 
 pub async fn delete_diary(
     State(state): State<Arc<AppState>>,
-    AuthenticatedUser(claims): AuthenticatedUser,
+    RequirePermission { claims, .. }: RequirePermission<DiaryWrite>,
     Json(payload): Json<DeleteDiaryRequest>,
 ) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
     let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
@@ -221,27 +529,16 @@ pub async fn delete_diary(
         )
     })?;
 
-    // Trust JWT claims for role check (already validated by middleware)
-    if !roles::can_operate(&claims.role) {
-        tracing::warn!(
-            user_id = %user_id,
-            role    = %claims.role,
-            "Permission denied - diary delete requires operator or above (403)"
-        );
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({"error": "Insufficient permissions"})),
-        ));
-    }
-
-    let result = sqlx::query("DELETE FROM diary_entries WHERE id = $1 AND owner = $2")
+    let result = sqlx::query(
+        "UPDATE diary_entries SET deleted_at = NOW() WHERE id = $1 AND owner = $2 AND deleted_at IS NULL",
+    )
         .bind(payload.id)
         .bind(user_id)
         .execute(&state.db)
         .await
         .map_err(|e| {
             tracing::error!(
-                query    = "DELETE FROM diary_entries WHERE id = ? AND owner = ?",
+                query    = "UPDATE diary_entries SET deleted_at = NOW() WHERE id = ? AND owner = ?",
                 error    = %e,
                 user_id  = %user_id,
                 entry_id = %payload.id,
@@ -266,3 +563,84 @@ pub async fn delete_diary(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Undo a delete within the grace period.
+pub async fn restore_diary(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { claims, .. }: RequirePermission<DiaryWrite>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DiaryResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = claims_user_id(&claims.sub)?;
+
+    let entry = sqlx::query_as::<_, DiaryEntry>(
+        r#"
+        UPDATE diary_entries
+        SET deleted_at = NULL
+        WHERE id = $1 AND owner = $2 AND deleted_at IS NOT NULL
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, user_id = %user_id, entry_id = %id, "DB error restoring diary entry");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Deleted diary entry not found"})),
+        )
+    })?;
+
+    let mut redis = state.redis.clone();
+    let _ = crate::cache::CacheService::invalidate_diary(&mut redis, &id.to_string()).await;
+
+    let found = links::linked_notifications(&state, &claims.role, &entry.notification_ids).await?;
+    Ok(Json(DiaryResponse::from(entry).with_notifications(&found)))
+}
+
+/// Earlier versions of an owned entry, newest first.
+pub async fn get_diary_revisions(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<DiaryRevision>>, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = claims_user_id(&claims.sub)?;
+
+    let owned = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM diary_entries WHERE id = $1 AND owner = $2)",
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(database_error)?;
+    if !owned {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Diary entry not found"})),
+        ));
+    }
+
+    let revisions = sqlx::query_as::<_, DiaryRevision>(
+        r#"
+        SELECT revision, working_minutes, text, visibility, work_date, tags, route_ids,
+               notification_ids, valid_from, replaced_at, replaced_by
+        FROM diary_entry_revisions
+        WHERE entry_id = $1
+        ORDER BY revision DESC
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(database_error)?;
+
+    Ok(Json(revisions))
+}
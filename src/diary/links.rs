@@ -0,0 +1,146 @@
+// Tags on diary entries and links to robot routes and notifications.
+//
+// Tags are normalized on write so `?tag=` matches however they were typed.
+// Linked routes and notifications must exist when the entry is saved; the
+// notification messages are shown inline to viewers who may read the
+// notification history (`robot.view`).
+
+use axum::{http::StatusCode, Json};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{
+    auth::permissions::{self, ROBOT_VIEW},
+    notifications::models::RobotNotification,
+    AppState,
+};
+
+pub const MAX_TAGS: usize = 20;
+pub const MAX_TAG_CHARS: usize = 32;
+
+/// Trimmed and lowercased; `None` for a blank tag
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Normalize, drop blanks and duplicates, and enforce the limits
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags.iter().filter_map(|t| normalize_tag(t)) {
+        if tag.chars().count() > MAX_TAG_CHARS {
+            return Err(format!("Tags must be at most {MAX_TAG_CHARS} characters"));
+        }
+        if !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    if out.len() > MAX_TAGS {
+        return Err(format!("At most {MAX_TAGS} tags per entry"));
+    }
+    Ok(out)
+}
+
+/// Drop repeated ids, keeping the first occurrence
+pub fn dedup_ids(ids: &[Uuid]) -> Vec<Uuid> {
+    let mut out: Vec<Uuid> = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !out.contains(id) {
+            out.push(*id);
+        }
+    }
+    out
+}
+
+/// 400 naming the first linked route or notification that does not exist
+pub async fn check_links(
+    db: &PgPool,
+    route_ids: &[Uuid],
+    notification_ids: &[Uuid],
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let missing = sqlx::query_as::<_, (String, Uuid)>(
+        r#"
+        SELECT 'route', r FROM UNNEST($1::uuid[]) r
+        WHERE NOT EXISTS (SELECT 1 FROM route_queue WHERE id = r)
+        UNION ALL
+        SELECT 'notification', n FROM UNNEST($2::uuid[]) n
+        WHERE NOT EXISTS (SELECT 1 FROM robot_notifications WHERE id = n)
+        LIMIT 1
+        "#,
+    )
+    .bind(route_ids)
+    .bind(notification_ids)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "DB error checking diary links");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+        )
+    })?;
+
+    match missing {
+        Some((kind, id)) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("Unknown {kind}: {id}")})),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The notifications linked from a set of entries, in one query. Empty for
+/// roles without `robot.view`.
+pub async fn linked_notifications<'a>(
+    state: &AppState,
+    role: &str,
+    ids: impl IntoIterator<Item = &'a Uuid>,
+) -> Result<HashMap<Uuid, RobotNotification>, (StatusCode, Json<serde_json::Value>)> {
+    let ids: Vec<Uuid> = ids.into_iter().copied().collect();
+    if ids.is_empty()
+        || !permissions::for_role(state, role)
+            .await
+            .contains(ROBOT_VIEW)
+    {
+        return Ok(HashMap::new());
+    }
+
+    let found = sqlx::query_as::<_, RobotNotification>(
+        r#"
+        SELECT id, robot_id, priority, message, received_at
+        FROM robot_notifications
+        WHERE id = ANY($1)
+        "#,
+    )
+    .bind(&ids)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "DB error loading linked notifications");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+        )
+    })?;
+
+    Ok(found.into_iter().map(|n| (n.id, n)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_are_normalized_and_deduplicated() {
+        let tags = ["  Maintenance ", "maintenance", "", "Lab 2"].map(String::from);
+        assert_eq!(
+            normalize_tags(&tags).unwrap(),
+            vec!["maintenance".to_string(), "lab 2".to_string()]
+        );
+        assert!(normalize_tags(&["x".repeat(MAX_TAG_CHARS + 1)]).is_err());
+        assert!(
+            normalize_tags(&(0..=MAX_TAGS).map(|i| i.to_string()).collect::<Vec<_>>()).is_err()
+        );
+    }
+}
@@ -1,2 +1,7 @@
 pub mod handlers;
+pub mod links;
 pub mod models;
+pub mod report;
+pub mod settings;
+pub mod trash;
+pub mod visibility;
@@ -1,16 +1,25 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::notifications::models::RobotNotification;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct DiaryEntry {
     pub id: Uuid,
     pub owner: Uuid,
     pub working_minutes: i32,
     pub text: String,
+    pub visibility: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub work_date: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub route_ids: Vec<Uuid>,
+    pub notification_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -19,8 +28,13 @@ pub struct DiaryEntryWithUser {
     pub owner: String,
     pub working_minutes: i32,
     pub text: String,
+    pub visibility: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub work_date: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub route_ids: Vec<Uuid>,
+    pub notification_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,8 +43,23 @@ pub struct DiaryResponseWithUser {
     pub owner: String,
     pub working_minutes: i32,
     pub text: String,
+    pub visibility: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub work_date: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub route_ids: Vec<Uuid>,
+    pub notification_ids: Vec<Uuid>,
+    /// The linked notifications, for viewers with `robot.view`
+    #[serde(default)]
+    pub notifications: Vec<RobotNotification>,
+}
+
+impl DiaryResponseWithUser {
+    pub fn with_notifications(mut self, found: &HashMap<Uuid, RobotNotification>) -> Self {
+        self.notifications = pick_notifications(&self.notification_ids, found);
+        self
+    }
 }
 
 impl From<DiaryEntryWithUser> for DiaryResponseWithUser {
@@ -40,8 +69,14 @@ impl From<DiaryEntryWithUser> for DiaryResponseWithUser {
             owner: entry.owner,
             working_minutes: entry.working_minutes,
             text: entry.text,
+            visibility: entry.visibility,
             created_at: entry.created_at,
             updated_at: entry.updated_at,
+            work_date: entry.work_date,
+            tags: entry.tags,
+            route_ids: entry.route_ids,
+            notification_ids: entry.notification_ids,
+            notifications: Vec::new(),
         }
     }
 }
@@ -52,8 +87,35 @@ pub struct DiaryResponse {
     pub owner: Uuid,
     pub working_minutes: i32,
     pub text: String,
+    pub visibility: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set on deleted entries, which are purged once the grace period ends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// The day the work was done, when it differs from `created_at`
+    pub work_date: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub route_ids: Vec<Uuid>,
+    pub notification_ids: Vec<Uuid>,
+    /// The linked notifications, for viewers with `robot.view`
+    #[serde(default)]
+    pub notifications: Vec<RobotNotification>,
+}
+
+impl DiaryResponse {
+    pub fn with_notifications(mut self, found: &HashMap<Uuid, RobotNotification>) -> Self {
+        self.notifications = pick_notifications(&self.notification_ids, found);
+        self
+    }
+}
+
+/// The linked notifications in link order, skipping ones not loaded
+fn pick_notifications(
+    ids: &[Uuid],
+    found: &HashMap<Uuid, RobotNotification>,
+) -> Vec<RobotNotification> {
+    ids.iter().filter_map(|id| found.get(id).cloned()).collect()
 }
 
 impl From<DiaryEntry> for DiaryResponse {
@@ -63,8 +125,15 @@ impl From<DiaryEntry> for DiaryResponse {
             owner: entry.owner,
             working_minutes: entry.working_minutes,
             text: entry.text,
+            visibility: entry.visibility,
             created_at: entry.created_at,
             updated_at: entry.updated_at,
+            deleted_at: entry.deleted_at,
+            work_date: entry.work_date,
+            tags: entry.tags,
+            route_ids: entry.route_ids,
+            notification_ids: entry.notification_ids,
+            notifications: Vec::new(),
         }
     }
 }
@@ -74,14 +143,228 @@ pub struct CreateDiaryRequest {
     pub id: Option<Uuid>,
     pub working_minutes: i32,
     pub text: String,
+    /// `private`, `team` or `public`; new entries default to `team`, updates
+    /// keep the current visibility
+    #[serde(default)]
+    pub visibility: Option<String>,
+    /// Omitted keeps the current work date on updates; `null` clears it
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub work_date: Option<Option<NaiveDate>>,
+    /// Replace the tags; omitted keeps them on updates
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Replace the linked routes; omitted keeps them on updates
+    #[serde(default)]
+    pub route_ids: Option<Vec<Uuid>>,
+    /// Replace the linked notifications; omitted keeps them on updates
+    #[serde(default)]
+    pub notification_ids: Option<Vec<Uuid>>,
+}
+
+/// Tells an explicit `null` (`Some(None)`) apart from an omitted field (`None`)
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// What the anonymous feed shows of a public entry: no owner identity
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicDiaryEntry {
+    pub id: Uuid,
+    pub working_minutes: i32,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+    pub work_date: Option<NaiveDate>,
+    pub tags: Vec<String>,
+}
+
+impl From<DiaryEntryWithUser> for PublicDiaryEntry {
+    fn from(entry: DiaryEntryWithUser) -> Self {
+        PublicDiaryEntry {
+            id: entry.id,
+            working_minutes: entry.working_minutes,
+            text: entry.text,
+            created_at: entry.created_at,
+            work_date: entry.work_date,
+            tags: entry.tags,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiarySettings {
+    /// Whether `GET /diary/all` answers requests without a token
+    pub public_feed: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DiaryQuery {
+    /// A single entry instead of a page
     pub id: Option<Uuid>,
+    /// Entries created at or after this instant
+    pub from: Option<DateTime<Utc>>,
+    /// Entries created before this instant
+    pub to: Option<DateTime<Utc>>,
+    /// Full-text search over the entry text (web search syntax)
+    pub q: Option<String>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    /// Entries carrying this tag
+    pub tag: Option<String>,
+    /// Entries linked to this route
+    pub route_id: Option<Uuid>,
+    /// List deleted entries that can still be restored instead
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AllDiariesQuery {
+    /// Owner name, case-insensitive
+    pub owner: Option<String>,
+    pub owner_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub q: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    pub tag: Option<String>,
+    pub route_id: Option<Uuid>,
+}
+
+/// One page of diary entries, newest first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiaryPage<T> {
+    pub entries: Vec<T>,
+    /// Entries matching the filters across all pages
+    pub total: i64,
+    /// Pass as `cursor` to fetch the next page; `null` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Position after the last entry of a page. Entries are ordered by
+/// `(created_at, id)` descending, so the pair is unique and stable while
+/// new entries are added.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiaryCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl DiaryCursor {
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.created_at.timestamp_micros(), self.id)
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let (micros, id) = cursor.split_once('_')?;
+        Some(Self {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct DeleteDiaryRequest {
     pub id: Uuid,
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportGroupBy {
+    /// One row per user over the whole range
+    #[default]
+    User,
+    /// One row per user and ISO week (Monday to Sunday, UTC)
+    Week,
+    /// One row per user and calendar month (UTC)
+    Month,
+}
+
+impl ReportGroupBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportGroupBy::User => "user",
+            ReportGroupBy::Week => "week",
+            ReportGroupBy::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    /// Comma-separated values that open directly in Excel
+    Csv,
+    /// iCalendar, one all-day event per row
+    Ics,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiaryReportQuery {
+    /// Entries worked at or after this instant (work date, else creation time)
+    pub from: Option<DateTime<Utc>>,
+    /// Entries worked before this instant
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub group_by: ReportGroupBy,
+    #[serde(default)]
+    pub format: ReportFormat,
+    /// Only this user's entries
+    pub owner_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DiaryReportRow {
+    /// `null` for entries kept after their owner's account was deleted
+    pub owner_id: Option<Uuid>,
+    pub owner: String,
+    /// First day of the week or month; `null` when grouped by user
+    pub period_start: Option<NaiveDate>,
+    /// First day after the week or month; `null` when grouped by user
+    pub period_end: Option<NaiveDate>,
+    pub entries: i64,
+    pub minutes: i64,
+    pub first_entry_at: DateTime<Utc>,
+    pub last_entry_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiaryReport {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub group_by: ReportGroupBy,
+    pub rows: Vec<DiaryReportRow>,
+    pub total_entries: i64,
+    pub total_minutes: i64,
+}
+
+/// A replaced version of a diary entry
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DiaryRevision {
+    /// 1 for the original text, counting up with each update
+    pub revision: i32,
+    pub working_minutes: i32,
+    pub text: String,
+    pub visibility: String,
+    pub work_date: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub route_ids: Vec<Uuid>,
+    pub notification_ids: Vec<Uuid>,
+    /// When this version was written
+    pub valid_from: DateTime<Utc>,
+    /// When an update replaced it
+    pub replaced_at: DateTime<Utc>,
+    pub replaced_by: Option<Uuid>,
+}
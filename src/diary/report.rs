@@ -0,0 +1,284 @@
+// Working-time reports over `diary_entries.working_minutes`.
+//
+// `GET /diary/report` sums minutes per user, optionally split by week or month,
+// and returns JSON, CSV or iCalendar. Without `diary.report` the report only
+// covers the caller's own entries.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Days, NaiveDate, Utc};
+use std::sync::Arc;
+
+use crate::{
+    auth::{
+        extractor::AuthenticatedUser,
+        login::claims_user_id,
+        permissions::{self, DIARY_REPORT},
+    },
+    diary::models::{DiaryReport, DiaryReportQuery, DiaryReportRow, ReportFormat, ReportGroupBy},
+    AppState,
+};
+
+pub async fn get_diary_report(
+    State(state): State<Arc<AppState>>,
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Query(query): Query<DiaryReportQuery>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = claims_user_id(&claims.sub)?;
+    let all_users = permissions::for_role(&state, &claims.role)
+        .await
+        .contains(DIARY_REPORT);
+
+    let owner_id = match query.owner_id {
+        Some(owner_id) if owner_id != user_id && !all_users => {
+            tracing::warn!(
+                user_id  = %user_id,
+                role     = %claims.role,
+                owner_id = %owner_id,
+                "Diary report on another user denied (403)"
+            );
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "error": "Insufficient permissions",
+                    "permission": DIARY_REPORT
+                })),
+            ));
+        }
+        Some(owner_id) => Some(owner_id),
+        None if all_users => None,
+        None => Some(user_id),
+    };
+
+    // `date_trunc` with a NULL unit yields NULL, which collapses the periods
+    // into one row per user. Entries count on their work date when they have
+    // one, otherwise on the day they were written.
+    let unit = match query.group_by {
+        ReportGroupBy::User => None,
+        ReportGroupBy::Week => Some("week"),
+        ReportGroupBy::Month => Some("month"),
+    };
+
+    let rows = sqlx::query_as::<_, DiaryReportRow>(
+        r#"
+        SELECT
+            d.owner AS owner_id,
+            COALESCE(u.name, 'Deleted user') AS owner,
+            date_trunc($1, d.worked_at AT TIME ZONE 'UTC')::date AS period_start,
+            (date_trunc($1, d.worked_at AT TIME ZONE 'UTC') + ('1 ' || $1)::interval)::date AS period_end,
+            COUNT(*) AS entries,
+            SUM(d.working_minutes)::bigint AS minutes,
+            MIN(d.worked_at) AS first_entry_at,
+            MAX(d.worked_at) AS last_entry_at
+        FROM (
+            SELECT *, COALESCE(work_date::timestamp AT TIME ZONE 'UTC', created_at) AS worked_at
+            FROM diary_entries
+            WHERE deleted_at IS NULL
+        ) d
+        LEFT JOIN users u ON d.owner = u.id
+        WHERE ($2::timestamptz IS NULL OR d.worked_at >= $2)
+          AND ($3::timestamptz IS NULL OR d.worked_at < $3)
+          AND ($4::uuid IS NULL OR d.owner = $4)
+        GROUP BY 1, 2, 3, 4
+        ORDER BY 3 NULLS FIRST, 2, 1
+        "#,
+    )
+    .bind(unit)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(owner_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, user_id = %user_id, "DB error building diary report");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+        )
+    })?;
+
+    let report = DiaryReport {
+        from: query.from,
+        to: query.to,
+        group_by: query.group_by,
+        total_entries: rows.iter().map(|r| r.entries).sum(),
+        total_minutes: rows.iter().map(|r| r.minutes).sum(),
+        rows,
+    };
+
+    let filename = format!("diary-report-{}", report.group_by.as_str());
+    Ok(match query.format {
+        ReportFormat::Json => Json(report).into_response(),
+        ReportFormat::Csv => (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{filename}.csv\""),
+                ),
+            ],
+            to_csv(&report),
+        )
+            .into_response(),
+        ReportFormat::Ics => (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    "text/calendar; charset=utf-8".to_string(),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{filename}.ics\""),
+                ),
+            ],
+            to_ics(&report),
+        )
+            .into_response(),
+    })
+}
+
+/// One line per row, with a byte order mark and CRLF line ends so Excel
+/// detects UTF-8 and splits the columns on open.
+fn to_csv(report: &DiaryReport) -> String {
+    let mut out =
+        String::from("\u{feff}owner_id,owner,period_start,period_end,entries,minutes,hours\r\n");
+    for row in &report.rows {
+        let fields = [
+            row.owner_id.map(|id| id.to_string()).unwrap_or_default(),
+            csv_field(&row.owner),
+            row.period_start.map(|d| d.to_string()).unwrap_or_default(),
+            row.period_end.map(|d| d.to_string()).unwrap_or_default(),
+            row.entries.to_string(),
+            row.minutes.to_string(),
+            format!("{:.2}", row.minutes as f64 / 60.0),
+        ];
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Quote a text field. Values starting with a formula character get a leading
+/// `'` so spreadsheets show them instead of evaluating them.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// An all-day event per row spanning its week or month, or for per-user
+/// rows the days from the first to the last entry.
+fn to_ics(report: &DiaryReport) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//TeleTable//Diary report//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for row in &report.rows {
+        let start = row
+            .period_start
+            .unwrap_or_else(|| row.first_entry_at.date_naive());
+        let end = row.period_end.unwrap_or_else(|| {
+            row.last_entry_at
+                .date_naive()
+                .checked_add_days(Days::new(1))
+                .unwrap_or(NaiveDate::MAX)
+        });
+        let owner = row
+            .owner_id
+            .map_or_else(|| "deleted".to_string(), |id| id.to_string());
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!(
+                "UID:{owner}-{}-{}@teletable",
+                start.format("%Y%m%d"),
+                report.group_by.as_str()
+            ),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+            format!(
+                "SUMMARY:{}: {}h {:02}m",
+                ics_text(&row.owner),
+                row.minutes / 60,
+                row.minutes % 60
+            ),
+            format!(
+                "DESCRIPTION:{} diary entries\\, {} minutes",
+                row.entries, row.minutes
+            ),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in lines {
+        fold_ics_line(&line, &mut out);
+    }
+    out
+}
+
+/// Escape a TEXT value (RFC 5545, section 3.3.11)
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+/// Append `line` folded to at most 75 octets per physical line
+fn fold_ics_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_and_defused() {
+        assert_eq!(csv_field("Alice"), "Alice");
+        assert_eq!(csv_field("Doe, Jane"), "\"Doe, Jane\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("=HYPERLINK(1)"), "'=HYPERLINK(1)");
+    }
+
+    #[test]
+    fn ics_lines_are_folded_at_75_octets() {
+        let mut out = String::new();
+        fold_ics_line(&format!("SUMMARY:{}", "ä".repeat(60)), &mut out);
+        for line in out.split("\r\n").filter(|l| !l.is_empty()) {
+            assert!(line.len() <= 75, "{line:?} is {} octets", line.len());
+        }
+        assert_eq!(
+            out.replace("\r\n ", ""),
+            format!("SUMMARY:{}\r\n", "ä".repeat(60))
+        );
+    }
+}
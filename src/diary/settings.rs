@@ -0,0 +1,92 @@
+// Diary switches, gated by `diary.settings` and stored in `app_settings`.
+//
+// `diary.public_feed` decides whether `GET /diary/all` answers anonymous
+// requests at all. Signed-in users keep seeing team and public entries either
+// way.
+
+use axum::{extract::State, http::StatusCode, Json};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::audit::{actions, AuditEvent};
+use crate::auth::extractor::ClientIp;
+use crate::auth::permissions::{self, RequirePermission};
+use crate::diary::models::DiarySettings;
+use crate::AppState;
+
+const PUBLIC_FEED: &str = "diary.public_feed";
+
+/// Whether the anonymous feed is on. A lookup failure keeps it off.
+pub async fn public_feed_enabled(state: &AppState) -> bool {
+    match sqlx::query_scalar::<_, serde_json::Value>(
+        "SELECT value FROM app_settings WHERE key = $1",
+    )
+    .bind(PUBLIC_FEED)
+    .fetch_optional(&state.db)
+    .await
+    {
+        Ok(value) => value.and_then(|v| v.as_bool()).unwrap_or(true),
+        Err(e) => {
+            tracing::error!(error = %e, "DB error loading diary feed setting");
+            false
+        }
+    }
+}
+
+pub async fn get_diary_settings(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<permissions::DiarySettings>,
+) -> Json<DiarySettings> {
+    Json(DiarySettings {
+        public_feed: public_feed_enabled(&state).await,
+    })
+}
+
+pub async fn update_diary_settings(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { claims, .. }: RequirePermission<permissions::DiarySettings>,
+    ClientIp(client_ip): ClientIp,
+    Json(payload): Json<DiarySettings>,
+) -> Result<Json<DiarySettings>, (StatusCode, Json<serde_json::Value>)> {
+    let before = public_feed_enabled(&state).await;
+
+    sqlx::query(
+        r#"
+        INSERT INTO app_settings (key, value, updated_at, updated_by)
+        VALUES ($1, $2, NOW(), $3)
+        ON CONFLICT (key) DO UPDATE
+        SET value = EXCLUDED.value,
+            updated_at = EXCLUDED.updated_at,
+            updated_by = EXCLUDED.updated_by
+        "#,
+    )
+    .bind(PUBLIC_FEED)
+    .bind(serde_json::json!(payload.public_feed))
+    .bind(Uuid::parse_str(&claims.sub).ok())
+    .execute(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "DB error updating diary feed setting");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+        )
+    })?;
+
+    tracing::info!(
+        public_feed = payload.public_feed,
+        changed_by  = %claims.name,
+        "Diary settings updated"
+    );
+    AuditEvent::new(actions::DIARY_SETTINGS_CHANGE, &claims)
+        .target("setting", PUBLIC_FEED)
+        .before(DiarySettings {
+            public_feed: before,
+        })
+        .after(&payload)
+        .client_ip(&client_ip)
+        .record(&state.db)
+        .await;
+
+    Ok(Json(payload))
+}
@@ -0,0 +1,26 @@
+// Purging of soft-deleted diary entries.
+//
+// `DELETE /diary` only sets `deleted_at`, so the owner can restore the entry
+// with `POST /diary/{id}/restore`. The housekeeping task calls `purge` every
+// `DIARY_PURGE_INTERVAL_SECS` to delete entries, and with them their
+// revisions, once they have been deleted for `DIARY_TRASH_RETENTION_DAYS`.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+/// How long a deleted entry can be restored
+pub const DIARY_TRASH_RETENTION_DAYS: i64 = 30;
+/// How often deleted entries past their retention are purged
+pub const DIARY_PURGE_INTERVAL_SECS: u64 = 3600;
+
+/// Delete entries that were soft-deleted before the retention window ending
+/// at `now`. Returns the number of purged entries.
+pub async fn purge(db: &PgPool, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let cutoff = now - Duration::days(DIARY_TRASH_RETENTION_DAYS);
+    let purged = sqlx::query("DELETE FROM diary_entries WHERE deleted_at < $1")
+        .bind(cutoff)
+        .execute(db)
+        .await?
+        .rows_affected();
+    Ok(purged)
+}
@@ -0,0 +1,16 @@
+// Values of `diary_entries.visibility`. The owner always sees their entries;
+// the visibility decides who else does.
+
+/// Only the owner
+pub const PRIVATE: &str = "private";
+/// Every signed-in user
+pub const TEAM: &str = "team";
+/// Everyone, including the anonymous feed
+pub const PUBLIC: &str = "public";
+
+/// Visibility of entries created without one
+pub const DEFAULT: &str = TEAM;
+
+pub fn is_valid(visibility: &str) -> bool {
+    [PRIVATE, TEAM, PUBLIC].contains(&visibility)
+}
@@ -1,16 +1,19 @@
+pub mod audit;
 pub mod auth;
 pub mod cache;
 pub mod config;
 pub mod database;
 pub mod diary;
 pub mod logging;
+pub mod mail;
 pub mod notifications;
+pub mod rate_limit;
 pub mod robot;
 
-use crate::auth::security::{admin_middleware, auth_middleware};
+use crate::auth::security::{admin_middleware, auth_middleware, optional_auth_middleware};
 use axum::{
     middleware,
-    routing::{delete, get, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 pub use config::Config;
@@ -27,21 +30,48 @@ pub struct AppState {
     pub redis: ConnectionManager,
     pub config: Config,
     pub robot_state: SharedRobotState,
-    pub static_nodes: Vec<robot::models::RobotNode>,
     pub http_client: reqwest::Client,
+    pub mailer: Arc<dyn mail::Mailer>,
 }
 
 pub fn create_router(state: Arc<AppState>) -> Router {
+    let rate_limiter = Arc::new(rate_limit::RateLimiter::new(&state));
+
     // public routes (no authentication required)
     let public_routes = Router::new()
         .route("/", get(root))
         .route("/register", post(auth::login::register))
         .route("/login", post(auth::login::login))
-        .route("/diary/all", get(diary::handlers::get_all_diaries));
+        .route("/login/2fa", post(auth::two_factor::login_two_factor))
+        .route("/token/refresh", post(auth::login::refresh_token))
+        .route("/password/forgot", post(auth::account::forgot_password))
+        .route("/password/reset", post(auth::account::reset_password))
+        .route("/email/verify", post(auth::account::verify_email));
+
+    // routes that serve anonymous callers but honour a presented token
+    let optional_auth_routes = Router::new()
+        .route("/diary/all", get(diary::handlers::get_all_diaries))
+        .route(
+            "/email/verify/resend",
+            post(auth::account::resend_verification_email),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            optional_auth_middleware,
+        ));
 
     // protected routes (authentication required)
     let protected_routes = Router::new()
         .route("/me", get(auth::login::get_me))
+        .route("/me", patch(auth::profile::update_me))
+        .route("/me", delete(auth::profile::delete_me))
+        .route("/me/password", post(auth::profile::change_password))
+        .route("/logout", post(auth::login::logout))
+        .route("/me/sessions", get(auth::login::get_my_sessions))
+        .route("/me/sessions/{id}", delete(auth::login::delete_my_session))
+        .route("/me/2fa/setup", post(auth::two_factor::setup))
+        .route("/me/2fa/verify", post(auth::two_factor::verify))
+        .route("/me/2fa/disable", post(auth::two_factor::disable))
         .route(
             "/robot/notifications",
             get(notifications::handlers::get_notification_history),
@@ -49,21 +79,47 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/diary", post(diary::handlers::create_or_update_diary))
         .route("/diary", get(diary::handlers::get_diary))
         .route("/diary", delete(diary::handlers::delete_diary))
+        .route("/diary/report", get(diary::report::get_diary_report))
+        .route(
+            "/diary/{id}/revisions",
+            get(diary::handlers::get_diary_revisions),
+        )
+        .route("/diary/{id}/restore", post(diary::handlers::restore_diary))
+        // administration, gated by users.manage / audit.view / diary.settings
+        .route("/user", get(auth::login::get_user))
+        .route("/user", post(auth::login::update_user))
+        .route("/user", delete(auth::login::delete_user))
+        .route("/users", get(auth::login::get_users))
+        .route(
+            "/users/{id}/sessions",
+            get(auth::login::get_user_sessions).delete(auth::login::delete_user_sessions),
+        )
+        .route("/user/{id}/sessions", get(auth::login::get_user_sessions))
+        .route("/lockouts", get(auth::login::get_lockouts))
+        .route("/lockouts", delete(auth::login::clear_lockout))
+        .route("/audit", get(audit::handlers::get_audit_events))
+        .route("/diary/settings", get(diary::settings::get_diary_settings))
+        .route(
+            "/diary/settings",
+            put(diary::settings::update_diary_settings),
+        )
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
         ));
 
-    // admin routes (authentication + admin role required)
+    // admin routes (authentication + admin role required), so the permission
+    // mapping can always be repaired
     let admin_routes = Router::new()
-        .route("/users", get(auth::login::get_users))
-        .route("/users/{id}/sessions", get(auth::login::get_user_sessions))
-        .route("/user/{id}/sessions", get(auth::login::get_user_sessions))
-        .route("/user", get(auth::login::get_user))
-        .route("/user", post(auth::login::update_user))
-        .route("/user", delete(auth::login::delete_user))
-        .route("/robot/debug", get(robot::client_routes::get_robot_debug))
-        .route_layer(middleware::from_fn(admin_middleware))
+        .route("/permissions", get(auth::permissions::list_permissions))
+        .route(
+            "/roles/{role}/permissions",
+            put(auth::permissions::set_role_permissions),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            admin_middleware,
+        ))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
@@ -96,6 +152,7 @@ pub fn create_router(state: Arc<AppState>) -> Router {
     // robot control routes (called by authenticated user)
     let robot_control_routes = Router::new()
         .route("/nodes", get(robot::client_routes::get_nodes))
+        .route("/robots", get(robot::client_routes::list_robots))
         .route("/routes", get(robot::queue_routes::get_routes))
         .route("/routes", post(robot::queue_routes::add_route))
         .route("/routes/{id}", delete(robot::queue_routes::delete_route))
@@ -110,6 +167,45 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             "/robot/check",
             get(robot::client_routes::check_robot_connection),
         )
+        .route(
+            "/robot/telemetry",
+            get(robot::telemetry_routes::get_robot_telemetry),
+        )
+        // fleet administration, gated by robot.debug / routes.history /
+        // nodes.manage / robot.keys
+        .route("/robot/debug", get(robot::client_routes::get_robot_debug))
+        .route(
+            "/routes/history",
+            get(robot::history_routes::get_route_history),
+        )
+        .route("/routes/stats", get(robot::history_routes::get_route_stats))
+        .route("/graph/edges", get(robot::graph_routes::list_edges))
+        .route("/graph/edges", put(robot::graph_routes::upsert_edge))
+        .route(
+            "/graph/edges/{from}/{to}",
+            delete(robot::graph_routes::delete_edge),
+        )
+        .route(
+            "/graph/learn",
+            post(robot::graph_routes::learn_edge_weights),
+        )
+        .route("/graph/path", get(robot::graph_routes::get_path_cost))
+        .route("/nodes", post(robot::node_routes::create_node))
+        .route("/nodes/all", get(robot::node_routes::list_all_nodes))
+        .route("/nodes/order", put(robot::node_routes::reorder_nodes))
+        .route("/nodes/{id}", patch(robot::node_routes::update_node))
+        .route(
+            "/robots/{robot_id}/keys",
+            get(robot::key_routes::list_robot_keys),
+        )
+        .route(
+            "/robots/{robot_id}/keys",
+            post(robot::key_routes::issue_robot_key),
+        )
+        .route(
+            "/robots/{robot_id}/keys/{key_id}",
+            delete(robot::key_routes::revoke_robot_key),
+        )
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
@@ -117,10 +213,16 @@ pub fn create_router(state: Arc<AppState>) -> Router {
 
     Router::new()
         .merge(public_routes)
+        .merge(optional_auth_routes)
         .merge(protected_routes)
         .merge(admin_routes)
         .merge(robot_api_routes)
         .merge(robot_control_routes)
+        // Route and role budgets, before any authentication work.
+        .layer(middleware::from_fn_with_state(
+            rate_limiter,
+            rate_limit::rate_limit_middleware,
+        ))
         // HTTP request/response tracing (method, path, status, latency).
         // Logged at DEBUG level so they don't spam INFO logs by default.
         .layer(TraceLayer::new_for_http())
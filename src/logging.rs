@@ -9,10 +9,23 @@
 //
 // To enable debug output:  `RUST_LOG=debug`
 // To enable sqlx queries:  `RUST_LOG=info,sqlx=debug`
+//
+// Fields whose name contains a sensitive word (password, token, API key,
+// secret, fingerprint) are written as `[REDACTED]` by both sinks. Add more
+// words with `LOG_REDACT_FIELDS=word1,word2`.
 
+use std::sync::Arc;
+use tracing::field::{Field, Visit};
+use tracing::Subscriber;
 use tracing_appender::non_blocking;
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::{format::Writer, FormatFields, MakeWriter};
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+/// Field name fragments that are always redacted
+const DEFAULT_REDACTED_FIELDS: &[&str] = &["password", "token", "api_key", "secret", "fingerprint"];
+const REDACTED: &str = "[REDACTED]";
+
 // Initialise the global tracing subscriber.
 //
 // Returns a [`WorkerGuard`] that **must** be kept alive for the entire
@@ -31,20 +44,225 @@ pub fn init() -> non_blocking::WorkerGuard {
         EnvFilter::new("info,sqlx=warn,hyper=warn,tungstenite=warn,tower=warn,h2=warn")
     });
 
+    let redaction = RedactingFields::new(std::env::var("LOG_REDACT_FIELDS").ok().as_deref());
+
+    subscriber(env_filter, std::io::stdout, file_writer, redaction).init();
+
+    guard
+}
+
+/// The stdout and file layers, both formatting fields through `redaction`.
+fn subscriber<S, F>(
+    env_filter: EnvFilter,
+    stdout_writer: S,
+    file_writer: F,
+    redaction: RedactingFields,
+) -> impl Subscriber + Send + Sync + 'static
+where
+    S: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+    F: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
     // Stdout layer – colourised, intended for docker logs / terminals.
-    let stdout_layer = fmt::layer().with_target(true).with_ansi(true);
+    let stdout_layer = fmt::layer()
+        .with_target(true)
+        .with_ansi(true)
+        .fmt_fields(redaction.clone())
+        .with_writer(stdout_writer);
 
     // File layer – plain text (no ANSI escape codes).
     let file_layer = fmt::layer()
         .with_target(true)
         .with_ansi(false)
+        .fmt_fields(redaction)
         .with_writer(file_writer);
 
     tracing_subscriber::registry()
         .with(env_filter)
         .with(stdout_layer)
         .with(file_layer)
-        .init();
+}
 
-    guard
+/// Formats event and span fields like the default `key=value` formatter, but
+/// masks the values of sensitive fields.
+#[derive(Clone)]
+pub struct RedactingFields {
+    /// Normalized fragments; a field is sensitive if its name contains one
+    fragments: Arc<[String]>,
+}
+
+/// Lowercase without separators, so `api_key`, `apiKey` and `API-KEY` match
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+impl RedactingFields {
+    /// The default fragments plus the comma-separated `extra` ones.
+    pub fn new(extra: Option<&str>) -> Self {
+        let fragments = DEFAULT_REDACTED_FIELDS
+            .iter()
+            .copied()
+            .chain(extra.unwrap_or_default().split(','))
+            .map(normalize)
+            .filter(|f| !f.is_empty())
+            .collect();
+        Self { fragments }
+    }
+
+    pub fn is_sensitive(&self, field: &str) -> bool {
+        let field = normalize(field);
+        self.fragments.iter().any(|f| field.contains(f.as_str()))
+    }
+}
+
+impl<'writer> FormatFields<'writer> for RedactingFields {
+    fn format_fields<R: RecordFields>(
+        &self,
+        writer: Writer<'writer>,
+        fields: R,
+    ) -> std::fmt::Result {
+        let mut visitor = RedactingVisitor {
+            redaction: self,
+            writer,
+            result: Ok(()),
+            is_empty: true,
+        };
+        fields.record(&mut visitor);
+        visitor.result
+    }
+}
+
+struct RedactingVisitor<'a, 'writer> {
+    redaction: &'a RedactingFields,
+    writer: Writer<'writer>,
+    result: std::fmt::Result,
+    is_empty: bool,
+}
+
+impl Visit for RedactingVisitor<'_, '_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.record_debug(field, &format_args!("{value}"));
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if self.result.is_err() {
+            return;
+        }
+        if !self.is_empty {
+            self.result = self.writer.write_char(' ');
+        }
+        self.is_empty = false;
+
+        let name = field.name();
+        self.result = self.result.and_then(|_| {
+            if name == "message" {
+                write!(self.writer, "{value:?}")
+            } else if self.redaction.is_sensitive(name) {
+                write!(self.writer, "{name}={REDACTED}")
+            } else {
+                write!(self.writer, "{name}={value:?}")
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Sink {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_no_secret_reaches_either_sink() {
+        let stdout = Sink::default();
+        let file = Sink::default();
+        let (stdout_writer, file_writer) = (stdout.clone(), file.clone());
+        let subscriber = subscriber(
+            EnvFilter::new("debug"),
+            move || stdout_writer.clone(),
+            move || file_writer.clone(),
+            RedactingFields::new(Some("otp_code, ")),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", refresh_token = "rt_span_secret");
+            let _entered = span.enter();
+
+            tracing::warn!(
+                email              = %"jane@example.com",
+                attempted_password = %"hunter2",
+                "Failed login attempt - wrong password"
+            );
+            tracing::info!(
+                token            = "eyJ.access.token",
+                robot_api_key    = ?"rk_robot_secret",
+                apiKey           = "camel_case_secret",
+                fingerprint_data = ?serde_json::json!({"gpu": "gpu_secret"}),
+                otp_code         = 123456,
+                user_id          = 42,
+                "Mixed fields"
+            );
+        });
+
+        for (sink, contents) in [("stdout", stdout.contents()), ("file", file.contents())] {
+            for secret in [
+                "hunter2",
+                "eyJ.access.token",
+                "rk_robot_secret",
+                "gpu_secret",
+                "rt_span_secret",
+                "camel_case_secret",
+                "123456",
+            ] {
+                assert!(
+                    !contents.contains(secret),
+                    "{secret} leaked to {sink}: {contents}"
+                );
+            }
+            assert!(contents.contains("Failed login attempt - wrong password"));
+            assert!(contents.contains("jane@example.com"));
+            assert!(contents.contains("42"));
+            assert!(contents.contains(REDACTED));
+        }
+    }
+
+    #[test]
+    fn test_sensitive_field_names() {
+        let redaction = RedactingFields::new(None);
+        assert!(redaction.is_sensitive("password"));
+        assert!(redaction.is_sensitive("attempted_password"));
+        assert!(redaction.is_sensitive("Refresh_Token"));
+        assert!(redaction.is_sensitive("robot_api_key"));
+        assert!(redaction.is_sensitive("apiKey"));
+        assert!(redaction.is_sensitive("fingerprint_data"));
+        assert!(!redaction.is_sensitive("email"));
+        assert!(!redaction.is_sensitive("session_id"));
+        assert!(!redaction.is_sensitive("key_prefix"));
+    }
 }
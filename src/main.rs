@@ -3,17 +3,6 @@ use backend::{
 };
 use std::sync::Arc;
 
-const STATIC_ROUTE_NODES: &[(&str, &str)] = &[
-    ("apotheke", "Apotheke"),
-    ("raum6", "Raum 6"),
-    ("raum5", "Raum 5"),
-    ("raum3", "Raum 3"),
-    ("raum4", "Raum 4"),
-    ("raum2", "Raum 2"),
-    ("raum1", "Raum 1"),
-    ("mensa", "Mensa"),
-];
-
 #[tokio::main]
 async fn main() {
     // Initialise logging first – guard must live for the entire process lifetime.
@@ -55,11 +44,21 @@ async fn main() {
 
     // Log server configuration (never log secret values).
     tracing::info!(
-        server_address  = %config.server_address,
-        jwt_expiry_hours = config.jwt_expiry_hours,
+        server_address           = %config.server_address,
+        access_token_ttl_minutes = config.access_token_ttl_minutes,
+        refresh_token_ttl_days   = config.refresh_token_ttl_days,
+        trusted_proxies          = config.trusted_proxies.len(),
         "Server configuration loaded"
     );
 
+    if let Err(e) = config.check_robot_keys() {
+        tracing::error!(error = %e, "Refusing to start with the default robot API key");
+        panic!("{e}");
+    }
+    if config.allow_default_robot_key {
+        tracing::warn!("ALLOW_DEFAULT_ROBOT_KEY is set - do not use this in production");
+    }
+
     let db = create_pool(&config.database_url)
         .await
         .expect("Failed to create database pool");
@@ -81,7 +80,44 @@ async fn main() {
         }
     }
 
-    let robot_state = SharedRobotState::new();
+    // Migrations may grant new default permissions; drop stale role lookups.
+    if let Err(e) =
+        backend::cache::CacheService::invalidate_all_role_permissions(&mut redis.clone()).await
+    {
+        tracing::warn!(error = %e, "Failed to clear cached role permissions");
+    }
+
+    // The fleet is every robot with an environment key or an issued key;
+    // with neither, the default robot waits for an admin to issue its key.
+    let mut robot_ids: Vec<String> = config.robot_keys().into_iter().map(|(id, _)| id).collect();
+    match backend::robot::key_store::active_robot_ids(&db).await {
+        Ok(ids) => robot_ids.extend(ids),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to load robot API keys");
+            panic!("Failed to load robot API keys: {e}");
+        }
+    }
+    robot_ids.sort();
+    robot_ids.dedup();
+    if robot_ids.is_empty() {
+        robot_ids.push(backend::robot::state::DEFAULT_ROBOT_ID.to_string());
+    }
+    tracing::info!(robots = ?robot_ids, "Robot fleet configured");
+    let robot_state = SharedRobotState::with_robots(&robot_ids);
+    match backend::robot::route_store::rehydrate(&db, &robot_state).await {
+        Ok(restored) => tracing::info!(restored, "Route queue rehydrated from database"),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to rehydrate route queue");
+            panic!("Failed to rehydrate route queue: {e}");
+        }
+    }
+    match backend::robot::node_store::refresh(&db, &robot_state).await {
+        Ok(loaded) => tracing::info!(loaded, "Navigation nodes loaded from database"),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to load navigation nodes");
+            panic!("Failed to load navigation nodes: {e}");
+        }
+    }
 
     // Create reusable HTTP client with optimised settings.
     let http_client = reqwest::Client::builder()
@@ -91,21 +127,26 @@ async fn main() {
         .build()
         .expect("Failed to create HTTP client");
 
+    let mailer = match backend::mail::from_config(&config) {
+        Ok(mailer) => mailer,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to set up mail transport");
+            panic!("Failed to set up mail transport: {e}");
+        }
+    };
+    tracing::info!(transport = %config.mail_transport, "Mail transport configured");
+
     let state = Arc::new(AppState {
         db,
         redis,
         config: config.clone(),
         robot_state: robot_state.clone(),
-        static_nodes: STATIC_ROUTE_NODES
-            .iter()
-            .map(|(id, label)| backend::robot::models::RobotNode {
-                id: (*id).to_string(),
-                label: (*label).to_string(),
-            })
-            .collect(),
         http_client,
+        mailer,
     });
 
+    let housekeeping = backend::robot::housekeeping::spawn(state.clone());
+
     let app = create_router(state);
 
     let server_address = config.server_address.clone();
@@ -125,6 +166,8 @@ async fn main() {
     .await
     .expect("Server error");
 
+    housekeeping.shutdown().await;
+
     tracing::info!("Server stopped");
 }
 
@@ -6,35 +6,22 @@ use axum::{
 use std::sync::Arc;
 
 use crate::{
-    auth::{extractor::AuthenticatedUser, roles},
+    auth::permissions::{RequirePermission, RobotView},
     notifications::models::{NotificationHistoryQuery, RobotNotification},
     AppState,
 };
 
 pub async fn get_notification_history(
     State(state): State<Arc<AppState>>,
-    AuthenticatedUser(claims): AuthenticatedUser,
+    _: RequirePermission<RobotView>,
     Query(query): Query<NotificationHistoryQuery>,
 ) -> Result<Json<Vec<RobotNotification>>, (StatusCode, Json<serde_json::Value>)> {
-    if !roles::can_view(&claims.role) {
-        tracing::warn!(
-            user_id = %claims.sub,
-            name    = %claims.name,
-            role    = %claims.role,
-            "Permission denied - notification history requires viewer role or above (403)"
-        );
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({ "error": "Insufficient permissions" })),
-        ));
-    }
-
     let limit = query.limit.unwrap_or(100).clamp(1, 500);
     let offset = query.offset.unwrap_or(0).max(0);
 
     let notifications = sqlx::query_as::<_, RobotNotification>(
         r#"
-        SELECT id, priority, message, received_at
+        SELECT id, robot_id, priority, message, received_at
         FROM robot_notifications
         ORDER BY received_at DESC
         LIMIT $1 OFFSET $2
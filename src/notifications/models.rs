@@ -6,6 +6,7 @@ use uuid::Uuid;
 #[serde(rename_all = "camelCase")]
 pub struct RobotNotification {
     pub id: Uuid,
+    pub robot_id: Option<String>,
     pub priority: String,
     pub message: String,
     pub received_at: DateTime<Utc>,
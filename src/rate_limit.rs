@@ -0,0 +1,427 @@
+// Request budgets for every route, checked before authentication and handlers.
+//
+// Each request is charged against up to two budgets of its caller:
+//   - the first `RATE_LIMIT_ROUTES` rule matching the route, e.g. `POST /login=10/60`
+//   - the budget of the caller's role from `RATE_LIMIT_ROLES`, shared by all routes
+//
+// The caller is the user of a valid access token (Bearer header, or `?token=`
+// on WebSocket upgrades) and otherwise the client IP, which uses the
+// `anonymous` role. The role is the one in the token; the auth middleware's
+// database refresh happens later.
+//
+// Budgets use GCRA: `N/S` allows a burst of N requests and then one more every
+// S/N seconds. The theoretical arrival time of each caller is stored in Redis
+// under `ratelimit:gcra:<budget>:<subject>` and updated by a Lua script, so all
+// instances share it. While Redis fails, each process keeps its own state.
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use redis::aio::ConnectionManager;
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use crate::auth::security::decode_jwt;
+use crate::config::TrustedProxy;
+use crate::AppState;
+
+/// Role of callers without a valid access token
+pub const ANONYMOUS_ROLE: &str = "anonymous";
+
+/// In-memory entries kept before expired ones are pruned
+const FALLBACK_PRUNE_THRESHOLD: usize = 10_000;
+
+/// KEYS[1] = state key, ARGV = now, emission interval, burst tolerance (ms).
+/// Returns `{1, 0}` when allowed, `{0, retry_after_ms}` otherwise.
+const GCRA_SCRIPT: &str = r#"
+local now = tonumber(ARGV[1])
+local interval = tonumber(ARGV[2])
+local tolerance = tonumber(ARGV[3])
+local tat = tonumber(redis.call('GET', KEYS[1]) or now)
+if tat < now then tat = now end
+local new_tat = tat + interval
+local allow_at = new_tat - tolerance
+if now < allow_at then return {0, allow_at - now} end
+redis.call('SET', KEYS[1], string.format('%d', new_tat), 'PX', string.format('%d', new_tat - now))
+return {1, 0}
+"#;
+
+static GCRA: LazyLock<redis::Script> = LazyLock::new(|| redis::Script::new(GCRA_SCRIPT));
+
+/// `requests` per `period_seconds`, written `N/S`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub requests: u32,
+    pub period_seconds: u32,
+}
+
+impl Budget {
+    pub const fn new(requests: u32, period_seconds: u32) -> Self {
+        Self {
+            requests,
+            period_seconds,
+        }
+    }
+
+    fn parse(raw: &str) -> Option<Self> {
+        let (requests, period) = raw.trim().split_once('/')?;
+        let budget = Self::new(requests.trim().parse().ok()?, period.trim().parse().ok()?);
+        (budget.requests > 0 && budget.period_seconds > 0).then_some(budget)
+    }
+
+    /// Time one request adds to the caller's arrival time
+    fn interval_ms(&self) -> i64 {
+        (i64::from(self.period_seconds) * 1000 / i64::from(self.requests)).max(1)
+    }
+
+    /// How far the arrival time may run ahead of now: the burst size
+    fn tolerance_ms(&self) -> i64 {
+        self.interval_ms() * i64::from(self.requests)
+    }
+
+    /// GCRA step: the new arrival time, or the milliseconds until the request
+    /// would be allowed.
+    fn check(&self, tat: Option<i64>, now_ms: i64) -> Result<i64, i64> {
+        let new_tat = tat.unwrap_or(now_ms).max(now_ms) + self.interval_ms();
+        let allow_at = new_tat - self.tolerance_ms();
+        if now_ms < allow_at {
+            Err(allow_at - now_ms)
+        } else {
+            Ok(new_tat)
+        }
+    }
+}
+
+/// Budget for one route, by method (any if `None`) and path template.
+/// A template ending in `*` matches every route starting with the rest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteBudget {
+    pub method: Option<String>,
+    pub path: String,
+    pub budget: Budget,
+}
+
+impl RouteBudget {
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        let method_matches = self
+            .method
+            .as_deref()
+            .is_none_or(|m| m.eq_ignore_ascii_case(method.as_str()));
+        let path_matches = match self.path.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == self.path,
+        };
+        method_matches && path_matches
+    }
+
+    /// The rule as configured, used in Redis keys and logs
+    fn label(&self) -> String {
+        match &self.method {
+            Some(method) => format!("{method} {}", self.path),
+            None => self.path.clone(),
+        }
+    }
+}
+
+/// Budgets used when `RATE_LIMIT_ROUTES` is unset or empty
+pub fn default_route_budgets() -> Vec<RouteBudget> {
+    parse_route_budgets(
+        "POST /login=10/60,POST /login/2fa=10/60,POST /token/refresh=30/60,\
+         POST /password/forgot=5/300,POST /email/verify/resend=5/300,POST /me/password=5/300,POST /routes/select=30/60,/drive/lock=30/60,\
+         POST /table/event=300/60,GET /ws/*=20/60,GET /diary/all=60/60",
+    )
+}
+
+/// Budgets used when `RATE_LIMIT_ROLES` is unset or empty
+pub fn default_role_budgets() -> Vec<(String, Budget)> {
+    parse_role_budgets("anonymous=600/60,Viewer=600/60,Operator=1200/60,Admin=1200/60")
+}
+
+/// Parse `METHOD /path=N/S,/other/*=N/S`. Malformed entries are skipped, so
+/// `off` yields no budgets.
+pub fn parse_route_budgets(raw: &str) -> Vec<RouteBudget> {
+    raw.split(',')
+        .filter_map(|entry| {
+            let (route, budget) = entry.trim().rsplit_once('=')?;
+            let budget = Budget::parse(budget)?;
+            let (method, path) = match route.trim().split_once(' ') {
+                Some((method, path)) => (Some(method.trim().to_uppercase()), path.trim()),
+                None => (None, route.trim()),
+            };
+            path.starts_with('/').then(|| RouteBudget {
+                method,
+                path: path.to_string(),
+                budget,
+            })
+        })
+        .collect()
+}
+
+/// Parse `Role=N/S,anonymous=N/S`. Malformed entries are skipped.
+pub fn parse_role_budgets(raw: &str) -> Vec<(String, Budget)> {
+    raw.split(',')
+        .filter_map(|entry| {
+            let (role, budget) = entry.trim().split_once('=')?;
+            let role = role.trim();
+            (!role.is_empty()).then_some((role.to_string(), Budget::parse(budget)?))
+        })
+        .collect()
+}
+
+pub struct RateLimiter {
+    redis: ConnectionManager,
+    jwt_secret: String,
+    routes: Vec<RouteBudget>,
+    roles: Vec<(String, Budget)>,
+    trusted_proxies: Vec<TrustedProxy>,
+    /// Arrival times used while Redis is unavailable
+    fallback: Mutex<HashMap<String, i64>>,
+    redis_down: AtomicBool,
+}
+
+impl RateLimiter {
+    pub fn new(state: &AppState) -> Self {
+        Self {
+            redis: state.redis.clone(),
+            jwt_secret: state.config.jwt_secret.clone(),
+            routes: state.config.rate_limit_routes.clone(),
+            roles: state.config.rate_limit_roles.clone(),
+            trusted_proxies: state.config.trusted_proxies.clone(),
+            fallback: Mutex::new(HashMap::new()),
+            redis_down: AtomicBool::new(false),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.routes.is_empty() || !self.roles.is_empty()
+    }
+
+    /// `(subject, role)` of the request; `None` for anonymous callers whose
+    /// IP is unknown, who cannot be told apart. The IP is the one the auth
+    /// handlers see, so forwarded headers only count from trusted proxies.
+    fn caller(&self, req: &Request) -> Option<(String, String)> {
+        let bearer = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        let query_token = req.uri().query().and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+        });
+
+        if let Some(claims) = bearer
+            .or(query_token)
+            .and_then(|token| decode_jwt(token, &self.jwt_secret).ok())
+        {
+            return Some((format!("user:{}", claims.sub), claims.role));
+        }
+
+        let client_addr = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| *addr);
+        let client_ip = crate::auth::login::extract_client_ip(
+            req.headers(),
+            client_addr,
+            &self.trusted_proxies,
+        );
+        (client_ip != "unknown").then(|| (format!("ip:{client_ip}"), ANONYMOUS_ROLE.to_string()))
+    }
+
+    /// Charge one request to `key`. Returns the milliseconds to wait if the
+    /// budget is exhausted.
+    async fn charge(&self, key: &str, budget: Budget) -> Result<(), i64> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let mut redis = self.redis.clone();
+
+        let result: Result<(i64, i64), redis::RedisError> = GCRA
+            .key(key)
+            .arg(now_ms)
+            .arg(budget.interval_ms())
+            .arg(budget.tolerance_ms())
+            .invoke_async(&mut redis)
+            .await;
+
+        match result {
+            Ok((allowed, retry_after_ms)) => {
+                if self.redis_down.swap(false, Ordering::Relaxed) {
+                    tracing::info!("Rate limiter is using Redis again");
+                }
+                if allowed == 1 {
+                    Ok(())
+                } else {
+                    Err(retry_after_ms)
+                }
+            }
+            Err(e) => {
+                if !self.redis_down.swap(true, Ordering::Relaxed) {
+                    tracing::warn!(error = %e, "Rate limiter falling back to in-memory state");
+                }
+                self.charge_in_memory(key, budget, now_ms)
+            }
+        }
+    }
+
+    fn charge_in_memory(&self, key: &str, budget: Budget, now_ms: i64) -> Result<(), i64> {
+        let mut tats = self.fallback.lock().unwrap_or_else(|e| e.into_inner());
+        if tats.len() >= FALLBACK_PRUNE_THRESHOLD {
+            tats.retain(|_, tat| *tat > now_ms);
+        }
+
+        let new_tat = budget.check(tats.get(key).copied(), now_ms)?;
+        tats.insert(key.to_string(), new_tat);
+        Ok(())
+    }
+}
+
+/// Refuse requests over the caller's route or role budget with `429`.
+pub async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !limiter.is_enabled() {
+        return next.run(req).await;
+    }
+    let Some((subject, role)) = limiter.caller(&req) else {
+        return next.run(req).await;
+    };
+
+    let method = req.method().clone();
+    let path = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    let route = limiter
+        .routes
+        .iter()
+        .find(|r| r.matches(&method, &path))
+        .map(|r| {
+            (
+                format!("ratelimit:gcra:route:{}:{subject}", r.label()),
+                r.budget,
+            )
+        });
+    let role_budget = limiter
+        .roles
+        .iter()
+        .find(|(r, _)| r.eq_ignore_ascii_case(&role))
+        .map(|(r, budget)| (format!("ratelimit:gcra:role:{r}:{subject}"), *budget));
+
+    for (key, budget) in route.into_iter().chain(role_budget) {
+        if let Err(retry_after_ms) = limiter.charge(&key, budget).await {
+            // Round up, so a client waiting this long is let through.
+            let retry_after_seconds = (retry_after_ms + 999) / 1000;
+            tracing::warn!(
+                subject = %subject,
+                role    = %role,
+                method  = %method,
+                path    = %path,
+                budget  = %key,
+                retry_after_seconds,
+                "Rate limit exceeded (429)"
+            );
+            return too_many_requests(retry_after_seconds);
+        }
+    }
+
+    next.run(req).await
+}
+
+fn too_many_requests(retry_after_seconds: i64) -> Response {
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(json!({
+            "error": "Too many requests. Please try again later.",
+            "retry_after_seconds": retry_after_seconds
+        })),
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_seconds));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_budgets() {
+        let routes = parse_route_budgets(
+            " post /login=10/60, /drive/lock = 5/30,GET /ws/*=2/1,bad,/x=0/60,nope=1/1",
+        );
+        assert_eq!(
+            routes,
+            vec![
+                RouteBudget {
+                    method: Some("POST".into()),
+                    path: "/login".into(),
+                    budget: Budget::new(10, 60),
+                },
+                RouteBudget {
+                    method: None,
+                    path: "/drive/lock".into(),
+                    budget: Budget::new(5, 30),
+                },
+                RouteBudget {
+                    method: Some("GET".into()),
+                    path: "/ws/*".into(),
+                    budget: Budget::new(2, 1),
+                },
+            ]
+        );
+        assert!(routes[0].matches(&Method::POST, "/login"));
+        assert!(!routes[0].matches(&Method::POST, "/login/2fa"));
+        assert!(routes[1].matches(&Method::DELETE, "/drive/lock"));
+        assert!(routes[2].matches(&Method::GET, "/ws/robot/events"));
+        assert!(!routes[2].matches(&Method::POST, "/ws/robot/events"));
+
+        assert_eq!(
+            parse_role_budgets("Admin=100/60, anonymous=10/60,=1/1,Viewer=1"),
+            vec![
+                ("Admin".to_string(), Budget::new(100, 60)),
+                ("anonymous".to_string(), Budget::new(10, 60)),
+            ]
+        );
+        assert!(parse_route_budgets("off").is_empty());
+        assert!(parse_role_budgets("off").is_empty());
+        assert_eq!(default_route_budgets().len(), 11);
+    }
+
+    #[test]
+    fn test_gcra_allows_bursts_then_refills() {
+        let budget = Budget::new(3, 60);
+        let now = 1_000_000;
+
+        let mut tat = None;
+        for _ in 0..3 {
+            tat = Some(budget.check(tat, now).expect("within the burst"));
+        }
+        assert_eq!(budget.check(tat, now), Err(20_000));
+
+        // One request refills every 20 seconds.
+        assert_eq!(budget.check(tat, now + 19_999), Err(1));
+        let refilled = budget.check(tat, now + 20_000).unwrap();
+        assert!(budget.check(Some(refilled), now + 20_000).is_err());
+
+        // A long pause restores the full burst, not more.
+        let mut tat = Some(refilled);
+        for _ in 0..3 {
+            tat = Some(budget.check(tat, now + 600_000).unwrap());
+        }
+        assert!(budget.check(tat, now + 600_000).is_err());
+    }
+}
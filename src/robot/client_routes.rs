@@ -1,19 +1,26 @@
+use crate::audit::{actions, AuditEvent};
+use crate::auth::extractor::ClientIp;
 use crate::auth::models::Claims;
-use crate::auth::roles;
+use crate::auth::permissions::{self, RequirePermission, RobotDebug, RobotDrive, RobotNavigate};
 use crate::auth::security::decode_jwt;
 use crate::notifications::models::RobotNotification;
 use crate::robot::models::{
-    NodesResponse, QueuedRoute, RobotCommand, RobotStatusUpdate, RouteSelectionRequest,
+    NodesResponse, QueuedRoute, RobotCommand, RobotIdQuery, RobotStatusUpdate, RobotSummary,
+    RouteSelectionRequest,
 };
+use crate::robot::robot_routes::{authenticate_robot, ROBOT_WS_PROTOCOL};
+use crate::robot::route_store;
+use crate::robot::route_validation;
+use crate::robot::state::RobotHandle;
 use crate::AppState;
 use axum::{
     extract::{
-        ws::{Message, WebSocket},
+        ws::{CloseFrame, Message, WebSocket},
         Query, State, WebSocketUpgrade,
     },
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
-    Extension, Json,
+    Json,
 };
 
 use chrono::Utc;
@@ -21,18 +28,60 @@ use futures::stream::StreamExt;
 use serde::Deserialize;
 use serde::Serialize;
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
 use uuid::Uuid;
 
+/// Close code sent when the socket's login session is revoked (policy violation)
+const SESSION_REVOKED_CLOSE_CODE: u16 = 1008;
+
+/// Resolve an optional `robot_id` parameter to a robot: 400 if it is omitted
+/// while the fleet has several robots, 404 if no such robot exists.
+pub(crate) async fn resolve_robot(
+    state: &AppState,
+    robot_id: Option<&str>,
+) -> Result<RobotHandle, (StatusCode, Json<serde_json::Value>)> {
+    if let Some(robot) = state.robot_state.resolve_robot(robot_id).await {
+        return Ok(robot);
+    }
+
+    Err(match robot_id {
+        Some(id) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Unknown robot: {id}")
+            })),
+        ),
+        None => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "robot_id required"
+            })),
+        ),
+    })
+}
+
 pub async fn robot_control_ws(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
-    ws.on_upgrade(|socket| handle_robot_socket(socket, state))
+    let robot = match authenticate_robot(&state, &headers, "/ws/robot/control").await {
+        Ok(robot_id) => state.robot_state.ensure_robot(&robot_id).await,
+        Err(response) => return response.into_response(),
+    };
+
+    tracing::info!(robot_id = %robot.id, "Robot control WebSocket connected");
+
+    ws.protocols([ROBOT_WS_PROTOCOL])
+        .on_upgrade(|socket| handle_robot_socket(socket, robot))
+        .into_response()
 }
 
-async fn handle_robot_socket(mut socket: WebSocket, state: Arc<AppState>) {
-    let mut rx = state.robot_state.command_sender.subscribe();
-    let mut audio_rx = state.robot_state.audio_sender.subscribe();
+async fn handle_robot_socket(mut socket: WebSocket, robot: RobotHandle) {
+    let mut rx = robot.command_sender.subscribe();
+    let mut audio_rx = robot.audio_sender.subscribe();
 
     loop {
         tokio::select! {
@@ -67,12 +116,14 @@ async fn handle_robot_socket(mut socket: WebSocket, state: Arc<AppState>) {
 #[derive(Deserialize)]
 pub struct WsParams {
     token: String,
+    robot_id: Option<String>,
 }
 
 pub async fn manual_control_ws(
     ws: WebSocketUpgrade,
     Query(params): Query<WsParams>,
     State(state): State<Arc<AppState>>,
+    ClientIp(client_ip): ClientIp,
 ) -> impl IntoResponse {
     let claims = match decode_jwt(&params.token, &state.config.jwt_secret) {
         Ok(c) => c,
@@ -82,7 +133,21 @@ pub async fn manual_control_ws(
         }
     };
 
-    ws.on_upgrade(move |socket| handle_manual_socket(socket, state, claims))
+    if crate::auth::sessions::is_revoked(&state, &claims.sid).await {
+        tracing::warn!(
+            user_id    = %claims.sub,
+            session_id = %claims.sid,
+            "WebSocket manual control - revoked session (401)"
+        );
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let robot = match resolve_robot(&state, params.robot_id.as_deref()).await {
+        Ok(robot) => robot,
+        Err(response) => return response.into_response(),
+    };
+
+    ws.on_upgrade(move |socket| handle_manual_socket(socket, state, robot, claims, client_ip))
 }
 
 pub async fn robot_events_ws(
@@ -98,7 +163,19 @@ pub async fn robot_events_ws(
         }
     };
 
-    if !roles::can_view(&claims.role) {
+    if crate::auth::sessions::is_revoked(&state, &claims.sid).await {
+        tracing::warn!(
+            user_id    = %claims.sub,
+            session_id = %claims.sid,
+            "WebSocket robot events - revoked session (401)"
+        );
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    if !permissions::for_role(&state, &claims.role)
+        .await
+        .contains(permissions::ROBOT_VIEW)
+    {
         tracing::warn!(
             user_id = %claims.sub,
             role    = %claims.role,
@@ -107,28 +184,78 @@ pub async fn robot_events_ws(
         return StatusCode::FORBIDDEN.into_response();
     }
 
-    ws.on_upgrade(move |socket| handle_events_socket(socket, state))
+    // Without robot_id the socket follows the whole fleet
+    let robot_filter = match params.robot_id {
+        Some(id) => match resolve_robot(&state, Some(&id)).await {
+            Ok(robot) => Some(robot.id),
+            Err(response) => return response.into_response(),
+        },
+        None => None,
+    };
+
+    ws.on_upgrade(move |socket| handle_events_socket(socket, state, robot_filter, claims.sid))
+}
+
+/// Whether a message from `session_revoked_sender` ends the socket of session `sid`
+async fn ends_session(state: &AppState, sid: &str, received: Result<Uuid, RecvError>) -> bool {
+    match received {
+        Ok(revoked) => revoked.to_string() == sid,
+        // The missed messages may have included this session.
+        Err(RecvError::Lagged(_)) => crate::auth::sessions::is_revoked(state, sid).await,
+        Err(RecvError::Closed) => true,
+    }
 }
 
-async fn handle_events_socket(mut socket: WebSocket, state: Arc<AppState>) {
+async fn close_revoked(socket: &mut WebSocket) {
+    let _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code: SESSION_REVOKED_CLOSE_CODE,
+            reason: "Session revoked".into(),
+        })))
+        .await;
+}
+
+async fn handle_events_socket(
+    mut socket: WebSocket,
+    state: Arc<AppState>,
+    robot_filter: Option<String>,
+    sid: String,
+) {
     let mut status_rx = state.robot_state.status_sender.subscribe();
     let mut notification_rx = state.robot_state.notification_sender.subscribe();
-
-    let initial_status = crate::robot::build_status_update(&state).await;
-    let initial_status_event = WsStatusUpdateEvent {
-        event: "status_update",
-        data: initial_status,
+    let mut revoked_rx = state.robot_state.session_revoked_sender.subscribe();
+    let wanted = |robot_id: Option<&str>| match &robot_filter {
+        Some(filter) => robot_id == Some(filter.as_str()),
+        None => true,
     };
-    if let Ok(msg) = serde_json::to_string(&initial_status_event) {
-        if socket.send(Message::Text(msg.into())).await.is_err() {
-            return;
+
+    for robot in state.robot_state.all_robots().await {
+        if !wanted(Some(&robot.id)) {
+            continue;
+        }
+        let initial_status_event = WsStatusUpdateEvent {
+            event: "status_update",
+            data: crate::robot::build_status_update(&state, &robot).await,
+        };
+        if let Ok(msg) = serde_json::to_string(&initial_status_event) {
+            if socket.send(Message::Text(msg.into())).await.is_err() {
+                return;
+            }
         }
     }
 
     loop {
         tokio::select! {
+            revoked = revoked_rx.recv() => {
+                if ends_session(&state, &sid, revoked).await {
+                    tracing::info!(session_id = %sid, "Closing robot events socket of revoked session");
+                    close_revoked(&mut socket).await;
+                    break;
+                }
+            }
             notification = notification_rx.recv() => {
                 match notification {
+                    Ok(notification) if !wanted(notification.robot_id.as_deref()) => continue,
                     Ok(notification) => {
                         let envelope = WsNotificationEvent {
                             event: "robot_notification",
@@ -147,6 +274,7 @@ async fn handle_events_socket(mut socket: WebSocket, state: Arc<AppState>) {
             }
             status_update = status_rx.recv() => {
                 match status_update {
+                    Ok(status_update) if !wanted(Some(&status_update.robot_id)) => continue,
                     Ok(status_update) => {
                         let envelope = WsStatusUpdateEvent {
                             event: "status_update",
@@ -167,11 +295,36 @@ async fn handle_events_socket(mut socket: WebSocket, state: Arc<AppState>) {
     }
 }
 
-async fn handle_manual_socket(mut socket: WebSocket, state: Arc<AppState>, claims: Claims) {
-    let role = claims.role.as_str();
-    let is_admin = roles::is_admin(role);
-    let is_operator = roles::is_operator(role);
-    while let Some(Ok(msg)) = socket.next().await {
+async fn handle_manual_socket(
+    mut socket: WebSocket,
+    state: Arc<AppState>,
+    robot: RobotHandle,
+    claims: Claims,
+    client_ip: String,
+) {
+    // Reloaded for every command so role edits apply to open sockets
+    let mut granted = permissions::for_role(&state, &claims.role).await;
+    let mut revoked_rx = state.robot_state.session_revoked_sender.subscribe();
+    loop {
+        let msg = tokio::select! {
+            msg = socket.next() => match msg {
+                Some(Ok(msg)) => msg,
+                _ => break,
+            },
+            revoked = revoked_rx.recv() => {
+                if ends_session(&state, &claims.sid, revoked).await {
+                    tracing::info!(
+                        user_id    = %claims.sub,
+                        session_id = %claims.sid,
+                        "Closing manual control socket of revoked session"
+                    );
+                    close_revoked(&mut socket).await;
+                    break;
+                }
+                continue;
+            }
+        };
+
         match msg {
             Message::Text(text) => {
                 let cmd: RobotCommand = match serde_json::from_str(&text) {
@@ -179,126 +332,215 @@ async fn handle_manual_socket(mut socket: WebSocket, state: Arc<AppState>, claim
                     Err(_) => continue,
                 };
 
-                // 1. Role Permission Check - Basic Level
-                if !roles::can_operate(role) {
-                    // Viewers cannot send commands.
+                // 1. Permission check for the command itself
+                granted = permissions::for_role(&state, &claims.role).await;
+                if !granted.contains(permissions::for_command(&cmd)) {
                     continue;
                 }
 
-                // 1a. Admin-only commands
-                if !is_admin
-                    && matches!(
-                        cmd,
-                        RobotCommand::Led { .. }
-                            | RobotCommand::LedAuto { .. }
-                            | RobotCommand::AudioBeep { .. }
-                            | RobotCommand::AudioVolume { .. }
-                            | RobotCommand::AudioStreamStart { .. }
-                            | RobotCommand::AudioStreamStop
-                    )
-                {
-                    continue;
-                }
+                // 2. Override (preemption) or lock holder
+                let mut debug_changed = false;
+                if granted.contains(permissions::ROBOT_OVERRIDE) {
+                    // Override can do anything, but only along real routes.
+                    // NAVIGATE preempts the queue, so its start is checked
+                    // against the robot's position rather than the queue tail.
+                    if let RobotCommand::Navigate { start, destination } = &cmd {
+                        let expected_start = if state.config.enforce_route_chaining {
+                            route_validation::robot_position(&state.robot_state, &robot).await
+                        } else {
+                            None
+                        };
+                        if let Err(e) = route_validation::validate_route(
+                            &state.robot_state,
+                            start,
+                            destination,
+                            expected_start.as_deref(),
+                        )
+                        .await
+                        {
+                            tracing::warn!(
+                                user_id     = %claims.sub,
+                                robot_id    = %robot.id,
+                                start       = %start,
+                                destination = %destination,
+                                reason      = e.code(),
+                                "Admin NAVIGATE rejected"
+                            );
+                            continue;
+                        }
+                    }
 
-                // 2. Admin Preemption & Logic
-                if is_admin {
-                    // Admin can do anything
                     // Check if this is a navigation command that needs preemption
-                    let mut debug_changed = false;
-                    if let RobotCommand::Navigate { .. } = &cmd {
-                        let mut lock = state.robot_state.manual_lock.write().await;
+                    if let RobotCommand::Navigate { start, destination } = &cmd {
+                        let mut lock = robot.manual_lock.write().await;
                         let should_revoke = if let Some(l) = &*lock {
                             l.holder_id.to_string() != claims.sub
                         } else {
                             false
                         };
 
-                        if should_revoke {
-                            let name = lock
-                                .as_ref()
-                                .map(|l| l.holder_name.clone())
-                                .unwrap_or_default();
-                            *lock = None; // Forcibly revoke
-                            debug_changed = true;
-                            tracing::info!("Admin revoked lock from operator {}", name);
-                        }
+                        let revoked = if should_revoke {
+                            lock.take() // Forcibly revoke
+                        } else {
+                            None
+                        };
                         drop(lock);
-
-                        // Handle Queue Preemption
-                        // Cancel active route, move to front of queue
-                        let mut active_route_guard = state.robot_state.active_route.write().await;
-                        if let Some(active) = active_route_guard.take() {
-                            // There was an active route. Cancel it on robot.
-                            let _ = state.robot_state.command_sender.send(RobotCommand::Cancel);
-
-                            // Move to front of queue
-                            // "Resumed route starts from beginning" -> So we just put it back in queue with same Start/End
-                            let mut queue = state.robot_state.queue.write().await;
-                            queue.push_front(active);
-                            debug_changed = true;
+                        if let Some(previous) = revoked {
+                            tracing::info!(
+                                "Admin revoked lock from operator {}",
+                                previous.holder_name
+                            );
+                            AuditEvent::new(actions::LOCK_REVOKE, &claims)
+                                .target("user", previous.holder_id)
+                                .robot(&robot.id)
+                                .before(&previous)
+                                .client_ip(&client_ip)
+                                .spawn(&state.db);
                         }
 
                         // Track this WS navigation as the active route (so it appears in queue view)
-                        if let RobotCommand::Navigate { start, destination } = &cmd {
-                            *active_route_guard = Some(QueuedRoute {
-                                id: Uuid::new_v4(),
-                                start: start.clone(),
-                                destination: destination.clone(),
-                                added_at: Utc::now(),
-                                added_by: claims.name.clone(),
-                            });
-                            debug_changed = true;
+                        let route = QueuedRoute {
+                            id: Uuid::new_v4(),
+                            start: start.clone(),
+                            destination: destination.clone(),
+                            added_at: Utc::now(),
+                            added_by: claims.name.clone(),
+                            robot_id: Some(robot.id.clone()),
+                        };
+
+                        // Handle Queue Preemption
+                        // Cancel active route, move to front of queue. The
+                        // swap happens under the lock so process_queue cannot
+                        // dispatch in between; persisting waits until after.
+                        let preempted = {
+                            let mut active_route_guard = robot.active_route.write().await;
+                            let preempted = active_route_guard.take();
+                            if let Some(active) = &preempted {
+                                // There was an active route. Cancel it on robot.
+                                let _ = robot.command_sender.send(RobotCommand::Cancel);
+
+                                // Move to front of queue
+                                // "Resumed route starts from beginning" -> So we just put it back in queue with same Start/End
+                                let mut queue = state.robot_state.queue.write().await;
+                                queue.push_front(active.clone());
+                            }
+                            *active_route_guard = Some(route.clone());
+                            preempted
+                        };
+
+                        if let Some(active) = preempted {
+                            if let Err(e) = route_store::requeue_front(&state.db, &active).await {
+                                route_store::log_persist_error(active.id, "requeue", &e);
+                            }
+                            AuditEvent::new(actions::ROUTE_PREEMPT, &claims)
+                                .target("route", active.id)
+                                .robot(&robot.id)
+                                .before(&active)
+                                .client_ip(&client_ip)
+                                .spawn(&state.db);
                         }
-                    }
 
-                    if matches!(cmd, RobotCommand::AudioStreamStart { .. }) {
-                        let mut streaming = state.robot_state.audio_streaming.write().await;
-                        *streaming = true;
-                    } else if matches!(cmd, RobotCommand::AudioStreamStop) {
-                        let mut streaming = state.robot_state.audio_streaming.write().await;
-                        *streaming = false;
+                        if let Err(e) =
+                            route_store::insert_dispatched(&state.db, &route, &robot.id).await
+                        {
+                            route_store::log_persist_error(route.id, "insert_dispatched", &e);
+                        }
+                        AuditEvent::new(actions::ROUTE_NAVIGATE, &claims)
+                            .target("route", route.id)
+                            .robot(&robot.id)
+                            .after(&route)
+                            .client_ip(&client_ip)
+                            .spawn(&state.db);
+                        debug_changed = true;
                     }
 
-                    // Execute Admin Command
-                    let _ = state.robot_state.command_sender.send(cmd);
-                    if debug_changed {
-                        crate::robot::broadcast_status_update(&state).await;
-                    }
-                } else if is_operator {
-                    // Operators cannot send navigation/cancel commands via WS
-                    if matches!(cmd, RobotCommand::Navigate { .. } | RobotCommand::Cancel) {
-                        continue;
+                    // An override CANCEL ends the active route for good (no re-queue)
+                    if matches!(cmd, RobotCommand::Cancel) {
+                        let cancelled = robot.active_route.write().await.take();
+                        if let Some(active) = cancelled {
+                            if let Err(e) =
+                                route_store::cancel(&state.db, active.id, &claims.name).await
+                            {
+                                route_store::log_persist_error(active.id, "cancel", &e);
+                            }
+                            tracing::info!(
+                                route_id     = %active.id,
+                                robot_id     = %robot.id,
+                                cancelled_by = %claims.name,
+                                "Admin cancelled active route"
+                            );
+                            AuditEvent::new(actions::ROUTE_CANCEL, &claims)
+                                .target("route", active.id)
+                                .robot(&robot.id)
+                                .before(&active)
+                                .client_ip(&client_ip)
+                                .spawn(&state.db);
+                            debug_changed = true;
+                        }
                     }
-
-                    // Operator must hold a non-expired lock to send commands
-                    let lock = state.robot_state.manual_lock.read().await;
+                } else {
+                    // Without override, commands need a non-expired lock held by this user
+                    let lock = robot.manual_lock.read().await;
                     let is_valid_holder = if let Some(l) = &*lock {
                         l.holder_id.to_string() == claims.sub && l.expires_at > chrono::Utc::now()
                     } else {
                         false
                     };
+                    if !is_valid_holder {
+                        continue;
+                    }
+                }
+
+                if matches!(cmd, RobotCommand::AudioStreamStart { .. }) {
+                    let mut streaming = robot.audio_streaming.write().await;
+                    *streaming = true;
+                } else if matches!(cmd, RobotCommand::AudioStreamStop) {
+                    let mut streaming = robot.audio_streaming.write().await;
+                    *streaming = false;
+                }
 
-                    if is_valid_holder {
-                        let _ = state.robot_state.command_sender.send(cmd);
+                let audited = match cmd {
+                    RobotCommand::Led { .. } | RobotCommand::LedAuto { .. } => {
+                        Some(actions::ROBOT_LED)
                     }
+                    RobotCommand::AudioBeep { .. }
+                    | RobotCommand::AudioVolume { .. }
+                    | RobotCommand::AudioStreamStart { .. }
+                    | RobotCommand::AudioStreamStop => Some(actions::ROBOT_AUDIO),
+                    _ => None,
+                };
+                let event = audited.map(|action| {
+                    AuditEvent::new(action, &claims)
+                        .target("robot", &robot.id)
+                        .robot(&robot.id)
+                        .after(&cmd)
+                        .client_ip(&client_ip)
+                });
+
+                let _ = robot.command_sender.send(cmd);
+                if let Some(event) = event {
+                    event.spawn(&state.db);
+                }
+                if debug_changed {
+                    crate::robot::broadcast_robot_status(&state, &robot).await;
                 }
             }
             Message::Binary(data) => {
-                if !is_admin {
+                if !granted.contains(permissions::ROBOT_AUDIO) {
                     continue;
                 }
-                let streaming = state.robot_state.audio_streaming.read().await;
+                let streaming = robot.audio_streaming.read().await;
                 if !*streaming {
                     continue;
                 }
-                let _ = state.robot_state.audio_sender.send(data.to_vec());
+                let _ = robot.audio_sender.send(data.to_vec());
             }
             _ => {}
         }
     }
 
-    if is_admin {
-        let mut streaming = state.robot_state.audio_streaming.write().await;
+    if granted.contains(permissions::ROBOT_AUDIO) {
+        let mut streaming = robot.audio_streaming.write().await;
         *streaming = false;
     }
 }
@@ -319,39 +561,72 @@ pub async fn get_nodes(State(state): State<Arc<AppState>>) -> impl IntoResponse
     (
         StatusCode::OK,
         Json(NodesResponse {
-            nodes: state.static_nodes.clone(),
+            nodes: state.robot_state.nodes.read().await.clone(),
         }),
     )
         .into_response()
 }
 
-pub async fn get_robot_debug(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let debug_snapshot = crate::robot::build_debug_snapshot(&state).await;
+pub async fn get_robot_debug(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<RobotDebug>,
+    Query(query): Query<RobotIdQuery>,
+) -> impl IntoResponse {
+    let robot = match resolve_robot(&state, query.robot_id.as_deref()).await {
+        Ok(robot) => robot,
+        Err(response) => return response.into_response(),
+    };
+    let debug_snapshot = crate::robot::build_debug_snapshot(&state, &robot).await;
     (StatusCode::OK, Json(debug_snapshot)).into_response()
 }
 
+pub async fn list_robots(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let queue = state.robot_state.queue.read().await.clone();
+    let mut robots = Vec::new();
+
+    for robot in state.robot_state.all_robots().await {
+        let status = crate::robot::build_status_update(&state, &robot).await;
+        robots.push(RobotSummary {
+            connected: status.robot_connected,
+            drive_mode: status.drive_mode,
+            position: status.position,
+            battery_level: status.battery_level,
+            last_state_update: *robot.last_state_update.read().await,
+            active_route: robot.active_route.read().await.clone(),
+            queued_routes: queue
+                .iter()
+                .filter(|r| r.robot_id.as_deref().is_none_or(|id| id == robot.id))
+                .count(),
+            manual_lock_holder_name: status.manual_lock_holder_name,
+            id: robot.id,
+        });
+    }
+
+    (StatusCode::OK, Json(robots)).into_response()
+}
+
 pub async fn select_route(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
+    RequirePermission { claims, .. }: RequirePermission<RobotNavigate>,
     Json(payload): Json<RouteSelectionRequest>,
 ) -> impl IntoResponse {
-    if !roles::can_operate(&claims.role) {
-        tracing::warn!(
-            user_id = %claims.sub,
-            name    = %claims.name,
-            role    = %claims.role,
-            "Permission denied - select_route requires operator or above (403)"
-        );
-        return StatusCode::FORBIDDEN.into_response();
-    }
+    let pinned =
+        match route_validation::pinned_robot(&state.robot_state, payload.robot_id.as_deref()).await
+        {
+            Ok(pinned) => pinned,
+            Err(e) => return e.into_response(),
+        };
 
     // Should route selection be locked? Maybe not, but concurrent nav commands are bad.
     // For now allow it broadly or require lock? Let's assume shared control allowed for nav unless locked?
     // User requested "correctly". If someone has manual lock, nav should be blocked?
-
-    let lock = state.robot_state.manual_lock.read().await;
-    if let Some(l) = &*lock {
-        if l.expires_at > chrono::Utc::now() {
+    // An unpinned route in a multi-robot fleet can go to any unlocked robot.
+    let target = match &pinned {
+        Some(robot) => Some(robot.clone()),
+        None => state.robot_state.resolve_robot(None).await,
+    };
+    if let Some(robot) = target {
+        if robot.is_locked().await {
             return Json(serde_json::json!({
                 "status": "error",
                 "message": "Robot is manually locked"
@@ -360,6 +635,29 @@ pub async fn select_route(
         }
     }
 
+    let expected_start = if state.config.enforce_route_chaining {
+        route_validation::next_queued_start(&state.robot_state, pinned.as_ref()).await
+    } else {
+        None
+    };
+    if let Err(e) = route_validation::validate_route(
+        &state.robot_state,
+        &payload.start,
+        &payload.destination,
+        expected_start.as_deref(),
+    )
+    .await
+    {
+        tracing::warn!(
+            user_id     = %claims.sub,
+            start       = %payload.start,
+            destination = %payload.destination,
+            reason      = e.code(),
+            "Route selection rejected (422)"
+        );
+        return e.into_response();
+    }
+
     // Add to Queue instead of direct send
     // This allows the queue view to see it, and process_queue to handle dispatch
     let route = QueuedRoute {
@@ -368,8 +666,21 @@ pub async fn select_route(
         destination: payload.destination,
         added_at: Utc::now(),
         added_by: claims.name,
+        robot_id: pinned.map(|robot| robot.id),
     };
 
+    if let Err(e) = route_store::enqueue_back(&state.db, &route).await {
+        tracing::error!(route_id = %route.id, error = %e, "DB error persisting selected route");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Failed to queue route"
+            })),
+        )
+            .into_response();
+    }
+
     {
         let mut queue = state.robot_state.queue.write().await;
         queue.push_back(route);
@@ -388,21 +699,21 @@ pub async fn select_route(
 
 pub async fn acquire_lock(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
+    RequirePermission {
+        claims,
+        permissions: granted,
+        ..
+    }: RequirePermission<RobotDrive>,
+    ClientIp(client_ip): ClientIp,
+    Query(query): Query<RobotIdQuery>,
 ) -> impl IntoResponse {
-    if !roles::can_operate(&claims.role) {
-        tracing::warn!(
-            user_id = %claims.sub,
-            name    = %claims.name,
-            role    = %claims.role,
-            "Permission denied - acquire_lock requires operator or above (403)"
-        );
-        return StatusCode::FORBIDDEN.into_response();
-    }
-
-    let is_admin = roles::is_admin(&claims.role);
+    let robot = match resolve_robot(&state, query.robot_id.as_deref()).await {
+        Ok(robot) => robot,
+        Err(response) => return response.into_response(),
+    };
+    let can_override = granted.contains(permissions::ROBOT_OVERRIDE);
 
-    if !state.robot_state.is_robot_connected().await {
+    if !robot.is_robot_connected().await {
         return Json(serde_json::json!({
             "status": "error",
             "message": "Cannot acquire lock because robot is not connected"
@@ -411,7 +722,7 @@ pub async fn acquire_lock(
     }
 
     // Check if queue is active
-    if !is_admin && state.robot_state.active_route.read().await.is_some() {
+    if !can_override && robot.active_route.read().await.is_some() {
         return Json(serde_json::json!({
             "status": "error",
             "message": "Cannot acquire lock while automated route is active"
@@ -419,11 +730,12 @@ pub async fn acquire_lock(
         .into_response();
     }
 
-    let mut lock = state.robot_state.manual_lock.write().await;
+    let mut lock = robot.manual_lock.write().await;
+    let mut revoked = None;
 
     if let Some(l) = &*lock {
         if l.expires_at > chrono::Utc::now() && l.holder_id.to_string() != claims.sub {
-            if !is_admin {
+            if !can_override {
                 return Json(serde_json::json!({
                     "status": "error",
                     "message": format!("Lock held by {}", l.holder_name)
@@ -432,26 +744,29 @@ pub async fn acquire_lock(
             }
 
             tracing::info!("Admin {} revoked lock from {}", claims.name, l.holder_name);
+            revoked = Some(l.clone());
         }
     }
 
     if let Ok(user_id) = Uuid::parse_str(&claims.sub) {
-        *lock = Some(super::state::LockInfo {
+        let acquired = super::state::LockInfo {
             holder_id: user_id,
             holder_name: claims.name.clone(),
             expires_at: chrono::Utc::now() + chrono::Duration::seconds(30),
-        });
+        };
+        *lock = Some(acquired.clone());
 
-        let message = if is_admin && state.robot_state.active_route.read().await.is_some() {
+        let message = if can_override && robot.active_route.read().await.is_some() {
             "Admin lock acquired while automated route is active"
         } else {
             "Lock acquired"
         };
 
         tracing::info!(
-            user_id = %user_id,
-            name    = %claims.name,
-            role    = %claims.role,
+            user_id  = %user_id,
+            name     = %claims.name,
+            role     = %claims.role,
+            robot_id = %robot.id,
             "Manual drive lock acquired"
         );
 
@@ -462,9 +777,26 @@ pub async fn acquire_lock(
         .into_response();
 
         drop(lock);
+        if let Some(previous) = revoked {
+            AuditEvent::new(actions::LOCK_REVOKE, &claims)
+                .target("user", previous.holder_id)
+                .robot(&robot.id)
+                .before(&previous)
+                .after(&acquired)
+                .client_ip(&client_ip)
+                .record(&state.db)
+                .await;
+        }
+        AuditEvent::new(actions::LOCK_ACQUIRE, &claims)
+            .target("robot", &robot.id)
+            .robot(&robot.id)
+            .after(&acquired)
+            .client_ip(&client_ip)
+            .record(&state.db)
+            .await;
         let state_for_broadcast = state.clone();
         tokio::spawn(async move {
-            crate::robot::broadcast_status_update(&state_for_broadcast).await;
+            crate::robot::broadcast_robot_status(&state_for_broadcast, &robot).await;
         });
 
         response
@@ -479,29 +811,26 @@ pub async fn acquire_lock(
 
 pub async fn release_lock(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
+    RequirePermission { claims, .. }: RequirePermission<RobotDrive>,
+    ClientIp(client_ip): ClientIp,
+    Query(query): Query<RobotIdQuery>,
 ) -> impl IntoResponse {
-    if !roles::can_operate(&claims.role) {
-        tracing::warn!(
-            user_id = %claims.sub,
-            name    = %claims.name,
-            role    = %claims.role,
-            "Permission denied - release_lock requires operator or above (403)"
-        );
-        return StatusCode::FORBIDDEN.into_response();
-    }
-
-    let mut lock = state.robot_state.manual_lock.write().await;
+    let robot = match resolve_robot(&state, query.robot_id.as_deref()).await {
+        Ok(robot) => robot,
+        Err(response) => return response.into_response(),
+    };
+    let mut lock = robot.manual_lock.write().await;
 
     // Only holder can release.
     if let Some(l) = &*lock {
         if l.holder_id.to_string() == claims.sub {
             tracing::info!(
-                user_id = %claims.sub,
-                name    = %claims.name,
+                user_id  = %claims.sub,
+                name     = %claims.name,
+                robot_id = %robot.id,
                 "Manual drive lock released"
             );
-            *lock = None;
+            let released = lock.take();
             let response = Json(serde_json::json!({
                 "status": "success",
                 "message": "Lock released"
@@ -509,9 +838,16 @@ pub async fn release_lock(
             .into_response();
 
             drop(lock);
+            AuditEvent::new(actions::LOCK_RELEASE, &claims)
+                .target("robot", &robot.id)
+                .robot(&robot.id)
+                .before(&released)
+                .client_ip(&client_ip)
+                .record(&state.db)
+                .await;
             let state_for_broadcast = state.clone();
             tokio::spawn(async move {
-                crate::robot::broadcast_status_update(&state_for_broadcast).await;
+                crate::robot::broadcast_robot_status(&state_for_broadcast, &robot).await;
             });
 
             return response;
@@ -525,9 +861,16 @@ pub async fn release_lock(
     .into_response()
 }
 
-pub async fn check_robot_connection(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let robot_url = state.robot_state.robot_url.read().await;
-    let robot_connected = state.robot_state.is_robot_connected().await;
+pub async fn check_robot_connection(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RobotIdQuery>,
+) -> impl IntoResponse {
+    let robot = match resolve_robot(&state, query.robot_id.as_deref()).await {
+        Ok(robot) => robot,
+        Err(response) => return response.into_response(),
+    };
+    let robot_url = robot.robot_url.read().await;
+    let robot_connected = robot.is_robot_connected().await;
 
     if let Some(url) = &*robot_url {
         if !robot_connected {
@@ -536,7 +879,8 @@ pub async fn check_robot_connection(State(state): State<Arc<AppState>>) -> impl
                 "connected": false,
                 "message": "Robot registered but no recent state updates (stale)",
                 "url": url
-            }));
+            }))
+            .into_response();
         }
 
         match state.http_client.get(format!("{url}/health")).send().await {
@@ -555,6 +899,7 @@ pub async fn check_robot_connection(State(state): State<Arc<AppState>>) -> impl
                     "robot_status": status.as_u16(),
                     "url": url
                 }))
+                .into_response()
             }
             Err(e) => {
                 tracing::error!(
@@ -568,6 +913,7 @@ pub async fn check_robot_connection(State(state): State<Arc<AppState>>) -> impl
                     "message": format!("Failed to reach robot: {}", e),
                     "url": url
                 }))
+                .into_response()
             }
         }
     } else {
@@ -576,5 +922,6 @@ pub async fn check_robot_connection(State(state): State<Arc<AppState>>) -> impl
             "connected": false,
             "message": "No robot URL registered"
         }))
+        .into_response()
     }
 }
@@ -0,0 +1,184 @@
+use crate::robot::models::NodeEdge;
+use sqlx::PgPool;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Cost used by the optimizer when no path exists between two nodes. Large
+/// enough to push unreachable transitions to the end, small enough that sums
+/// stay finite.
+pub const UNREACHABLE_COST: f64 = 1_000_000.0;
+
+/// Directed weighted graph of navigation nodes built from `node_edges`.
+#[derive(Debug, Clone, Default)]
+pub struct NodeGraph {
+    adjacency: HashMap<String, Vec<(String, f64)>>,
+}
+
+#[derive(PartialEq)]
+struct Visit {
+    cost: f64,
+    node: String,
+}
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    // Reversed so BinaryHeap pops the cheapest node first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.node.cmp(&other.node))
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl NodeGraph {
+    pub fn from_edges(edges: &[NodeEdge]) -> Self {
+        let mut adjacency: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+        for edge in edges {
+            adjacency
+                .entry(edge.from_node.clone())
+                .or_default()
+                .push((edge.to_node.clone(), edge.cost_seconds));
+        }
+        Self { adjacency }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adjacency.is_empty()
+    }
+
+    /// Dijkstra from `source`; returns the cost and predecessor of every reachable node.
+    fn dijkstra(&self, source: &str) -> HashMap<String, (f64, Option<String>)> {
+        let mut best: HashMap<String, (f64, Option<String>)> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+
+        best.insert(source.to_string(), (0.0, None));
+        heap.push(Visit {
+            cost: 0.0,
+            node: source.to_string(),
+        });
+
+        while let Some(Visit { cost, node }) = heap.pop() {
+            if best.get(&node).is_some_and(|(c, _)| cost > *c) {
+                continue;
+            }
+
+            for (next, weight) in self.adjacency.get(&node).into_iter().flatten() {
+                let next_cost = cost + weight;
+                let improves = best.get(next).is_none_or(|(c, _)| next_cost < *c);
+                if improves {
+                    best.insert(next.clone(), (next_cost, Some(node.clone())));
+                    heap.push(Visit {
+                        cost: next_cost,
+                        node: next.clone(),
+                    });
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Cheapest path from `from` to `to` as `(cost, [from, ..., to])`.
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<(f64, Vec<String>)> {
+        let best = self.dijkstra(from);
+        let (cost, _) = best.get(to)?;
+
+        let mut path = vec![to.to_string()];
+        let mut current = to.to_string();
+        while let Some((_, Some(prev))) = best.get(&current) {
+            path.push(prev.clone());
+            current = prev.clone();
+        }
+        path.reverse();
+
+        Some((*cost, path))
+    }
+
+    /// Shortest-path costs from each of `sources` to every reachable node.
+    pub fn cost_table<'a, I>(&self, sources: I) -> HashMap<(String, String), f64>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut table = HashMap::new();
+        for source in sources {
+            if table.contains_key(&(source.to_string(), source.to_string())) {
+                continue;
+            }
+            for (target, (cost, _)) in self.dijkstra(source) {
+                table.insert((source.to_string(), target), cost);
+            }
+        }
+        table
+    }
+}
+
+pub async fn load_edges(db: &PgPool) -> Result<Vec<NodeEdge>, sqlx::Error> {
+    sqlx::query_as::<_, NodeEdge>(
+        r#"
+        SELECT from_node, to_node, cost_seconds, source, updated_at
+        FROM node_edges
+        ORDER BY from_node, to_node
+        "#,
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn load_graph(db: &PgPool) -> Result<NodeGraph, sqlx::Error> {
+    Ok(NodeGraph::from_edges(&load_edges(db).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(from: &str, to: &str, cost: f64) -> NodeEdge {
+        NodeEdge {
+            from_node: from.to_string(),
+            to_node: to.to_string(),
+            cost_seconds: cost,
+            source: "manual".to_string(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_shortest_path_prefers_cheaper_detour() {
+        let graph = NodeGraph::from_edges(&[
+            edge("a", "c", 10.0),
+            edge("a", "b", 2.0),
+            edge("b", "c", 3.0),
+        ]);
+
+        let (cost, path) = graph.shortest_path("a", "c").unwrap();
+        assert_eq!(cost, 5.0);
+        assert_eq!(path, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_edges_are_directed() {
+        let graph = NodeGraph::from_edges(&[edge("a", "b", 1.0)]);
+
+        assert!(graph.shortest_path("a", "b").is_some());
+        assert!(graph.shortest_path("b", "a").is_none());
+    }
+
+    #[test]
+    fn test_cost_table_contains_reachable_pairs() {
+        let graph = NodeGraph::from_edges(&[edge("a", "b", 1.0), edge("b", "c", 4.0)]);
+        let table = graph.cost_table(["a"]);
+
+        assert_eq!(table.get(&("a".to_string(), "a".to_string())), Some(&0.0));
+        assert_eq!(table.get(&("a".to_string(), "c".to_string())), Some(&5.0));
+        assert!(!table.contains_key(&("c".to_string(), "a".to_string())));
+    }
+}
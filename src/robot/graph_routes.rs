@@ -0,0 +1,208 @@
+use crate::auth::permissions::{NodesManage, RequirePermission};
+use crate::robot::graph;
+use crate::robot::models::{NodeEdge, PathCostQuery, PathCostResponse, UpsertEdgeRequest};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+/// Completed trips required per start/destination pair before its average
+/// duration is trusted as a learned edge weight.
+const LEARN_MIN_TRIPS: i64 = 3;
+
+pub async fn list_edges(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<NodesManage>,
+) -> Result<Json<Vec<NodeEdge>>, (StatusCode, Json<serde_json::Value>)> {
+    let edges = graph::load_edges(&state.db).await.map_err(|e| {
+        tracing::error!(error = %e, "DB error listing node edges");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to fetch node edges" })),
+        )
+    })?;
+
+    Ok(Json(edges))
+}
+
+pub async fn upsert_edge(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<NodesManage>,
+    Json(payload): Json<UpsertEdgeRequest>,
+) -> Result<Json<Vec<NodeEdge>>, (StatusCode, Json<serde_json::Value>)> {
+    if payload.from == payload.to {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Edge must connect two different nodes" })),
+        ));
+    }
+
+    if !payload.cost_seconds.is_finite() || payload.cost_seconds < 0.0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "cost_seconds must be a non-negative number" })),
+        ));
+    }
+
+    for node in [&payload.from, &payload.to] {
+        if !state.robot_state.is_known_node(node).await {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": format!("Unknown node: {node}") })),
+            ));
+        }
+    }
+
+    let mut directions = vec![(payload.from.as_str(), payload.to.as_str())];
+    if payload.bidirectional {
+        directions.push((payload.to.as_str(), payload.from.as_str()));
+    }
+
+    let mut edges = Vec::with_capacity(directions.len());
+    for (from, to) in directions {
+        let edge = sqlx::query_as::<_, NodeEdge>(
+            r#"
+            INSERT INTO node_edges (from_node, to_node, cost_seconds, source)
+            VALUES ($1, $2, $3, 'manual')
+            ON CONFLICT (from_node, to_node) DO UPDATE
+            SET cost_seconds = EXCLUDED.cost_seconds,
+                source = 'manual'
+            RETURNING from_node, to_node, cost_seconds, source, updated_at
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(payload.cost_seconds)
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, from = %from, to = %to, "DB error upserting node edge");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to save node edge" })),
+            )
+        })?;
+        edges.push(edge);
+    }
+
+    tracing::info!(
+        from          = %payload.from,
+        to            = %payload.to,
+        cost_seconds  = payload.cost_seconds,
+        bidirectional = payload.bidirectional,
+        "Node edge saved"
+    );
+
+    Ok(Json(edges))
+}
+
+pub async fn delete_edge(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<NodesManage>,
+    Path((from, to)): Path<(String, String)>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let result = sqlx::query("DELETE FROM node_edges WHERE from_node = $1 AND to_node = $2")
+        .bind(&from)
+        .bind(&to)
+        .execute(&state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, from = %from, to = %to, "DB error deleting node edge");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to delete node edge" })),
+            )
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Node edge not found" })),
+        ));
+    }
+
+    tracing::info!(from = %from, to = %to, "Node edge deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Replace edge weights with the average observed trip duration for every
+/// start/destination pair with at least `LEARN_MIN_TRIPS` completed routes.
+pub async fn learn_edge_weights(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<NodesManage>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO node_edges (from_node, to_node, cost_seconds, source)
+        SELECT
+            start,
+            destination,
+            AVG(EXTRACT(EPOCH FROM (finished_at - dispatched_at)))::float8,
+            'learned'
+        FROM route_queue
+        WHERE status = 'completed'
+          AND dispatched_at IS NOT NULL
+          AND finished_at >= dispatched_at
+          AND start <> destination
+        GROUP BY start, destination
+        HAVING COUNT(*) >= $1
+        ON CONFLICT (from_node, to_node) DO UPDATE
+        SET cost_seconds = EXCLUDED.cost_seconds,
+            source = 'learned'
+        "#,
+    )
+    .bind(LEARN_MIN_TRIPS)
+    .execute(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "DB error learning node edge weights");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to learn edge weights" })),
+        )
+    })?;
+
+    tracing::info!(
+        updated = result.rows_affected(),
+        "Node edge weights learned from route history"
+    );
+
+    Ok(Json(serde_json::json!({
+        "status": "success",
+        "updated": result.rows_affected()
+    })))
+}
+
+pub async fn get_path_cost(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<NodesManage>,
+    Query(query): Query<PathCostQuery>,
+) -> Result<Json<PathCostResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let node_graph = graph::load_graph(&state.db).await.map_err(|e| {
+        tracing::error!(error = %e, "DB error loading node graph");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to load node graph" })),
+        )
+    })?;
+
+    let (cost_seconds, path) = node_graph
+        .shortest_path(&query.from, &query.to)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "No path between nodes" })),
+            )
+        })?;
+
+    Ok(Json(PathCostResponse {
+        from: query.from,
+        to: query.to,
+        cost_seconds,
+        path,
+    }))
+}
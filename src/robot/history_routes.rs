@@ -0,0 +1,161 @@
+use crate::auth::permissions::{RequirePermission, RoutesHistory};
+use crate::robot::models::{
+    DailyDeliveries, RouteHistoryEntry, RouteHistoryQuery, RoutePairStats, RouteStatsQuery,
+    RouteStatsResponse,
+};
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+pub async fn get_route_history(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<RoutesHistory>,
+    Query(query): Query<RouteHistoryQuery>,
+) -> Result<Json<Vec<RouteHistoryEntry>>, (StatusCode, Json<serde_json::Value>)> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    // Routes still waiting in the queue are not history yet.
+    let history = sqlx::query_as::<_, RouteHistoryEntry>(
+        r#"
+        SELECT
+            id,
+            COALESCE(dispatched_robot_id, robot_id) AS robot_id,
+            start,
+            destination,
+            status,
+            added_by,
+            added_at,
+            dispatched_at,
+            finished_at,
+            cancelled_by,
+            EXTRACT(EPOCH FROM (finished_at - dispatched_at))::float8 AS duration_seconds
+        FROM route_queue
+        WHERE status <> 'queued'
+          AND ($1::timestamptz IS NULL OR added_at >= $1)
+          AND ($2::timestamptz IS NULL OR added_at < $2)
+          AND ($3::text IS NULL OR added_by = $3 OR cancelled_by = $3)
+          AND ($4::text IS NULL OR start = $4)
+          AND ($5::text IS NULL OR destination = $5)
+          AND ($6::text IS NULL OR status = $6)
+          AND ($7::text IS NULL OR COALESCE(dispatched_robot_id, robot_id) = $7)
+        ORDER BY added_at DESC
+        LIMIT $8 OFFSET $9
+        "#,
+    )
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.user.as_deref())
+    .bind(query.start.as_deref())
+    .bind(query.destination.as_deref())
+    .bind(query.status.map(|s| s.as_str()))
+    .bind(query.robot_id.as_deref())
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "DB error fetching route history");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to fetch route history" })),
+        )
+    })?;
+
+    Ok(Json(history))
+}
+
+pub async fn get_route_stats(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<RoutesHistory>,
+    Query(query): Query<RouteStatsQuery>,
+) -> Result<Json<RouteStatsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let db_error = |e: sqlx::Error| {
+        tracing::error!(error = %e, "DB error computing route stats");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to compute route stats" })),
+        )
+    };
+
+    // All aggregates are scoped by finished_at: a route counts towards the
+    // period in which its outcome became known.
+    let (completed, cancelled, failed) = sqlx::query_as::<_, (i64, i64, i64)>(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE status = 'completed'),
+            COUNT(*) FILTER (WHERE status = 'cancelled'),
+            COUNT(*) FILTER (WHERE status = 'failed')
+        FROM route_queue
+        WHERE finished_at IS NOT NULL
+          AND ($1::timestamptz IS NULL OR finished_at >= $1)
+          AND ($2::timestamptz IS NULL OR finished_at < $2)
+        "#,
+    )
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    let pairs = sqlx::query_as::<_, RoutePairStats>(
+        r#"
+        SELECT
+            start,
+            destination,
+            COUNT(*) AS trips,
+            AVG(EXTRACT(EPOCH FROM (finished_at - dispatched_at)))::float8 AS avg_duration_seconds
+        FROM route_queue
+        WHERE status = 'completed'
+          AND dispatched_at IS NOT NULL
+          AND ($1::timestamptz IS NULL OR finished_at >= $1)
+          AND ($2::timestamptz IS NULL OR finished_at < $2)
+        GROUP BY start, destination
+        ORDER BY trips DESC, start, destination
+        "#,
+    )
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    let deliveries_per_day = sqlx::query_as::<_, DailyDeliveries>(
+        r#"
+        SELECT
+            (finished_at AT TIME ZONE 'UTC')::date AS day,
+            COUNT(*) AS deliveries
+        FROM route_queue
+        WHERE status = 'completed'
+          AND ($1::timestamptz IS NULL OR finished_at >= $1)
+          AND ($2::timestamptz IS NULL OR finished_at < $2)
+        GROUP BY day
+        ORDER BY day
+        "#,
+    )
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    let finished = completed + cancelled + failed;
+    let cancellation_rate = if finished > 0 {
+        cancelled as f64 / finished as f64
+    } else {
+        0.0
+    };
+
+    Ok(Json(RouteStatsResponse {
+        completed,
+        cancelled,
+        failed,
+        cancellation_rate,
+        pairs,
+        deliveries_per_day,
+    }))
+}
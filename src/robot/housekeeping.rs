@@ -0,0 +1,201 @@
+use crate::diary::trash::{self, DIARY_PURGE_INTERVAL_SECS};
+use crate::robot::state::{RobotHandle, CLEANUP_INTERVAL_SECS, ROBOT_STALE_TIMEOUT_SECS};
+use crate::robot::telemetry_store::{self, TELEMETRY_COMPACTION_INTERVAL_SECS};
+use crate::AppState;
+use chrono::Utc;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Handle to the background housekeeping tasks spawned by [`spawn`].
+///
+/// Dropping the handle does not stop the tasks; call [`HousekeepingHandle::shutdown`]
+/// once the HTTP server has finished its graceful shutdown.
+pub struct HousekeepingHandle {
+    shutdown_tx: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl HousekeepingHandle {
+    /// Signal the housekeeping tasks to stop and wait for their current cycles to finish.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(true);
+        for task in self.tasks {
+            if let Err(e) = task.await {
+                tracing::error!(error = %e, "Housekeeping task terminated abnormally");
+            }
+        }
+    }
+}
+
+/// Spawn the supervised housekeeping tasks: robot cleanup every
+/// `CLEANUP_INTERVAL_SECS`, telemetry compaction every
+/// `TELEMETRY_COMPACTION_INTERVAL_SECS` and the purge of expired deleted diary
+/// entries every `DIARY_PURGE_INTERVAL_SECS`.
+///
+/// Each job has its own task, so a slow compaction or purge never delays the
+/// stale-robot checks.
+pub fn spawn(state: Arc<AppState>) -> HousekeepingHandle {
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+    let tasks = vec![
+        supervise(
+            "Robot housekeeping",
+            CLEANUP_INTERVAL_SECS,
+            state.clone(),
+            shutdown_rx.clone(),
+            |state| async move {
+                run_housekeeping_cycle(&state).await;
+            },
+        ),
+        supervise(
+            "Telemetry compaction",
+            TELEMETRY_COMPACTION_INTERVAL_SECS,
+            state.clone(),
+            shutdown_rx.clone(),
+            |state| async move { compact_telemetry(&state).await },
+        ),
+        supervise(
+            "Diary purge",
+            DIARY_PURGE_INTERVAL_SECS,
+            state,
+            shutdown_rx,
+            |state| async move { purge_deleted_diary_entries(&state).await },
+        ),
+    ];
+
+    HousekeepingHandle { shutdown_tx, tasks }
+}
+
+/// Spawn a task that runs `job` every `interval_secs` until shutdown.
+///
+/// Each cycle runs in its own task so a panic inside a single cycle is logged
+/// and the loop keeps going on the next tick.
+fn supervise<F, Fut>(
+    name: &'static str,
+    interval_secs: u64,
+    state: Arc<AppState>,
+    mut shutdown_rx: watch::Receiver<bool>,
+    job: F,
+) -> JoinHandle<()>
+where
+    F: Fn(Arc<AppState>) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        tracing::info!(task = name, interval_secs, "Housekeeping task started");
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let cycle = tokio::spawn(job(state.clone()));
+
+                    if let Err(e) = cycle.await {
+                        tracing::error!(task = name, error = %e, "Housekeeping cycle panicked - continuing");
+                    }
+                }
+                changed = shutdown_rx.changed() => {
+                    if changed.is_err() || *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        tracing::info!(task = name, "Housekeeping task stopped");
+    })
+}
+
+/// Run a single housekeeping pass. Returns true if any shared state changed.
+///
+/// For every robot in the fleet:
+/// - clears an expired manual lock
+/// - when the robot has gone stale, re-queues its active route at the front of
+///   the queue and forgets its registered `robot_url`
+///
+/// Then re-runs queue processing and broadcasts a `status_update` on change.
+pub async fn run_housekeeping_cycle(state: &Arc<AppState>) -> bool {
+    let mut changed = false;
+
+    for robot in state.robot_state.all_robots().await {
+        changed |= robot.clear_expired_lock().await;
+
+        if is_robot_stale(&robot).await {
+            // Bound first so the guard is released before the database write
+            let stale_route = robot.active_route.write().await.take();
+            if let Some(route) = stale_route {
+                tracing::warn!(
+                    route_id    = %route.id,
+                    robot_id    = %robot.id,
+                    start       = %route.start,
+                    destination = %route.destination,
+                    "Robot went stale during active route - re-queuing"
+                );
+                state
+                    .robot_state
+                    .queue
+                    .write()
+                    .await
+                    .push_front(route.clone());
+                if let Err(e) = crate::robot::route_store::requeue_front(&state.db, &route).await {
+                    crate::robot::route_store::log_persist_error(route.id, "requeue", &e);
+                }
+                changed = true;
+            }
+
+            let mut robot_url = robot.robot_url.write().await;
+            if let Some(url) = robot_url.take() {
+                tracing::warn!(
+                    robot_id  = %robot.id,
+                    robot_url = %url,
+                    "Robot went stale - forgetting registered URL"
+                );
+                changed = true;
+            }
+        }
+    }
+
+    if changed {
+        crate::robot::process_queue(state).await;
+        crate::robot::broadcast_status_update(state).await;
+    }
+
+    changed
+}
+
+/// Fold raw telemetry past its retention window into per-minute rollups and
+/// drop expired rollups.
+pub async fn compact_telemetry(state: &Arc<AppState>) {
+    match telemetry_store::compact(&state.db, Utc::now()).await {
+        Ok((rolled_up, purged)) if rolled_up > 0 || purged > 0 => {
+            tracing::info!(rolled_up, purged, "Robot telemetry compacted");
+        }
+        Ok(_) => {}
+        Err(e) => tracing::error!(error = %e, "Failed to compact robot telemetry"),
+    }
+}
+
+/// Permanently delete diary entries whose restore window has passed.
+pub async fn purge_deleted_diary_entries(state: &Arc<AppState>) {
+    match trash::purge(&state.db, Utc::now()).await {
+        Ok(purged) if purged > 0 => {
+            tracing::info!(purged, "Deleted diary entries purged");
+        }
+        Ok(_) => {}
+        Err(e) => tracing::error!(error = %e, "Failed to purge deleted diary entries"),
+    }
+}
+
+/// A robot that has never reported state is not stale, just not connected yet;
+/// it may have registered its URL before sending the first telemetry update.
+async fn is_robot_stale(robot: &RobotHandle) -> bool {
+    match *robot.last_state_update.read().await {
+        Some(t) => (Utc::now() - t).num_seconds() >= ROBOT_STALE_TIMEOUT_SECS,
+        None => false,
+    }
+}
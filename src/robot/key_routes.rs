@@ -0,0 +1,143 @@
+use crate::audit::{actions, AuditEvent};
+use crate::auth::extractor::ClientIp;
+use crate::auth::permissions::{RequirePermission, RobotKeys};
+use crate::robot::key_store;
+use crate::robot::models::{IssueRobotKeyRequest, IssuedRobotKey, RobotApiKey};
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+const MAX_ROBOT_ID_LEN: usize = 64;
+
+fn db_error(e: sqlx::Error, operation: &str) -> ApiError {
+    tracing::error!(error = %e, operation = operation, "DB error managing robot API keys");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Failed to manage robot API keys" })),
+    )
+}
+
+/// Robot ids appear in URLs and config strings (`id:key`), so keep them simple.
+fn is_valid_robot_id(robot_id: &str) -> bool {
+    !robot_id.is_empty()
+        && robot_id.len() <= MAX_ROBOT_ID_LEN
+        && robot_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub async fn list_robot_keys(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<RobotKeys>,
+    Path(robot_id): Path<String>,
+) -> Result<Json<Vec<RobotApiKey>>, ApiError> {
+    let keys = key_store::list(&state.db, &robot_id)
+        .await
+        .map_err(|e| db_error(e, "list"))?;
+
+    Ok(Json(keys))
+}
+
+/// Issue a key, adding the robot to the fleet if it is new. The plaintext key
+/// is only part of this response.
+pub async fn issue_robot_key(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { claims, .. }: RequirePermission<RobotKeys>,
+    ClientIp(client_ip): ClientIp,
+    Path(robot_id): Path<String>,
+    payload: Option<Json<IssueRobotKeyRequest>>,
+) -> Result<(StatusCode, Json<IssuedRobotKey>), ApiError> {
+    if !is_valid_robot_id(&robot_id) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Robot id must be 1-64 letters, digits, '-' or '_'"
+            })),
+        ));
+    }
+
+    let Json(payload) = payload.unwrap_or_default();
+    let label = payload
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty());
+
+    let issued = key_store::issue(&state.db, &robot_id, label, &claims.name)
+        .await
+        .map_err(|e| db_error(e, "issue"))?
+        .ok_or_else(|| {
+            (
+                StatusCode::CONFLICT,
+                Json(serde_json::json!({
+                    "error": format!(
+                        "Robot already has {} active keys; revoke one first",
+                        key_store::MAX_ACTIVE_KEYS_PER_ROBOT
+                    )
+                })),
+            )
+        })?;
+
+    tracing::info!(
+        robot_id   = %robot_id,
+        key_id     = %issued.key.id,
+        key_prefix = %issued.key.key_prefix,
+        issued_by  = %claims.name,
+        "Robot API key issued"
+    );
+    AuditEvent::new(actions::ROBOT_KEY_ISSUE, &claims)
+        .target("robot_key", issued.key.id)
+        .robot(&robot_id)
+        .after(&issued.key)
+        .client_ip(&client_ip)
+        .record(&state.db)
+        .await;
+
+    if state.robot_state.robot(&robot_id).await.is_none() {
+        let robot = state.robot_state.ensure_robot(&robot_id).await;
+        crate::robot::broadcast_robot_status(&state, &robot).await;
+    }
+
+    Ok((StatusCode::CREATED, Json(issued)))
+}
+
+pub async fn revoke_robot_key(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { claims, .. }: RequirePermission<RobotKeys>,
+    ClientIp(client_ip): ClientIp,
+    Path((robot_id, key_id)): Path<(String, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let revoked = key_store::revoke(&state.db, &robot_id, key_id)
+        .await
+        .map_err(|e| db_error(e, "revoke"))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Active robot API key not found" })),
+            )
+        })?;
+
+    tracing::info!(
+        robot_id   = %robot_id,
+        key_id     = %revoked.id,
+        key_prefix = %revoked.key_prefix,
+        revoked_by = %claims.name,
+        "Robot API key revoked"
+    );
+    AuditEvent::new(actions::ROBOT_KEY_REVOKE, &claims)
+        .target("robot_key", revoked.id)
+        .robot(&robot_id)
+        .after(&revoked)
+        .client_ip(&client_ip)
+        .record(&state.db)
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
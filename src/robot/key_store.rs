@@ -0,0 +1,154 @@
+// Persistence and verification of admin-issued robot API keys.
+//
+// A key is 32 random bytes, returned to the admin once when issued; only its
+// SHA-256 hash is stored. Keys are high-entropy, so a fast unsalted hash is
+// enough and lets a presented key be looked up by hash directly.
+
+use crate::robot::models::{IssuedRobotKey, RobotApiKey};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Active keys a robot may hold at once: the current key and its replacement
+/// while the robot is being rotated over.
+pub const MAX_ACTIVE_KEYS_PER_ROBOT: i64 = 2;
+
+/// `last_used_at` is only rewritten when older than this, so frequent
+/// telemetry does not turn every request into a write.
+const LAST_USED_RESOLUTION_SECS: f64 = 60.0;
+
+/// Marks issued keys so they are recognisable in configs and logs
+const KEY_TAG: &str = "rk_";
+/// Characters of the key (after the tag) kept in `key_prefix`
+const KEY_PREFIX_LEN: usize = 8;
+
+fn generate_key() -> String {
+    let bytes: [u8; 32] = rand::random();
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!("{KEY_TAG}{hex}")
+}
+
+/// SHA-256 of a robot key, hex encoded
+pub fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// Robot id of the active key matching `key`, if any. Records the use in
+/// `last_used_at`.
+pub async fn authenticate(db: &PgPool, key: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        r#"
+        WITH matched AS (
+            SELECT id, robot_id, last_used_at
+            FROM robot_api_keys
+            WHERE key_hash = $1 AND revoked_at IS NULL
+        ), touched AS (
+            UPDATE robot_api_keys
+            SET last_used_at = NOW()
+            WHERE id IN (
+                SELECT id FROM matched
+                WHERE last_used_at IS NULL
+                   OR last_used_at < NOW() - make_interval(secs => $2)
+            )
+        )
+        SELECT robot_id FROM matched
+        "#,
+    )
+    .bind(hash_key(key))
+    .bind(LAST_USED_RESOLUTION_SECS)
+    .fetch_optional(db)
+    .await
+}
+
+/// Issue a new key for `robot_id`. Returns `None` if the robot already has
+/// `MAX_ACTIVE_KEYS_PER_ROBOT` active keys.
+pub async fn issue(
+    db: &PgPool,
+    robot_id: &str,
+    label: Option<&str>,
+    created_by: &str,
+) -> Result<Option<IssuedRobotKey>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    // Serialise issuing per robot so concurrent requests cannot both pass
+    // the active key check.
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(robot_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let active: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM robot_api_keys WHERE robot_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(robot_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if active >= MAX_ACTIVE_KEYS_PER_ROBOT {
+        return Ok(None);
+    }
+
+    let api_key = generate_key();
+    let key = sqlx::query_as::<_, RobotApiKey>(
+        r#"
+        INSERT INTO robot_api_keys (robot_id, key_hash, key_prefix, label, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, robot_id, key_prefix, label, created_by, created_at, last_used_at, revoked_at
+        "#,
+    )
+    .bind(robot_id)
+    .bind(hash_key(&api_key))
+    .bind(&api_key[..KEY_TAG.len() + KEY_PREFIX_LEN])
+    .bind(label)
+    .bind(created_by)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(IssuedRobotKey { key, api_key }))
+}
+
+/// All keys of a robot, newest first, including revoked ones.
+pub async fn list(db: &PgPool, robot_id: &str) -> Result<Vec<RobotApiKey>, sqlx::Error> {
+    sqlx::query_as::<_, RobotApiKey>(
+        r#"
+        SELECT id, robot_id, key_prefix, label, created_by, created_at, last_used_at, revoked_at
+        FROM robot_api_keys
+        WHERE robot_id = $1
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(robot_id)
+    .fetch_all(db)
+    .await
+}
+
+/// Revoke an active key. Returns `None` if the robot has no such active key.
+pub async fn revoke(
+    db: &PgPool,
+    robot_id: &str,
+    key_id: Uuid,
+) -> Result<Option<RobotApiKey>, sqlx::Error> {
+    sqlx::query_as::<_, RobotApiKey>(
+        r#"
+        UPDATE robot_api_keys
+        SET revoked_at = NOW()
+        WHERE id = $1 AND robot_id = $2 AND revoked_at IS NULL
+        RETURNING id, robot_id, key_prefix, label, created_by, created_at, last_used_at, revoked_at
+        "#,
+    )
+    .bind(key_id)
+    .bind(robot_id)
+    .fetch_optional(db)
+    .await
+}
+
+/// Robots holding at least one active key, ordered by id.
+pub async fn active_robot_ids(db: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT DISTINCT robot_id FROM robot_api_keys WHERE revoked_at IS NULL ORDER BY robot_id",
+    )
+    .fetch_all(db)
+    .await
+}
@@ -1,9 +1,21 @@
 pub mod client_routes;
+pub mod graph;
+pub mod graph_routes;
+pub mod history_routes;
+pub mod housekeeping;
+pub mod key_routes;
+pub mod key_store;
 pub mod models;
+pub mod node_routes;
+pub mod node_store;
 mod optimization_helper;
 pub mod queue_routes;
 pub mod robot_routes;
+pub mod route_store;
+pub mod route_validation;
 pub mod state;
+pub mod telemetry_routes;
+pub mod telemetry_store;
 
 use crate::AppState;
 use models::{
@@ -12,6 +24,7 @@ use models::{
     RobotDebugRfidSensor, RobotDebugRouting, RobotDebugSensors, RobotDebugSnapshot,
     RobotDebugTelemetry, RobotStatusHttpResponse, RobotStatusUpdate,
 };
+use state::RobotHandle;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -20,10 +33,10 @@ const SENSOR_SOURCE_TABLE_STATE: &str = "table_state";
 const SENSOR_SOURCE_UNAVAILABLE: &str = "unavailable";
 const ROBOT_STATUS_TIMEOUT_SECS: u64 = 2;
 
-pub async fn build_status_update(state: &Arc<AppState>) -> RobotStatusUpdate {
-    let robot_state = state.robot_state.current_state.read().await;
-    let lock_state = state.robot_state.manual_lock.read().await;
-    let robot_connected = state.robot_state.is_robot_connected().await;
+pub async fn build_status_update(state: &Arc<AppState>, robot: &RobotHandle) -> RobotStatusUpdate {
+    let robot_state = robot.current_state.read().await;
+    let lock_state = robot.manual_lock.read().await;
+    let robot_connected = robot.is_robot_connected().await;
 
     let (system_health, battery_level, drive_mode, cargo_status, position, last_route) =
         if let Some(rs) = &*robot_state {
@@ -58,9 +71,24 @@ pub async fn build_status_update(state: &Arc<AppState>) -> RobotStatusUpdate {
         .filter(|l| l.expires_at > chrono::Utc::now())
         .map(|l| l.holder_name.clone());
 
-    let nodes = state.static_nodes.clone();
+    let nodes = state.robot_state.nodes.read().await.clone();
+
+    // A robot that cannot report its position itself is located by the RFID
+    // tag it last read.
+    let position = match robot_state
+        .as_ref()
+        .and_then(|rs| rs.last_read_uuid.as_deref())
+    {
+        Some(uuid) if position.is_empty() || position == "UNKNOWN" => nodes
+            .iter()
+            .find(|n| n.rfid_uuid.as_deref() == Some(uuid))
+            .map(|n| n.id.clone())
+            .unwrap_or(position),
+        _ => position,
+    };
 
     RobotStatusUpdate {
+        robot_id: robot.id.clone(),
         system_health,
         battery_level,
         drive_mode,
@@ -73,8 +101,16 @@ pub async fn build_status_update(state: &Arc<AppState>) -> RobotStatusUpdate {
     }
 }
 
+/// Broadcast a `status_update` for every robot in the fleet.
 pub async fn broadcast_status_update(state: &Arc<AppState>) {
-    let status_update = build_status_update(state).await;
+    for robot in state.robot_state.all_robots().await {
+        broadcast_robot_status(state, &robot).await;
+    }
+}
+
+/// Broadcast a `status_update` for a single robot.
+pub async fn broadcast_robot_status(state: &Arc<AppState>, robot: &RobotHandle) {
+    let status_update = build_status_update(state, robot).await;
     let _ = state.robot_state.status_sender.send(status_update);
 }
 
@@ -124,22 +160,27 @@ async fn fetch_robot_status(
     }
 }
 
-pub async fn build_debug_snapshot(state: &Arc<AppState>) -> RobotDebugSnapshot {
-    let current_state = state.robot_state.current_state.read().await.clone();
-    let robot_connected = state.robot_state.is_robot_connected().await;
-    let last_state_update = *state.robot_state.last_state_update.read().await;
-    let robot_url = state.robot_state.robot_url.read().await.clone();
-    let active_route = state.robot_state.active_route.read().await.clone();
+pub async fn build_debug_snapshot(
+    state: &Arc<AppState>,
+    robot: &RobotHandle,
+) -> RobotDebugSnapshot {
+    let current_state = robot.current_state.read().await.clone();
+    let robot_connected = robot.is_robot_connected().await;
+    let last_state_update = *robot.last_state_update.read().await;
+    let robot_url = robot.robot_url.read().await.clone();
+    let active_route = robot.active_route.read().await.clone();
+    // Routes this robot may pick up: pinned to it or to no robot
     let queue = state
         .robot_state
         .queue
         .read()
         .await
         .iter()
+        .filter(|r| r.robot_id.as_deref().is_none_or(|id| id == robot.id))
         .cloned()
         .collect::<Vec<_>>();
-    let lock = state.robot_state.manual_lock.read().await.clone();
-    let nodes = state.static_nodes.clone();
+    let lock = robot.manual_lock.read().await.clone();
+    let nodes = state.robot_state.nodes.read().await.clone();
     let robot_status = fetch_robot_status(state, robot_url.as_deref()).await;
     let robot_status_reachable = robot_status.is_some();
     let now = chrono::Utc::now();
@@ -308,10 +349,18 @@ pub async fn build_debug_snapshot(state: &Arc<AppState>) -> RobotDebugSnapshot {
         .as_ref()
         .and_then(|state| state.last_read_uuid.as_ref())
         .is_some();
+    let rfid_node_id = match current_state
+        .as_ref()
+        .and_then(|state| state.last_read_uuid.as_deref())
+    {
+        Some(uuid) => state.robot_state.node_for_rfid(uuid).await.map(|n| n.id),
+        None => None,
+    };
     let rfid_sensor = RobotDebugRfidSensor {
         last_read_uuid: current_state
             .as_ref()
             .and_then(|state| state.last_read_uuid.clone()),
+        node_id: rfid_node_id,
         source: if has_rfid {
             SENSOR_SOURCE_TABLE_STATE
         } else {
@@ -321,6 +370,7 @@ pub async fn build_debug_snapshot(state: &Arc<AppState>) -> RobotDebugSnapshot {
     };
 
     RobotDebugSnapshot {
+        robot_id: robot.id.clone(),
         telemetry: RobotDebugTelemetry {
             system_health,
             battery_level,
@@ -356,73 +406,85 @@ pub async fn build_debug_snapshot(state: &Arc<AppState>) -> RobotDebugSnapshot {
     }
 }
 
+/// Dispatch queued routes to every robot that can take one.
 pub async fn process_queue(state: &Arc<AppState>) {
+    for robot in state.robot_state.all_robots().await {
+        process_robot_queue(state, &robot).await;
+    }
+}
+
+/// Dispatch the first queued route this robot may drive (pinned to it or to
+/// no robot), if the robot is connected, idle, unlocked and has no active route.
+async fn process_robot_queue(state: &Arc<AppState>, robot: &RobotHandle) {
     // 1. Check Manual Lock (only if not expired)
-    {
-        let lock = state.robot_state.manual_lock.read().await;
-        if let Some(l) = &*lock {
-            if l.expires_at > chrono::Utc::now() {
-                return; // Active lock held, don't process queue
-            }
-        }
+    if robot.is_locked().await {
+        return; // Active lock held, don't process queue
     }
 
     // 2. Don't process queue if robot is disconnected/stale
-    if !state.robot_state.is_robot_connected().await {
+    if !robot.is_robot_connected().await {
         return;
     }
 
-    // 3. Check if Robot is IDLE
-    let is_idle = {
-        let rs = state.robot_state.current_state.read().await;
-        match &*rs {
-            Some(s) => s.drive_mode == "IDLE",
-            None => false, // Can't drive if unknown
-        }
-    };
-
-    if !is_idle {
+    // 3. Check if Robot is IDLE (can't drive if unknown)
+    if !robot.is_idle().await {
         return;
     }
 
     // 4. Check Active Route (should be None if we want to start one)
-    let mut active_route_guard = state.robot_state.active_route.write().await;
+    let mut active_route_guard = robot.active_route.write().await;
     if active_route_guard.is_some() {
         return;
     }
 
-    // 5. Pop from Queue
+    // 5. Take the first route this robot may drive
     let mut queue = state.robot_state.queue.write().await;
-    if let Some(next_route) = queue.pop_front() {
+    let mut dispatched_id = None;
+    let next_index = queue
+        .iter()
+        .position(|r| r.robot_id.as_deref().is_none_or(|id| id == robot.id));
+    if let Some(next_route) = next_index.and_then(|i| queue.remove(i)) {
         // 6. Send Command
         let cmd = RobotCommand::Navigate {
             start: next_route.start.clone(),
             destination: next_route.destination.clone(),
         };
 
-        match state.robot_state.command_sender.send(cmd) {
+        match robot.command_sender.send(cmd) {
             Ok(_) => {
                 // 7. Set Active
                 tracing::info!(
                     route_id    = %next_route.id,
+                    robot_id    = %robot.id,
                     start       = %next_route.start,
                     destination = %next_route.destination,
                     added_by    = %next_route.added_by,
                     "Dispatched route from queue"
                 );
+                dispatched_id = Some(next_route.id);
                 *active_route_guard = Some(next_route);
             }
             Err(e) => {
                 tracing::error!(
                     route_id    = %next_route.id,
+                    robot_id    = %robot.id,
                     start       = %next_route.start,
                     destination = %next_route.destination,
                     error       = %e,
                     "Failed to dispatch route command - re-queuing"
                 );
-                // Push back to front?
-                queue.push_front(next_route);
+                // Put it back where it was
+                queue.insert(next_index.unwrap_or(0), next_route);
             }
         }
     }
+    drop(queue);
+    drop(active_route_guard);
+
+    // 8. Persist dispatch outside the in-memory locks
+    if let Some(id) = dispatched_id {
+        if let Err(e) = route_store::mark_dispatched(&state.db, id, &robot.id).await {
+            route_store::log_persist_error(id, "dispatch", &e);
+        }
+    }
 }
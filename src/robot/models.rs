@@ -22,6 +22,32 @@ pub struct QueuedRoute {
     pub destination: String,
     pub added_at: DateTime<Utc>,
     pub added_by: String, // User name or ID
+    /// Robot the route is pinned to (`None` = any robot)
+    #[serde(default)]
+    pub robot_id: Option<String>,
+}
+
+/// Lifecycle status of a route persisted in `route_queue`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteStatus {
+    Queued,
+    Dispatched,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl RouteStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RouteStatus::Queued => "queued",
+            RouteStatus::Dispatched => "dispatched",
+            RouteStatus::Completed => "completed",
+            RouteStatus::Cancelled => "cancelled",
+            RouteStatus::Failed => "failed",
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -100,6 +126,8 @@ impl RobotEventPriority {
 pub struct RobotNode {
     pub id: String,
     pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rfid_uuid: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -155,6 +183,7 @@ pub struct LastRoute {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RobotStatusUpdate {
+    pub robot_id: String,
     pub system_health: String,
     pub battery_level: u8,
     pub drive_mode: String,
@@ -188,11 +217,34 @@ pub struct NodesResponse {
 pub struct RouteSelectionRequest {
     pub start: String,
     pub destination: String,
+    pub robot_id: Option<String>,
+}
+
+/// Optional `?robot_id=` parameter; may be omitted while the fleet has one robot.
+#[derive(Debug, Deserialize)]
+pub struct RobotIdQuery {
+    pub robot_id: Option<String>,
+}
+
+/// Per-robot entry of `GET /robots`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RobotSummary {
+    pub id: String,
+    pub connected: bool,
+    pub drive_mode: String,
+    pub position: String,
+    pub battery_level: u8,
+    pub last_state_update: Option<DateTime<Utc>>,
+    pub active_route: Option<QueuedRoute>,
+    pub queued_routes: usize,
+    pub manual_lock_holder_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RobotDebugSnapshot {
+    pub robot_id: String,
     pub telemetry: RobotDebugTelemetry,
     pub lock: RobotDebugLock,
     pub routing: RobotDebugRouting,
@@ -286,6 +338,8 @@ pub struct RobotDebugGyroscopeSensor {
 #[serde(rename_all = "camelCase")]
 pub struct RobotDebugRfidSensor {
     pub last_read_uuid: Option<String>,
+    /// Node whose RFID tag matches `last_read_uuid`, if any
+    pub node_id: Option<String>,
     pub source: String,
 }
 
@@ -326,3 +380,183 @@ pub struct RobotStatusHttpPower {
     pub current_a: f32,
     pub power_w: f32,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct RouteHistoryEntry {
+    pub id: Uuid,
+    pub robot_id: Option<String>,
+    pub start: String,
+    pub destination: String,
+    pub status: String,
+    pub added_by: String,
+    pub added_at: DateTime<Utc>,
+    pub dispatched_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub cancelled_by: Option<String>,
+    pub duration_seconds: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RouteHistoryQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub user: Option<String>,
+    pub start: Option<String>,
+    pub destination: Option<String>,
+    pub status: Option<RouteStatus>,
+    pub robot_id: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RouteStatsQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct RoutePairStats {
+    pub start: String,
+    pub destination: String,
+    pub trips: i64,
+    pub avg_duration_seconds: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct DailyDeliveries {
+    pub day: chrono::NaiveDate,
+    pub deliveries: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RouteStatsResponse {
+    pub completed: i64,
+    pub cancelled: i64,
+    pub failed: i64,
+    pub cancellation_rate: f64,
+    pub pairs: Vec<RoutePairStats>,
+    pub deliveries_per_day: Vec<DailyDeliveries>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct NodeEdge {
+    pub from_node: String,
+    pub to_node: String,
+    pub cost_seconds: f64,
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertEdgeRequest {
+    pub from: String,
+    pub to: String,
+    pub cost_seconds: f64,
+    #[serde(default)]
+    pub bidirectional: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PathCostQuery {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PathCostResponse {
+    pub from: String,
+    pub to: String,
+    pub cost_seconds: f64,
+    pub path: Vec<String>,
+}
+
+/// Full `nodes` row, including disabled nodes, for the admin node endpoints.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct NodeRecord {
+    pub id: String,
+    pub label: String,
+    pub rfid_uuid: Option<String>,
+    pub enabled: bool,
+    pub sort_order: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateNodeRequest {
+    pub id: String,
+    pub label: String,
+    pub rfid_uuid: Option<String>,
+}
+
+/// Partial node update. An empty `rfid_uuid` clears the tag.
+#[derive(Debug, Deserialize)]
+pub struct UpdateNodeRequest {
+    pub label: Option<String>,
+    pub rfid_uuid: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReorderNodesRequest {
+    pub ids: Vec<String>,
+}
+
+/// Admin view of a `robot_api_keys` row. The key itself is never stored.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct RobotApiKey {
+    pub id: Uuid,
+    pub robot_id: String,
+    /// First characters of the key, to tell keys apart
+    pub key_prefix: String,
+    pub label: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct IssueRobotKeyRequest {
+    pub label: Option<String>,
+}
+
+/// Response to issuing a key: the only time the plaintext key is returned.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IssuedRobotKey {
+    #[serde(flatten)]
+    pub key: RobotApiKey,
+    pub api_key: String,
+}
+
+/// Query for `GET /robot/telemetry`. `fields` is a comma-separated list of
+/// metric names; `bucket` is the bucket width in seconds.
+#[derive(Debug, Deserialize)]
+pub struct TelemetryQuery {
+    pub robot_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub fields: Option<String>,
+    pub bucket: Option<i64>,
+}
+
+/// Aggregate of one metric over one bucket
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TelemetryPoint {
+    pub bucket: DateTime<Utc>,
+    pub samples: i64,
+    pub avg: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TelemetryResponse {
+    pub robot_id: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub bucket_seconds: i64,
+    /// One series per requested metric, keyed by metric name
+    pub series: std::collections::BTreeMap<String, Vec<TelemetryPoint>>,
+}
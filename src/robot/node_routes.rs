@@ -0,0 +1,179 @@
+use crate::auth::permissions::{NodesManage, RequirePermission};
+use crate::robot::models::{CreateNodeRequest, NodeRecord, ReorderNodesRequest, UpdateNodeRequest};
+use crate::robot::node_store;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+fn bad_request(message: &str) -> ApiError {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": message })),
+    )
+}
+
+/// Map a failed node write to 409 for duplicate ids / RFID tags, 500 otherwise.
+fn write_error(e: sqlx::Error, operation: &str) -> ApiError {
+    let duplicate = e
+        .as_database_error()
+        .is_some_and(|db_err| db_err.is_unique_violation());
+
+    if duplicate {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "Node id or RFID tag already in use" })),
+        );
+    }
+
+    tracing::error!(error = %e, operation = operation, "DB error writing node");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": "Failed to save node" })),
+    )
+}
+
+/// Reload the in-memory registry and push the new node list to status subscribers.
+async fn publish_nodes(state: &Arc<AppState>) -> Result<(), ApiError> {
+    node_store::refresh(&state.db, &state.robot_state)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "DB error reloading node registry");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to reload nodes" })),
+            )
+        })?;
+
+    crate::robot::broadcast_status_update(state).await;
+    Ok(())
+}
+
+fn normalize_rfid(rfid_uuid: Option<&str>) -> Option<&str> {
+    rfid_uuid.map(str::trim).filter(|tag| !tag.is_empty())
+}
+
+pub async fn list_all_nodes(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<NodesManage>,
+) -> Result<Json<Vec<NodeRecord>>, ApiError> {
+    let nodes = node_store::list_all(&state.db).await.map_err(|e| {
+        tracing::error!(error = %e, "DB error listing nodes");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to fetch nodes" })),
+        )
+    })?;
+
+    Ok(Json(nodes))
+}
+
+pub async fn create_node(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<NodesManage>,
+    Json(payload): Json<CreateNodeRequest>,
+) -> Result<(StatusCode, Json<NodeRecord>), ApiError> {
+    let id = payload.id.trim();
+    let label = payload.label.trim();
+
+    if id.is_empty() || label.is_empty() {
+        return Err(bad_request("Node id and label must not be empty"));
+    }
+
+    let node = node_store::insert(
+        &state.db,
+        id,
+        label,
+        normalize_rfid(payload.rfid_uuid.as_deref()),
+    )
+    .await
+    .map_err(|e| write_error(e, "create"))?;
+
+    publish_nodes(&state).await?;
+
+    tracing::info!(node_id = %node.id, label = %node.label, "Node created");
+
+    Ok((StatusCode::CREATED, Json(node)))
+}
+
+pub async fn update_node(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<NodesManage>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateNodeRequest>,
+) -> Result<Json<NodeRecord>, ApiError> {
+    let label = payload.label.as_deref().map(str::trim);
+    if label.is_some_and(str::is_empty) {
+        return Err(bad_request("Node label must not be empty"));
+    }
+
+    let rfid_uuid = payload
+        .rfid_uuid
+        .as_deref()
+        .map(|tag| normalize_rfid(Some(tag)));
+
+    let node = node_store::update(&state.db, &id, label, rfid_uuid, payload.enabled)
+        .await
+        .map_err(|e| write_error(e, "update"))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Node not found" })),
+            )
+        })?;
+
+    publish_nodes(&state).await?;
+
+    tracing::info!(
+        node_id = %node.id,
+        label   = %node.label,
+        enabled = node.enabled,
+        "Node updated"
+    );
+
+    Ok(Json(node))
+}
+
+/// Reorder nodes. `ids` must list every node (enabled or not) exactly once.
+pub async fn reorder_nodes(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<NodesManage>,
+    Json(payload): Json<ReorderNodesRequest>,
+) -> Result<Json<Vec<NodeRecord>>, ApiError> {
+    let db_error = |e: sqlx::Error| {
+        tracing::error!(error = %e, "DB error reordering nodes");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to reorder nodes" })),
+        )
+    };
+
+    let existing: HashSet<String> = node_store::list_all(&state.db)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|n| n.id)
+        .collect();
+    let requested: HashSet<String> = payload.ids.iter().cloned().collect();
+
+    if requested.len() != payload.ids.len() || requested != existing {
+        return Err(bad_request("ids must list every node exactly once"));
+    }
+
+    node_store::save_order(&state.db, &payload.ids)
+        .await
+        .map_err(db_error)?;
+
+    publish_nodes(&state).await?;
+
+    tracing::info!(count = payload.ids.len(), "Nodes reordered");
+
+    let nodes = node_store::list_all(&state.db).await.map_err(db_error)?;
+    Ok(Json(nodes))
+}
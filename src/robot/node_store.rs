@@ -0,0 +1,104 @@
+// Persistence for the navigation node registry.
+//
+// `nodes` is the source of truth; `SharedRobotState.nodes` caches the enabled
+// nodes in display order and is reloaded after every admin change so
+// `GET /nodes` and `RobotStatusUpdate.nodes` never need a database round trip.
+
+use crate::robot::models::{NodeRecord, RobotNode};
+use crate::robot::state::SharedRobotState;
+use sqlx::PgPool;
+
+/// All nodes, including disabled ones, in display order.
+pub async fn list_all(db: &PgPool) -> Result<Vec<NodeRecord>, sqlx::Error> {
+    sqlx::query_as::<_, NodeRecord>(
+        r#"
+        SELECT id, label, rfid_uuid, enabled, sort_order, created_at, updated_at
+        FROM nodes
+        ORDER BY sort_order, id
+        "#,
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Reload the enabled nodes into the in-memory registry. Returns the number
+/// of enabled nodes.
+pub async fn refresh(db: &PgPool, robot_state: &SharedRobotState) -> Result<usize, sqlx::Error> {
+    let nodes: Vec<RobotNode> = list_all(db)
+        .await?
+        .into_iter()
+        .filter(|n| n.enabled)
+        .map(|n| RobotNode {
+            id: n.id,
+            label: n.label,
+            rfid_uuid: n.rfid_uuid,
+        })
+        .collect();
+
+    let count = nodes.len();
+    *robot_state.nodes.write().await = nodes;
+    Ok(count)
+}
+
+pub async fn insert(
+    db: &PgPool,
+    id: &str,
+    label: &str,
+    rfid_uuid: Option<&str>,
+) -> Result<NodeRecord, sqlx::Error> {
+    sqlx::query_as::<_, NodeRecord>(
+        r#"
+        INSERT INTO nodes (id, label, rfid_uuid, sort_order)
+        VALUES ($1, $2, $3, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM nodes))
+        RETURNING id, label, rfid_uuid, enabled, sort_order, created_at, updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(label)
+    .bind(rfid_uuid)
+    .fetch_one(db)
+    .await
+}
+
+/// Apply a partial update. `rfid_uuid` is `Some(None)` to clear the tag.
+pub async fn update(
+    db: &PgPool,
+    id: &str,
+    label: Option<&str>,
+    rfid_uuid: Option<Option<&str>>,
+    enabled: Option<bool>,
+) -> Result<Option<NodeRecord>, sqlx::Error> {
+    sqlx::query_as::<_, NodeRecord>(
+        r#"
+        UPDATE nodes
+        SET label = COALESCE($2, label),
+            rfid_uuid = CASE WHEN $3 THEN $4 ELSE rfid_uuid END,
+            enabled = COALESCE($5, enabled)
+        WHERE id = $1
+        RETURNING id, label, rfid_uuid, enabled, sort_order, created_at, updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(label)
+    .bind(rfid_uuid.is_some())
+    .bind(rfid_uuid.flatten())
+    .bind(enabled)
+    .fetch_optional(db)
+    .await
+}
+
+/// Rewrite `sort_order` so nodes appear in the order of `ids`.
+pub async fn save_order(db: &PgPool, ids: &[String]) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE nodes
+        SET sort_order = ordered.position
+        FROM UNNEST($1::text[]) WITH ORDINALITY AS ordered(id, position)
+        WHERE nodes.id = ordered.id
+        "#,
+    )
+    .bind(ids)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
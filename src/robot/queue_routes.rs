@@ -1,27 +1,33 @@
-use crate::auth::models::Claims;
-use crate::auth::roles;
+use crate::audit::{actions, AuditEvent};
+use crate::auth::extractor::ClientIp;
+use crate::auth::permissions::{QueueManage, RequirePermission};
+use crate::robot::graph;
 use crate::robot::models::QueuedRoute;
+use crate::robot::route_store;
+use crate::robot::route_validation;
 use crate::AppState;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
-    Extension, Json,
+    Json,
 };
 use chrono::Utc;
 use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Active routes of every robot (by robot id), followed by the queue.
 pub async fn get_routes(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let active = state.robot_state.active_route.read().await.clone();
-    let queue = state.robot_state.queue.read().await;
-    let mut routes = Vec::with_capacity(queue.len() + if active.is_some() { 1 } else { 0 });
-
-    if let Some(route) = active {
-        routes.push(route);
+    let mut routes = Vec::new();
+    for robot in state.robot_state.all_robots().await {
+        if let Some(route) = robot.active_route.read().await.clone() {
+            routes.push(route);
+        }
     }
 
+    let queue = state.robot_state.queue.read().await;
+
     routes.extend(queue.iter().cloned());
 
     Json(routes)
@@ -31,21 +37,43 @@ pub async fn get_routes(State(state): State<Arc<AppState>>) -> impl IntoResponse
 pub struct AddRouteRequest {
     pub start: String,
     pub destination: String,
+    /// Pin the route to one robot; any robot may drive it when omitted
+    pub robot_id: Option<String>,
 }
 
 pub async fn add_route(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
+    RequirePermission { claims, .. }: RequirePermission<QueueManage>,
     Json(payload): Json<AddRouteRequest>,
 ) -> impl IntoResponse {
-    if !roles::is_admin(&claims.role) {
+    let pinned =
+        match route_validation::pinned_robot(&state.robot_state, payload.robot_id.as_deref()).await
+        {
+            Ok(pinned) => pinned,
+            Err(e) => return e.into_response(),
+        };
+
+    let expected_start = if state.config.enforce_route_chaining {
+        route_validation::next_queued_start(&state.robot_state, pinned.as_ref()).await
+    } else {
+        None
+    };
+    if let Err(e) = route_validation::validate_route(
+        &state.robot_state,
+        &payload.start,
+        &payload.destination,
+        expected_start.as_deref(),
+    )
+    .await
+    {
         tracing::warn!(
-            user_id = %claims.sub,
-            name    = %claims.name,
-            role    = %claims.role,
-            "Permission denied - add_route requires admin (403)"
+            user_id     = %claims.sub,
+            start       = %payload.start,
+            destination = %payload.destination,
+            reason      = e.code(),
+            "Add route rejected (422)"
         );
-        return StatusCode::FORBIDDEN.into_response();
+        return e.into_response();
     }
 
     let route = QueuedRoute {
@@ -54,8 +82,14 @@ pub async fn add_route(
         destination: payload.destination,
         added_at: Utc::now(),
         added_by: claims.name,
+        robot_id: pinned.map(|robot| robot.id),
     };
 
+    if let Err(e) = route_store::enqueue_back(&state.db, &route).await {
+        tracing::error!(route_id = %route.id, error = %e, "DB error persisting queued route");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
     let mut queue = state.robot_state.queue.write().await;
     queue.push_back(route.clone());
     drop(queue);
@@ -65,6 +99,7 @@ pub async fn add_route(
         start       = %route.start,
         destination = %route.destination,
         added_by    = %route.added_by,
+        robot_id    = ?route.robot_id,
         "Route added to queue"
     );
 
@@ -77,25 +112,26 @@ pub async fn add_route(
 
 pub async fn delete_route(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
+    RequirePermission { claims, .. }: RequirePermission<QueueManage>,
+    ClientIp(client_ip): ClientIp,
     Path(id): Path<Uuid>,
 ) -> impl IntoResponse {
-    if !roles::is_admin(&claims.role) {
-        tracing::warn!(
-            user_id  = %claims.sub,
-            name     = %claims.name,
-            role     = %claims.role,
-            route_id = %id,
-            "Permission denied - delete_route requires admin (403)"
-        );
-        return StatusCode::FORBIDDEN.into_response();
-    }
-
     let mut queue = state.robot_state.queue.write().await;
     if let Some(pos) = queue.iter().position(|r| r.id == id) {
-        queue.remove(pos);
+        let removed = queue.remove(pos);
         drop(queue);
         tracing::info!(route_id = %id, deleted_by = %claims.name, "Route removed from queue");
+        if let Err(e) = route_store::cancel(&state.db, id, &claims.name).await {
+            route_store::log_persist_error(id, "cancel", &e);
+        }
+        let mut event = AuditEvent::new(actions::ROUTE_DELETE, &claims)
+            .target("route", id)
+            .before(&removed)
+            .client_ip(&client_ip);
+        if let Some(robot_id) = removed.as_ref().and_then(|r| r.robot_id.as_deref()) {
+            event = event.robot(robot_id);
+        }
+        event.record(&state.db).await;
         crate::robot::broadcast_status_update(&state).await;
         StatusCode::NO_CONTENT.into_response()
     } else {
@@ -105,30 +141,39 @@ pub async fn delete_route(
 
 pub async fn optimize_routes(
     State(state): State<Arc<AppState>>,
-    Extension(claims): Extension<Claims>,
+    _: RequirePermission<QueueManage>,
 ) -> impl IntoResponse {
-    if !roles::is_admin(&claims.role) {
-        tracing::warn!(
-            user_id = %claims.sub,
-            name    = %claims.name,
-            role    = %claims.role,
-            "Permission denied - optimize_routes requires admin (403)"
-        );
-        return StatusCode::FORBIDDEN.into_response();
-    }
+    // Load the graph before taking the queue lock; an unavailable graph
+    // degrades to uniform costs rather than failing the request.
+    let node_graph = graph::load_graph(&state.db).await.unwrap_or_else(|e| {
+        tracing::error!(error = %e, "DB error loading node graph - optimizing with uniform costs");
+        graph::NodeGraph::default()
+    });
 
     let mut guard = state.robot_state.queue.write().await;
     let routes: Vec<_> = guard.iter().cloned().collect();
+    let costs = node_graph.cost_table(routes.iter().map(|r| r.destination.as_str()));
     let optimized = crate::robot::optimization_helper::solve_atsp_path(routes, |from, to| {
         if from == to {
             0.0
+        } else if node_graph.is_empty() {
+            1.0
         } else {
-            1.0 // replace with real distance / latency / lookup
+            costs
+                .get(&(from.to_string(), to.to_string()))
+                .copied()
+                .unwrap_or(graph::UNREACHABLE_COST)
         }
     });
 
     guard.truncate(0);
-    guard.extend(optimized);
+    guard.extend(optimized.iter().cloned());
+
+    // Persist before releasing the queue, so a concurrent optimize cannot
+    // store its order first and leave the table disagreeing with memory.
+    if let Err(e) = route_store::save_order(&state.db, &optimized).await {
+        tracing::error!(error = %e, "DB error persisting optimized route order");
+    }
     drop(guard);
 
     crate::robot::broadcast_status_update(&state).await;
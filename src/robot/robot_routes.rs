@@ -1,5 +1,9 @@
+use crate::auth::login::extract_client_ip;
 use crate::notifications::models::RobotNotification;
-use crate::robot::models::{RobotEvent, RobotState};
+use crate::robot::key_store;
+use crate::robot::models::{RobotEvent, RobotState, RouteStatus};
+use crate::robot::route_store;
+use crate::robot::telemetry_store;
 use crate::AppState;
 use axum::{
     extract::{ConnectInfo, State},
@@ -12,46 +16,132 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// WebSocket subprotocol a robot offers alongside its API key
+/// (`Sec-WebSocket-Protocol: robot, <api-key>`), for clients that cannot set
+/// custom headers on the upgrade request.
+pub const ROBOT_WS_PROTOCOL: &str = "robot";
+
+/// Robot credential from the `X-Api-Key` header, or failing that the
+/// non-`robot` entry of `Sec-WebSocket-Protocol`.
+fn robot_credential(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get("X-Api-Key").and_then(|v| v.to_str().ok()) {
+        return Some(key);
+    }
+
+    headers
+        .get_all("Sec-WebSocket-Protocol")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .find(|p| !p.is_empty() && *p != ROBOT_WS_PROTOCOL)
+}
+
+/// Robot a key belongs to: an active key issued through the admin API, or a
+/// bootstrap key from the environment. Keys are only ever compared by hash.
+async fn robot_for_key(state: &AppState, key: &str) -> Option<String> {
+    match key_store::authenticate(&state.db, key).await {
+        Ok(Some(robot_id)) => return Some(robot_id),
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!(error = %e, "DB error checking robot API key - trying configured keys");
+        }
+    }
+
+    let key_hash = key_store::hash_key(key);
+    state
+        .config
+        .robot_keys()
+        .into_iter()
+        .find(|(_, configured)| key_store::hash_key(configured) == key_hash)
+        .map(|(robot_id, _)| robot_id)
+}
+
+/// Check the robot credential and return the id of the robot it belongs to,
+/// logging and returning a 401 response on failure.
+pub(crate) async fn authenticate_robot(
+    state: &AppState,
+    headers: &HeaderMap,
+    endpoint: &str,
+) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let provided = robot_credential(headers);
+    let robot_id = match provided {
+        Some(key) => robot_for_key(state, key).await,
+        None => None,
+    };
+
+    match robot_id {
+        Some(robot_id) => Ok(robot_id),
+        None => {
+            tracing::warn!(
+                endpoint    = %endpoint,
+                key_present = provided.is_some(),
+                "Robot request rejected - invalid API key (401)"
+            );
+            Err((
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Invalid API Key"
+                })),
+            ))
+        }
+    }
+}
+
 pub async fn update_robot_state(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
     Json(payload): Json<RobotState>,
 ) -> impl IntoResponse {
-    let api_key = headers.get("X-Api-Key").and_then(|v| v.to_str().ok());
-
-    if api_key != Some(&state.config.robot_api_key) {
-        tracing::warn!("Robot state update rejected - invalid API key");
-        return (
-            StatusCode::UNAUTHORIZED,
-            Json(serde_json::json!({
-                "status": "error",
-                "message": "Invalid API Key"
-            })),
-        )
-            .into_response();
-    }
+    let robot = match authenticate_robot(&state, &headers, "/table/state").await {
+        Ok(robot_id) => state.robot_state.ensure_robot(&robot_id).await,
+        Err(response) => return response.into_response(),
+    };
+    let now = chrono::Utc::now();
     {
-        let mut current_state = state.robot_state.current_state.write().await;
+        let mut current_state = robot.current_state.write().await;
         *current_state = Some(payload.clone());
     }
     {
-        let mut last_update = state.robot_state.last_state_update.write().await;
-        *last_update = Some(chrono::Utc::now());
+        let mut last_update = robot.last_state_update.write().await;
+        *last_update = Some(now);
+    }
+
+    if robot.take_telemetry_slot(now).await {
+        if let Err(e) = telemetry_store::record(&state.db, &robot.id, now, &payload).await {
+            tracing::error!(robot_id = %robot.id, error = %e, "Failed to persist robot telemetry");
+        }
     }
 
     // Queue Logic
-    {
-        let mut active_route_guard = state.robot_state.active_route.write().await;
+    let finished = {
+        let mut active_route_guard = robot.active_route.write().await;
 
         // Check if we just finished a route
         if active_route_guard.is_some() && payload.drive_mode == "IDLE" {
             // Assumption: IDLE means finished.
-            tracing::info!(drive_mode = %payload.drive_mode, "Active route finished - robot returned to IDLE");
-            *active_route_guard = None;
+            tracing::info!(
+                robot_id   = %robot.id,
+                drive_mode = %payload.drive_mode,
+                "Active route finished - robot returned to IDLE"
+            );
+            active_route_guard.take()
+        } else {
+            None
+        }
+    };
+    // Persist outside the lock so a slow database cannot stall the robot
+    if let Some(finished) = finished {
+        if let Err(e) =
+            route_store::set_status(&state.db, finished.id, RouteStatus::Completed).await
+        {
+            route_store::log_persist_error(finished.id, "complete", &e);
         }
     }
 
-    // Trigger processing (checks IDLE, Lock, Queue)
+    // Trigger processing (checks IDLE, Lock, Queue). Every robot is
+    // rebroadcast, as a dispatch changes the queue seen by the others.
     crate::robot::process_queue(&state).await;
     crate::robot::broadcast_status_update(&state).await;
 
@@ -66,19 +156,10 @@ pub async fn handle_robot_event(
     headers: HeaderMap,
     Json(payload): Json<RobotEvent>,
 ) -> impl IntoResponse {
-    let api_key = headers.get("X-Api-Key").and_then(|v| v.to_str().ok());
-
-    if api_key != Some(&state.config.robot_api_key) {
-        tracing::warn!("Robot event rejected - invalid API key");
-        return (
-            StatusCode::UNAUTHORIZED,
-            Json(serde_json::json!({
-                "status": "error",
-                "message": "Invalid API Key"
-            })),
-        )
-            .into_response();
-    }
+    let robot_id = match authenticate_robot(&state, &headers, "/table/event").await {
+        Ok(robot_id) => robot_id,
+        Err(response) => return response.into_response(),
+    };
 
     let message = payload.message.trim();
     if message.is_empty() {
@@ -94,12 +175,13 @@ pub async fn handle_robot_event(
 
     let notification = match sqlx::query_as::<_, RobotNotification>(
         r#"
-        INSERT INTO robot_notifications (id, priority, message)
-        VALUES ($1, $2, $3)
-        RETURNING id, priority, message, received_at
+        INSERT INTO robot_notifications (id, robot_id, priority, message)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, robot_id, priority, message, received_at
         "#,
     )
     .bind(Uuid::new_v4())
+    .bind(&robot_id)
     .bind(payload.priority.as_str())
     .bind(message)
     .fetch_one(&state.db)
@@ -125,6 +207,7 @@ pub async fn handle_robot_event(
         .send(notification.clone());
 
     tracing::info!(
+        robot_id = %robot_id,
         priority = %notification.priority,
         message  = %notification.message,
         "Received and broadcast robot event"
@@ -148,31 +231,26 @@ pub async fn register_robot(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(payload): Json<RobotRegistration>,
 ) -> impl IntoResponse {
-    let mut ip = addr.ip();
+    let robot = match authenticate_robot(&state, &headers, "/table/register").await {
+        Ok(robot_id) => state.robot_state.ensure_robot(&robot_id).await,
+        Err(response) => return response.into_response(),
+    };
 
-    // Prioritize X-Real-IP, then X-Forwarded-For, then socket address
-    if let Some(real_ip_str) = headers.get("X-Real-IP").and_then(|v| v.to_str().ok()) {
-        if let Ok(parsed_ip) = real_ip_str.parse() {
-            ip = parsed_ip;
-        }
-    } else if let Some(fwd_str) = headers.get("X-Forwarded-For").and_then(|v| v.to_str().ok()) {
-        if let Some(first_ip) = fwd_str.split(',').next() {
-            if let Ok(parsed_ip) = first_ip.trim().parse() {
-                ip = parsed_ip;
-            }
-        }
-    }
+    // Forwarded headers count only from a trusted proxy, as for users
+    let ip = extract_client_ip(&headers, Some(addr), &state.config.trusted_proxies);
 
     let port = payload.port;
     let url = format!("http://{ip}:{port}");
 
-    let mut url_lock = state.robot_state.robot_url.write().await;
+    let mut url_lock = robot.robot_url.write().await;
     if url_lock.as_deref() != Some(&url) {
-        tracing::info!("Registered robot at {}", url);
+        tracing::info!(robot_id = %robot.id, "Registered robot at {}", url);
         *url_lock = Some(url);
     }
 
-    crate::robot::broadcast_status_update(&state).await;
+    drop(url_lock);
+
+    crate::robot::broadcast_robot_status(&state, &robot).await;
 
-    StatusCode::OK
+    StatusCode::OK.into_response()
 }
@@ -0,0 +1,272 @@
+// Write-through persistence for the in-memory route queue.
+//
+// `SharedRobotState.queue` and `active_route` stay the source of truth while
+// the process is running; every mutation is mirrored into `route_queue` so
+// the queue can be rehydrated on startup. Queue order is kept in
+// `queue_position` (ascending), which only has meaning for `queued` rows.
+
+use crate::robot::models::{QueuedRoute, RouteStatus};
+use crate::robot::state::SharedRobotState;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+/// Serialise writes to `queue_position` so concurrent inserts cannot read the
+/// same MIN/MAX and end up with the same position. Held until commit.
+async fn lock_positions(tx: &mut Transaction<'_, Postgres>) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('route_queue.queue_position'))")
+        .execute(&mut **tx)
+        .await
+        .map(|_| ())
+}
+
+/// Insert a new route at the back of the persisted queue.
+pub async fn enqueue_back(db: &PgPool, route: &QueuedRoute) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    lock_positions(&mut tx).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO route_queue
+            (id, start, destination, added_by, added_at, robot_id, status, queue_position)
+        VALUES ($1, $2, $3, $4, $5, $6, 'queued',
+            (SELECT COALESCE(MAX(queue_position), 0) + 1 FROM route_queue WHERE status = 'queued'))
+        "#,
+    )
+    .bind(route.id)
+    .bind(&route.start)
+    .bind(&route.destination)
+    .bind(&route.added_by)
+    .bind(route.added_at)
+    .bind(route.robot_id.as_deref())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+/// Move an existing route (typically the preempted or stale active route)
+/// back to the front of the persisted queue.
+pub async fn requeue_front(db: &PgPool, route: &QueuedRoute) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    lock_positions(&mut tx).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO route_queue
+            (id, start, destination, added_by, added_at, robot_id, status, queue_position)
+        VALUES ($1, $2, $3, $4, $5, $6, 'queued',
+            (SELECT COALESCE(MIN(queue_position), 0) - 1 FROM route_queue WHERE status = 'queued'))
+        ON CONFLICT (id) DO UPDATE
+        SET status = 'queued',
+            queue_position = EXCLUDED.queue_position,
+            dispatched_robot_id = NULL
+        "#,
+    )
+    .bind(route.id)
+    .bind(&route.start)
+    .bind(&route.destination)
+    .bind(&route.added_by)
+    .bind(route.added_at)
+    .bind(route.robot_id.as_deref())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+/// Record a route that was sent to a robot without passing through the queue
+/// (admin `NAVIGATE` over `/ws/drive/manual`).
+pub async fn insert_dispatched(
+    db: &PgPool,
+    route: &QueuedRoute,
+    robot_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO route_queue
+            (id, start, destination, added_by, added_at, robot_id, dispatched_robot_id,
+             status, dispatched_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'dispatched', NOW())
+        "#,
+    )
+    .bind(route.id)
+    .bind(&route.start)
+    .bind(&route.destination)
+    .bind(&route.added_by)
+    .bind(route.added_at)
+    .bind(route.robot_id.as_deref())
+    .bind(robot_id)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+/// Mark a queued route as dispatched to `robot_id`.
+pub async fn mark_dispatched(db: &PgPool, id: Uuid, robot_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE route_queue
+        SET status = 'dispatched',
+            dispatched_at = NOW(),
+            dispatched_robot_id = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(robot_id)
+    .bind(id)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+/// Transition a persisted route to a new lifecycle status.
+///
+/// `dispatched` stamps `dispatched_at`; terminal statuses stamp `finished_at`.
+pub async fn set_status(db: &PgPool, id: Uuid, status: RouteStatus) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE route_queue
+        SET status = $1,
+            dispatched_at = CASE WHEN $1 = 'dispatched' THEN NOW() ELSE dispatched_at END,
+            finished_at = CASE WHEN $1 IN ('completed', 'cancelled', 'failed') THEN NOW() ELSE finished_at END
+        WHERE id = $2
+        "#,
+    )
+    .bind(status.as_str())
+    .bind(id)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+/// Mark a route as cancelled and record who cancelled it.
+pub async fn cancel(db: &PgPool, id: Uuid, cancelled_by: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE route_queue
+        SET status = 'cancelled',
+            finished_at = NOW(),
+            cancelled_by = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(cancelled_by)
+    .bind(id)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+/// Rewrite `queue_position` so the persisted order matches `routes`.
+pub async fn save_order(db: &PgPool, routes: &[QueuedRoute]) -> Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = routes.iter().map(|r| r.id).collect();
+    let mut tx = db.begin().await?;
+    lock_positions(&mut tx).await?;
+    sqlx::query(
+        r#"
+        UPDATE route_queue AS rq
+        SET queue_position = ordered.position
+        FROM UNNEST($1::uuid[]) WITH ORDINALITY AS ordered(id, position)
+        WHERE rq.id = ordered.id AND rq.status = 'queued'
+        "#,
+    )
+    .bind(&ids)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+#[derive(sqlx::FromRow)]
+struct RouteRow {
+    id: Uuid,
+    start: String,
+    destination: String,
+    added_at: chrono::DateTime<chrono::Utc>,
+    added_by: String,
+    robot_id: Option<String>,
+    dispatched_robot_id: Option<String>,
+    status: String,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<RouteRow> for QueuedRoute {
+    fn from(row: RouteRow) -> Self {
+        QueuedRoute {
+            id: row.id,
+            start: row.start,
+            destination: row.destination,
+            added_at: row.added_at,
+            added_by: row.added_by,
+            robot_id: row.robot_id,
+        }
+    }
+}
+
+/// Load pending routes from `route_queue` into `robot_state`.
+///
+/// `queued` rows become the in-memory queue in `queue_position` order. For
+/// every robot in the fleet, its most recently dispatched route becomes its
+/// `active_route`; any other `dispatched` rows (older ones, or ones sent to a
+/// robot that is no longer configured) can no longer be in progress and are
+/// marked `failed`.
+///
+/// Returns the number of routes restored (queue + active).
+pub async fn rehydrate(db: &PgPool, robot_state: &SharedRobotState) -> Result<usize, sqlx::Error> {
+    let rows = sqlx::query_as::<_, RouteRow>(
+        r#"
+        SELECT id, start, destination, added_at, added_by, robot_id, dispatched_robot_id,
+               status, updated_at
+        FROM route_queue
+        WHERE status IN ('queued', 'dispatched')
+        ORDER BY queue_position ASC, added_at ASC
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut queue = VecDeque::new();
+    let mut dispatched = Vec::new();
+    for row in rows {
+        if row.status == RouteStatus::Dispatched.as_str() {
+            dispatched.push(row);
+        } else {
+            queue.push_back(QueuedRoute::from(row));
+        }
+    }
+
+    // Newest first, so the first row seen for each robot is its active route.
+    dispatched.sort_by_key(|r| std::cmp::Reverse(r.updated_at));
+    let mut restored = queue.len();
+    for row in dispatched {
+        let robot = match row.dispatched_robot_id.as_deref() {
+            Some(id) => robot_state.robot(id).await,
+            None => None,
+        };
+
+        match robot {
+            Some(robot) if robot.active_route.read().await.is_none() => {
+                *robot.active_route.write().await = Some(QueuedRoute::from(row));
+                restored += 1;
+            }
+            _ => {
+                tracing::warn!(
+                    route_id = %row.id,
+                    robot_id = ?row.dispatched_robot_id,
+                    "Superseded dispatched route found during rehydration - marking failed"
+                );
+                set_status(db, row.id, RouteStatus::Failed).await?;
+            }
+        }
+    }
+
+    *robot_state.queue.write().await = queue;
+
+    Ok(restored)
+}
+
+/// Log a failed write-through without interrupting robot control flow.
+pub(crate) fn log_persist_error(route_id: Uuid, operation: &str, error: &sqlx::Error) {
+    tracing::error!(
+        route_id  = %route_id,
+        operation = %operation,
+        error     = %error,
+        "Failed to persist route queue change"
+    );
+}
@@ -0,0 +1,202 @@
+// Server-side validation of route endpoints against the node registry.
+//
+// Used by `POST /routes`, `POST /routes/select` and the admin `NAVIGATE`
+// command so a robot never receives a route it cannot drive.
+
+use crate::robot::state::{RobotHandle, SharedRobotState};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteValidationError {
+    UnknownNode {
+        field: &'static str,
+        node: String,
+        valid_nodes: Vec<String>,
+    },
+    SameStartAndDestination {
+        node: String,
+    },
+    StartMismatch {
+        start: String,
+        expected_start: String,
+    },
+    UnknownRobot {
+        robot_id: String,
+    },
+}
+
+impl RouteValidationError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RouteValidationError::UnknownNode { .. } => "unknown_node",
+            RouteValidationError::SameStartAndDestination { .. } => "same_start_and_destination",
+            RouteValidationError::StartMismatch { .. } => "start_mismatch",
+            RouteValidationError::UnknownRobot { .. } => "unknown_robot",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            RouteValidationError::UnknownNode { field, node, .. } => {
+                format!("Unknown {field} node: {node}")
+            }
+            RouteValidationError::SameStartAndDestination { node } => {
+                format!("Start and destination are both {node}")
+            }
+            RouteValidationError::StartMismatch {
+                start,
+                expected_start,
+            } => format!("Route must start at {expected_start}, not {start}"),
+            RouteValidationError::UnknownRobot { robot_id } => {
+                format!("Unknown robot: {robot_id}")
+            }
+        }
+    }
+}
+
+impl IntoResponse for RouteValidationError {
+    /// 422 in the robot route error shape, with the details needed to
+    /// correct the request.
+    fn into_response(self) -> Response {
+        let mut body = serde_json::json!({
+            "status": "error",
+            "code": self.code(),
+            "message": self.message(),
+        });
+
+        match self {
+            RouteValidationError::UnknownNode {
+                field, valid_nodes, ..
+            } => {
+                body["field"] = serde_json::json!(field);
+                body["valid_nodes"] = serde_json::json!(valid_nodes);
+            }
+            RouteValidationError::StartMismatch { expected_start, .. } => {
+                body["expected_start"] = serde_json::json!(expected_start);
+            }
+            RouteValidationError::SameStartAndDestination { .. }
+            | RouteValidationError::UnknownRobot { .. } => {}
+        }
+
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+    }
+}
+
+/// Check that both endpoints are enabled nodes and differ. When
+/// `expected_start` is given, `start` must also equal it.
+pub async fn validate_route(
+    robot_state: &SharedRobotState,
+    start: &str,
+    destination: &str,
+    expected_start: Option<&str>,
+) -> Result<(), RouteValidationError> {
+    let valid_nodes: Vec<String> = robot_state
+        .nodes
+        .read()
+        .await
+        .iter()
+        .map(|n| n.id.clone())
+        .collect();
+
+    for (field, node) in [("start", start), ("destination", destination)] {
+        if !valid_nodes.iter().any(|id| id == node) {
+            return Err(RouteValidationError::UnknownNode {
+                field,
+                node: node.to_string(),
+                valid_nodes,
+            });
+        }
+    }
+
+    if start == destination {
+        return Err(RouteValidationError::SameStartAndDestination {
+            node: start.to_string(),
+        });
+    }
+
+    if let Some(expected) = expected_start {
+        if expected != start {
+            return Err(RouteValidationError::StartMismatch {
+                start: start.to_string(),
+                expected_start: expected.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolve the robot a route is pinned to. `None` means any robot may drive it.
+pub async fn pinned_robot(
+    robot_state: &SharedRobotState,
+    robot_id: Option<&str>,
+) -> Result<Option<RobotHandle>, RouteValidationError> {
+    let Some(id) = robot_id else {
+        return Ok(None);
+    };
+
+    robot_state
+        .robot(id)
+        .await
+        .map(Some)
+        .ok_or_else(|| RouteValidationError::UnknownRobot {
+            robot_id: id.to_string(),
+        })
+}
+
+/// Node the robot is currently at: `current_position` if it names a known
+/// node, otherwise `last_node`.
+pub async fn robot_position(robot_state: &SharedRobotState, robot: &RobotHandle) -> Option<String> {
+    let current = robot.current_state.read().await.clone()?;
+
+    if robot_state.is_known_node(&current.current_position).await {
+        return Some(current.current_position);
+    }
+
+    match current.last_node {
+        Some(node) if robot_state.is_known_node(&node).await => Some(node),
+        _ => None,
+    }
+}
+
+/// Where a newly queued route has to start so it chains onto the routes
+/// ahead of it: the destination of the last queued route for the same robot,
+/// else of that robot's active route, else its reported position.
+///
+/// Unpinned routes chain onto the last queued route; the robot's own route
+/// and position are only used while the fleet has a single robot, since with
+/// several robots it is not known which one will pick the route up.
+/// `None` if nothing is known.
+pub async fn next_queued_start(
+    robot_state: &SharedRobotState,
+    pinned: Option<&RobotHandle>,
+) -> Option<String> {
+    {
+        let queue = robot_state.queue.read().await;
+        let last = match pinned {
+            Some(robot) => queue
+                .iter()
+                .rev()
+                .find(|r| r.robot_id.as_deref() == Some(robot.id.as_str())),
+            None => queue.back(),
+        };
+        if let Some(last) = last {
+            return Some(last.destination.clone());
+        }
+    }
+
+    let robot = match pinned {
+        Some(robot) => robot.clone(),
+        None => robot_state.resolve_robot(None).await?,
+    };
+
+    if let Some(active) = robot.active_route.read().await.as_ref() {
+        return Some(active.destination.clone());
+    }
+
+    robot_position(robot_state, &robot).await
+}
@@ -1,7 +1,8 @@
-use super::models::{QueuedRoute, RobotCommand, RobotState, RobotStatusUpdate};
+use super::models::{QueuedRoute, RobotCommand, RobotNode, RobotState, RobotStatusUpdate};
+use super::telemetry_store::TELEMETRY_SAMPLE_INTERVAL_SECS;
 use crate::notifications::models::RobotNotification;
 use chrono::{DateTime, Utc};
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
 use uuid::Uuid;
@@ -10,46 +11,76 @@ use uuid::Uuid;
 pub const ROBOT_STALE_TIMEOUT_SECS: i64 = 30;
 /// How often the background cleanup task runs (in seconds)
 pub const CLEANUP_INTERVAL_SECS: u64 = 5;
+/// Robot id used when only the legacy single `ROBOT_API_KEY` is configured
+pub const DEFAULT_ROBOT_ID: &str = "teletable";
+
+/// Fleet-wide state: the shared route queue, the node registry, the
+/// status/notification/session-revocation channels, and one `RobotHandle` per
+/// robot.
 #[derive(Debug, Clone)]
 pub struct SharedRobotState {
+    pub robots: Arc<RwLock<BTreeMap<String, RobotHandle>>>,
+    pub status_sender: broadcast::Sender<RobotStatusUpdate>,
+    pub notification_sender: broadcast::Sender<RobotNotification>,
+    /// Routes waiting for a robot; a route with `robot_id` set only goes to that robot
+    pub queue: Arc<RwLock<VecDeque<QueuedRoute>>>,
+    /// Enabled navigation nodes in display order, cached from the `nodes` table
+    pub nodes: Arc<RwLock<Vec<RobotNode>>>,
+    /// Login sessions revoked at runtime; client sockets opened under them close
+    pub session_revoked_sender: broadcast::Sender<Uuid>,
+}
+
+/// State of a single robot. Cloning is cheap and shares the underlying locks.
+#[derive(Debug, Clone)]
+pub struct RobotHandle {
+    pub id: String,
     pub current_state: Arc<RwLock<Option<RobotState>>>,
     pub last_state_update: Arc<RwLock<Option<DateTime<Utc>>>>,
     pub manual_lock: Arc<RwLock<Option<LockInfo>>>,
     pub command_sender: broadcast::Sender<RobotCommand>,
     pub audio_sender: broadcast::Sender<Vec<u8>>,
     pub audio_streaming: Arc<RwLock<bool>>,
-    pub status_sender: broadcast::Sender<RobotStatusUpdate>,
-    pub notification_sender: broadcast::Sender<RobotNotification>,
     pub robot_url: Arc<RwLock<Option<String>>>,
-    pub queue: Arc<RwLock<VecDeque<QueuedRoute>>>,
     pub active_route: Arc<RwLock<Option<QueuedRoute>>>,
+    /// When the last telemetry sample of this robot was persisted
+    pub last_telemetry_sample: Arc<RwLock<Option<DateTime<Utc>>>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct LockInfo {
     pub holder_id: Uuid,
     pub holder_name: String,
     pub expires_at: chrono::DateTime<chrono::Utc>,
 }
 
-impl SharedRobotState {
-    pub fn new() -> Self {
+impl RobotHandle {
+    pub fn new(id: &str) -> Self {
         let (command_tx, _) = broadcast::channel(100);
         let (audio_tx, _) = broadcast::channel(200);
-        let (status_tx, _) = broadcast::channel(200);
-        let (notification_tx, _) = broadcast::channel(200);
         Self {
+            id: id.to_string(),
             current_state: Arc::new(RwLock::new(None)),
             last_state_update: Arc::new(RwLock::new(None)),
             manual_lock: Arc::new(RwLock::new(None)),
             command_sender: command_tx,
             audio_sender: audio_tx,
             audio_streaming: Arc::new(RwLock::new(false)),
-            status_sender: status_tx,
-            notification_sender: notification_tx,
             robot_url: Arc::new(RwLock::new(None)),
-            queue: Arc::new(RwLock::new(VecDeque::new())),
             active_route: Arc::new(RwLock::new(None)),
+            last_telemetry_sample: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Returns true, and claims the slot, if a telemetry sample taken at `now`
+    /// should be persisted; samples are spaced by `TELEMETRY_SAMPLE_INTERVAL_SECS`.
+    pub async fn take_telemetry_slot(&self, now: DateTime<Utc>) -> bool {
+        let mut last = self.last_telemetry_sample.write().await;
+        match *last {
+            Some(t) if (now - t).num_seconds() < TELEMETRY_SAMPLE_INTERVAL_SECS => false,
+            _ => {
+                *last = Some(now);
+                true
+            }
         }
     }
 
@@ -62,12 +93,34 @@ impl SharedRobotState {
         }
     }
 
+    /// Returns true if the robot last reported `IDLE`
+    pub async fn is_idle(&self) -> bool {
+        self.current_state
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|s| s.drive_mode == "IDLE")
+    }
+
+    /// Returns true if a non-expired manual lock is held
+    pub async fn is_locked(&self) -> bool {
+        self.manual_lock
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|l| l.expires_at > Utc::now())
+    }
+
     /// Clear an expired manual lock. Returns true if a lock was cleared.
     pub async fn clear_expired_lock(&self) -> bool {
         let mut lock = self.manual_lock.write().await;
         if let Some(l) = &*lock {
             if l.expires_at <= Utc::now() {
-                tracing::info!("Clearing expired lock held by {}", l.holder_name);
+                tracing::info!(
+                    robot_id = %self.id,
+                    "Clearing expired lock held by {}",
+                    l.holder_name
+                );
                 *lock = None;
                 return true;
             }
@@ -76,6 +129,83 @@ impl SharedRobotState {
     }
 }
 
+impl SharedRobotState {
+    /// Fleet with the single legacy robot (`DEFAULT_ROBOT_ID`)
+    pub fn new() -> Self {
+        Self::with_robots([DEFAULT_ROBOT_ID])
+    }
+
+    pub fn with_robots<I, S>(ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let (status_tx, _) = broadcast::channel(200);
+        let (notification_tx, _) = broadcast::channel(200);
+        let (session_revoked_tx, _) = broadcast::channel(100);
+        let robots = ids
+            .into_iter()
+            .map(|id| (id.as_ref().to_string(), RobotHandle::new(id.as_ref())))
+            .collect();
+        Self {
+            robots: Arc::new(RwLock::new(robots)),
+            status_sender: status_tx,
+            notification_sender: notification_tx,
+            queue: Arc::new(RwLock::new(VecDeque::new())),
+            nodes: Arc::new(RwLock::new(Vec::new())),
+            session_revoked_sender: session_revoked_tx,
+        }
+    }
+
+    pub async fn robot(&self, id: &str) -> Option<RobotHandle> {
+        self.robots.read().await.get(id).cloned()
+    }
+
+    /// Return the robot with `id`, adding it to the fleet if it is new
+    pub async fn ensure_robot(&self, id: &str) -> RobotHandle {
+        if let Some(robot) = self.robot(id).await {
+            return robot;
+        }
+        self.robots
+            .write()
+            .await
+            .entry(id.to_string())
+            .or_insert_with(|| RobotHandle::new(id))
+            .clone()
+    }
+
+    /// All robots, ordered by id
+    pub async fn all_robots(&self) -> Vec<RobotHandle> {
+        self.robots.read().await.values().cloned().collect()
+    }
+
+    /// Resolve an optional `robot_id` request parameter: an explicit id must
+    /// exist, and omitting it is only allowed while the fleet has one robot.
+    pub async fn resolve_robot(&self, id: Option<&str>) -> Option<RobotHandle> {
+        let robots = self.robots.read().await;
+        match id {
+            Some(id) => robots.get(id).cloned(),
+            None if robots.len() == 1 => robots.values().next().cloned(),
+            None => None,
+        }
+    }
+
+    /// Returns true if `id` is an enabled node
+    pub async fn is_known_node(&self, id: &str) -> bool {
+        self.nodes.read().await.iter().any(|n| n.id == id)
+    }
+
+    /// Look up the enabled node tagged with the given RFID UUID
+    pub async fn node_for_rfid(&self, rfid_uuid: &str) -> Option<RobotNode> {
+        self.nodes
+            .read()
+            .await
+            .iter()
+            .find(|n| n.rfid_uuid.as_deref() == Some(rfid_uuid))
+            .cloned()
+    }
+}
+
 impl Default for SharedRobotState {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,110 @@
+use crate::auth::permissions::{RequirePermission, RobotView};
+use crate::robot::client_routes::resolve_robot;
+use crate::robot::models::{TelemetryQuery, TelemetryResponse};
+use crate::robot::telemetry_store::{self, TELEMETRY_METRICS, TELEMETRY_SAMPLE_INTERVAL_SECS};
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Range returned when `from` is omitted
+const DEFAULT_RANGE_HOURS: i64 = 24;
+/// Points per series the default bucket width aims for
+const DEFAULT_POINTS: i64 = 300;
+/// Upper bound on points per series, to keep responses plottable
+const MAX_POINTS: i64 = 5000;
+
+fn bad_request(message: String) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({
+            "status": "error",
+            "message": message
+        })),
+    )
+}
+
+pub async fn get_robot_telemetry(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<RobotView>,
+    Query(query): Query<TelemetryQuery>,
+) -> Result<Json<TelemetryResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let robot = resolve_robot(&state, query.robot_id.as_deref()).await?;
+
+    let to = query.to.unwrap_or_else(chrono::Utc::now);
+    let from = query
+        .from
+        .unwrap_or(to - chrono::Duration::hours(DEFAULT_RANGE_HOURS));
+    if from >= to {
+        return Err(bad_request("from must be before to".to_string()));
+    }
+    let range_secs = (to - from).num_seconds().max(1);
+
+    let metrics: Vec<String> = match query.fields.as_deref() {
+        Some(fields) => fields
+            .split(',')
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+            .collect(),
+        None => TELEMETRY_METRICS.iter().map(|m| m.to_string()).collect(),
+    };
+    if metrics.is_empty() {
+        return Err(bad_request("fields must not be empty".to_string()));
+    }
+    if let Some(unknown) = metrics
+        .iter()
+        .find(|m| !TELEMETRY_METRICS.contains(&m.as_str()))
+    {
+        return Err(bad_request(format!(
+            "Unknown field: {unknown}. Expected one of: {}",
+            TELEMETRY_METRICS.join(", ")
+        )));
+    }
+
+    let bucket_seconds = match query.bucket {
+        Some(bucket) if bucket < 1 => {
+            return Err(bad_request("bucket must be at least 1 second".to_string()));
+        }
+        Some(bucket) => bucket,
+        None => {
+            ((range_secs + DEFAULT_POINTS - 1) / DEFAULT_POINTS).max(TELEMETRY_SAMPLE_INTERVAL_SECS)
+        }
+    };
+    if range_secs / bucket_seconds > MAX_POINTS {
+        return Err(bad_request(format!(
+            "Too many buckets: at most {MAX_POINTS} per series, use a larger bucket"
+        )));
+    }
+
+    let rows = telemetry_store::series(&state.db, &robot.id, from, to, bucket_seconds, &metrics)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, robot_id = %robot.id, "DB error fetching robot telemetry");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to fetch robot telemetry"
+                })),
+            )
+        })?;
+
+    let mut series: BTreeMap<String, Vec<_>> =
+        metrics.into_iter().map(|m| (m, Vec::new())).collect();
+    for (metric, point) in rows {
+        series.entry(metric).or_default().push(point);
+    }
+
+    Ok(Json(TelemetryResponse {
+        robot_id: robot.id,
+        from,
+        to,
+        bucket_seconds,
+        series,
+    }))
+}
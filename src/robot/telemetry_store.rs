@@ -0,0 +1,226 @@
+// Persistence of robot telemetry as a time series.
+//
+// `update_robot_state` records at most one raw sample per robot every
+// `TELEMETRY_SAMPLE_INTERVAL_SECS`. The housekeeping task periodically folds
+// raw samples older than `TELEMETRY_RAW_RETENTION_HOURS` into per-minute
+// rollups (one row per metric) and drops rollups older than
+// `TELEMETRY_ROLLUP_RETENTION_DAYS`. Queries read both tables, so a series
+// spanning the retention boundary is continuous, just coarser in its older part.
+
+use crate::robot::models::{RobotState, TelemetryPoint};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+/// Minimum spacing between stored raw samples of one robot
+pub const TELEMETRY_SAMPLE_INTERVAL_SECS: i64 = 5;
+/// Raw samples older than this are rolled up into per-minute aggregates
+pub const TELEMETRY_RAW_RETENTION_HOURS: i64 = 48;
+/// Per-minute aggregates older than this are deleted
+pub const TELEMETRY_ROLLUP_RETENTION_DAYS: i64 = 90;
+/// How often the housekeeping task compacts telemetry (in seconds)
+pub const TELEMETRY_COMPACTION_INTERVAL_SECS: u64 = 600;
+
+/// Metrics that can be requested from `GET /robot/telemetry`
+pub const TELEMETRY_METRICS: &[&str] = &[
+    "battery_level",
+    "voltage_v",
+    "current_a",
+    "power_w",
+    "lux",
+    "gyro_x_dps",
+    "gyro_y_dps",
+    "gyro_z_dps",
+    "ir_front",
+    "ir_left",
+    "ir_right",
+];
+
+/// Unpivots a `robot_telemetry` row `t` into `m(metric, value)` pairs, one per
+/// entry of `TELEMETRY_METRICS`. Infrared flags become 0/1, so their average
+/// is the fraction of samples in which the sensor was triggered.
+const METRIC_VALUES: &str = r#"
+    (VALUES
+        ('battery_level', t.battery_level::float8),
+        ('voltage_v', t.voltage_v::float8),
+        ('current_a', t.current_a::float8),
+        ('power_w', t.power_w::float8),
+        ('lux', t.lux::float8),
+        ('gyro_x_dps', t.gyro_x_dps::float8),
+        ('gyro_y_dps', t.gyro_y_dps::float8),
+        ('gyro_z_dps', t.gyro_z_dps::float8),
+        ('ir_front', t.ir_front::int::float8),
+        ('ir_left', t.ir_left::int::float8),
+        ('ir_right', t.ir_right::int::float8)
+    ) AS m(metric, value)
+"#;
+
+/// Store one raw sample. A second sample with the same timestamp is ignored.
+pub async fn record(
+    db: &PgPool,
+    robot_id: &str,
+    recorded_at: DateTime<Utc>,
+    sample: &RobotState,
+) -> Result<(), sqlx::Error> {
+    let gyro = sample.gyroscope.clone().unwrap_or_default();
+    let infrared = sample.infrared.clone().unwrap_or_default();
+
+    sqlx::query(
+        r#"
+        INSERT INTO robot_telemetry (
+            robot_id, recorded_at, drive_mode, battery_level,
+            voltage_v, current_a, power_w, lux,
+            gyro_x_dps, gyro_y_dps, gyro_z_dps,
+            ir_front, ir_left, ir_right
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+        ON CONFLICT (robot_id, recorded_at) DO NOTHING
+        "#,
+    )
+    .bind(robot_id)
+    .bind(recorded_at)
+    .bind(&sample.drive_mode)
+    .bind(i16::from(sample.battery_level))
+    .bind(sample.voltage_v)
+    .bind(sample.current_a)
+    .bind(sample.power_w)
+    .bind(sample.lux)
+    .bind(gyro.x_dps)
+    .bind(gyro.y_dps)
+    .bind(gyro.z_dps)
+    .bind(infrared.front)
+    .bind(infrared.left)
+    .bind(infrared.right)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Roll raw samples that left the raw retention window up into per-minute
+/// aggregates, then delete expired aggregates. Returns the number of raw
+/// samples rolled up and the number of aggregates deleted.
+pub async fn compact(db: &PgPool, now: DateTime<Utc>) -> Result<(u64, u64), sqlx::Error> {
+    let raw_cutoff = now - Duration::hours(TELEMETRY_RAW_RETENTION_HOURS);
+    let rollup_cutoff = now - Duration::days(TELEMETRY_ROLLUP_RETENTION_DAYS);
+
+    let mut tx = db.begin().await?;
+
+    // Merging on conflict keeps the result correct if a minute was split
+    // across two compaction runs.
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO robot_telemetry_rollups (
+            robot_id, metric, bucket_start, samples, value_sum, value_min, value_max
+        )
+        SELECT
+            t.robot_id,
+            m.metric,
+            date_trunc('minute', t.recorded_at),
+            COUNT(*),
+            SUM(m.value),
+            MIN(m.value),
+            MAX(m.value)
+        FROM robot_telemetry t
+        CROSS JOIN LATERAL {METRIC_VALUES}
+        WHERE t.recorded_at < $1
+          AND m.value IS NOT NULL
+        GROUP BY 1, 2, 3
+        ON CONFLICT (robot_id, metric, bucket_start) DO UPDATE SET
+            samples = robot_telemetry_rollups.samples + EXCLUDED.samples,
+            value_sum = robot_telemetry_rollups.value_sum + EXCLUDED.value_sum,
+            value_min = LEAST(robot_telemetry_rollups.value_min, EXCLUDED.value_min),
+            value_max = GREATEST(robot_telemetry_rollups.value_max, EXCLUDED.value_max)
+        "#
+    ))
+    .bind(raw_cutoff)
+    .execute(&mut *tx)
+    .await?;
+
+    let rolled_up = sqlx::query("DELETE FROM robot_telemetry WHERE recorded_at < $1")
+        .bind(raw_cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    let purged = sqlx::query("DELETE FROM robot_telemetry_rollups WHERE bucket_start < $1")
+        .bind(rollup_cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+
+    Ok((rolled_up, purged))
+}
+
+/// Aggregate `metrics` of one robot into buckets of `bucket_secs`, aligned to
+/// the Unix epoch. Buckets without samples are omitted.
+pub async fn series(
+    db: &PgPool,
+    robot_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    bucket_secs: i64,
+    metrics: &[String],
+) -> Result<Vec<(String, TelemetryPoint)>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, DateTime<Utc>, i64, f64, f64, f64)>(&format!(
+        r#"
+        WITH points AS (
+            SELECT
+                t.recorded_at AS at,
+                m.metric,
+                1::bigint AS samples,
+                m.value AS value_sum,
+                m.value AS value_min,
+                m.value AS value_max
+            FROM robot_telemetry t
+            CROSS JOIN LATERAL {METRIC_VALUES}
+            WHERE t.robot_id = $1
+              AND t.recorded_at >= $2
+              AND t.recorded_at < $3
+              AND m.metric = ANY($5)
+              AND m.value IS NOT NULL
+            UNION ALL
+            SELECT bucket_start, metric, samples::bigint, value_sum, value_min, value_max
+            FROM robot_telemetry_rollups
+            WHERE robot_id = $1
+              AND bucket_start >= $2
+              AND bucket_start < $3
+              AND metric = ANY($5)
+        )
+        SELECT
+            metric,
+            date_bin(make_interval(secs => $4), at, TIMESTAMPTZ 'epoch') AS bucket,
+            SUM(samples)::bigint,
+            SUM(value_sum) / SUM(samples)::float8,
+            MIN(value_min),
+            MAX(value_max)
+        FROM points
+        GROUP BY metric, bucket
+        ORDER BY metric, bucket
+        "#
+    ))
+    .bind(robot_id)
+    .bind(from)
+    .bind(to)
+    .bind(bucket_secs as f64)
+    .bind(metrics)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(metric, bucket, samples, avg, min, max)| {
+            (
+                metric,
+                TelemetryPoint {
+                    bucket,
+                    samples,
+                    avg,
+                    min,
+                    max,
+                },
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,359 @@
+use axum::http::StatusCode;
+use backend::auth::models::{LoginResponse, RegisterRequest};
+use backend::Config;
+use uuid::Uuid;
+
+mod common;
+
+use common::{post_json, request, send};
+
+async fn register(app: &common::TestApp) -> String {
+    let email = format!("recovery-{}@example.com", Uuid::new_v4());
+    let register = RegisterRequest {
+        name: "Recovery User".into(),
+        email: email.clone(),
+        password: "password123".into(),
+        fingerprint_data: None,
+    };
+    let (status, _) = send(
+        app,
+        post_json("/register", serde_json::to_value(&register).unwrap()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    email
+}
+
+async fn login(
+    app: &common::TestApp,
+    email: &str,
+    password: &str,
+) -> (StatusCode, serde_json::Value) {
+    send(
+        app,
+        post_json(
+            "/login",
+            serde_json::json!({ "email": email, "password": password }),
+        ),
+    )
+    .await
+}
+
+/// The token from the link in the newest mail to `to`
+fn mailed_token(app: &common::TestApp, to: &str, path: &str) -> String {
+    let body = app.last_mail_to(to).expect("mail was sent");
+    let link = format!("http://localhost/{path}?token=");
+    let start = body.find(&link).expect("mail contains the link") + link.len();
+    body[start..].split_whitespace().next().unwrap().to_string()
+}
+
+fn mail_count(app: &common::TestApp) -> usize {
+    std::fs::read_dir(&app.mail_dir)
+        .map(|entries| entries.count())
+        .unwrap_or(0)
+}
+
+/// Wait for the background send to leave `count` mails, then a little longer
+/// so an extra mail would show up too
+async fn settle_mails(app: &common::TestApp, count: usize) {
+    for _ in 0..50 {
+        if mail_count(app) >= count {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+}
+
+#[tokio::test]
+async fn test_password_reset_flow() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_password_reset_flow: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let (status, body) = login(&app, &email, "password123").await;
+    assert_eq!(status, StatusCode::OK);
+    let session: LoginResponse = serde_json::from_value(body).unwrap();
+
+    let sent_before = mail_count(&app);
+    let (status, unknown) = send(
+        &app,
+        post_json(
+            "/password/forgot",
+            serde_json::json!({ "email": "nobody@example.com" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    settle_mails(&app, sent_before + 1).await;
+    assert_eq!(mail_count(&app), sent_before);
+
+    // The address is matched however its case was typed
+    let (status, known) = send(
+        &app,
+        post_json(
+            "/password/forgot",
+            serde_json::json!({ "email": email.to_uppercase() }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(known, unknown, "Responses do not reveal registered emails");
+    settle_mails(&app, sent_before + 1).await;
+    assert_eq!(mail_count(&app), sent_before + 1);
+    let token = mailed_token(&app, &email, "reset-password");
+    assert!(token.starts_with("pr_"));
+
+    // A second request right away is accepted but sends nothing.
+    let (status, _) = send(
+        &app,
+        post_json("/password/forgot", serde_json::json!({ "email": email })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    settle_mails(&app, sent_before + 2).await;
+    assert_eq!(mail_count(&app), sent_before + 1);
+
+    let (status, _) = send(
+        &app,
+        post_json(
+            "/password/reset",
+            serde_json::json!({ "token": token, "password": " " }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(
+        &app,
+        post_json(
+            "/password/reset",
+            serde_json::json!({ "token": token, "password": "brand-new-password" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(
+        &app,
+        post_json(
+            "/password/reset",
+            serde_json::json!({ "token": token, "password": "another-password" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "Tokens are single-use");
+
+    let (status, _) = send(&app, request("GET", "/me", &session.token, None)).await;
+    assert_eq!(
+        status,
+        StatusCode::UNAUTHORIZED,
+        "Existing sessions are revoked"
+    );
+
+    let (status, _) = login(&app, &email, "password123").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = login(&app, &email, "brand-new-password").await;
+    assert_eq!(status, StatusCode::OK);
+    let session: LoginResponse = serde_json::from_value(body).unwrap();
+    let (_, me) = send(&app, request("GET", "/me", &session.token, None)).await;
+    assert_eq!(
+        me["email_verified"], true,
+        "Following the reset link verifies the address"
+    );
+}
+
+#[tokio::test]
+async fn test_expired_reset_token_is_rejected() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_expired_reset_token_is_rejected: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let sent_before = mail_count(&app);
+    let (status, _) = send(
+        &app,
+        post_json("/password/forgot", serde_json::json!({ "email": email })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    settle_mails(&app, sent_before + 1).await;
+    let token = mailed_token(&app, &email, "reset-password");
+
+    let stored: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM account_tokens WHERE token_hash = $1")
+            .bind(&token)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(stored, 0, "Tokens are only stored hashed");
+
+    sqlx::query(
+        "UPDATE account_tokens SET expires_at = NOW() - INTERVAL '1 minute' WHERE token_hash = $1",
+    )
+    .bind(backend::auth::sessions::hash_token(&token))
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let (status, body) = send(
+        &app,
+        post_json(
+            "/password/reset",
+            serde_json::json!({ "token": token, "password": "brand-new-password" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Invalid or expired token");
+
+    let (status, _) = login(&app, &email, "password123").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_email_verification_flow() {
+    let app = match common::setup_test_app_with(|config| {
+        config.require_email_verification = true;
+    })
+    .await
+    {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_email_verification_flow: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let token = mailed_token(&app, &email, "verify-email");
+    assert!(token.starts_with("ev_"));
+
+    let (status, body) = login(&app, &email, "password123").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], "Email address not verified");
+
+    // Within the cooldown of the registration mail nothing new is sent
+    let sent_before = mail_count(&app);
+    let (status, _) = send(
+        &app,
+        post_json(
+            "/email/verify/resend",
+            serde_json::json!({ "email": email }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    settle_mails(&app, sent_before + 1).await;
+    assert_eq!(mail_count(&app), sent_before);
+
+    // Once it has passed, a new link replaces the old one
+    sqlx::query(
+        "UPDATE account_tokens SET created_at = NOW() - INTERVAL '5 minutes' WHERE email = $1",
+    )
+    .bind(&email)
+    .execute(&app.db)
+    .await
+    .unwrap();
+    let (status, _) = send(
+        &app,
+        post_json(
+            "/email/verify/resend",
+            serde_json::json!({ "email": email.to_uppercase() }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    settle_mails(&app, sent_before + 1).await;
+    assert_eq!(mail_count(&app), sent_before + 1);
+    let (status, _) = send(
+        &app,
+        post_json("/email/verify", serde_json::json!({ "token": token })),
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::BAD_REQUEST,
+        "The old link is superseded"
+    );
+    let token = mailed_token(&app, &email, "verify-email");
+
+    let (status, _) = send(
+        &app,
+        post_json("/email/verify", serde_json::json!({ "token": "ev_bogus" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, user) = send(
+        &app,
+        post_json("/email/verify", serde_json::json!({ "token": token })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["email"], email.as_str());
+    assert_eq!(user["email_verified"], true);
+
+    let (status, _) = send(
+        &app,
+        post_json("/email/verify", serde_json::json!({ "token": token })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "Tokens are single-use");
+
+    let (status, _) = login(&app, &email, "password123").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+// The SMTP transport's connection pool needs a runtime.
+#[tokio::test]
+async fn test_mail_transport_from_config() {
+    let mut config = Config {
+        database_url: String::new(),
+        redis_url: String::new(),
+        jwt_secret: "test_secret".to_string(),
+        access_token_ttl_minutes: 15,
+        refresh_token_ttl_days: 30,
+        server_address: "127.0.0.1:0".to_string(),
+        robot_api_key: None,
+        robot_api_keys: Vec::new(),
+        allow_default_robot_key: false,
+        enforce_route_chaining: false,
+        app_base_url: "http://localhost".to_string(),
+        mail_transport: "file".to_string(),
+        smtp_url: None,
+        mail_from: "TeleTable <no-reply@localhost>".to_string(),
+        mail_dir: "./mail".to_string(),
+        require_email_verification: false,
+        require_admin_2fa: false,
+        retain_diary_on_account_deletion: false,
+        rate_limit_routes: Vec::new(),
+        rate_limit_roles: Vec::new(),
+        trusted_proxies: Vec::new(),
+    };
+    assert!(backend::mail::from_config(&config).is_ok());
+
+    config.mail_transport = "smtp".to_string();
+    assert!(
+        backend::mail::from_config(&config).is_err(),
+        "SMTP needs SMTP_URL"
+    );
+
+    config.smtp_url = Some("smtp://localhost:2525".to_string());
+    assert!(backend::mail::from_config(&config).is_ok());
+
+    config.mail_from = "not an address".to_string();
+    assert!(backend::mail::from_config(&config).is_err());
+
+    config.mail_transport = "carrier-pigeon".to_string();
+    assert!(backend::mail::from_config(&config).is_err());
+}
@@ -0,0 +1,308 @@
+use axum::{
+    body::Body,
+    http::{HeaderValue, Request, StatusCode},
+};
+use uuid::Uuid;
+
+mod common;
+
+use common::send;
+
+fn token(user_id: &str, role: &str) -> String {
+    common::token(user_id, &format!("{role} {user_id}"), role)
+}
+
+/// A request from the client IP the tests look for in the log
+fn request(method: &str, uri: &str, token: &str, body: Option<serde_json::Value>) -> Request<Body> {
+    common::via_proxy(common::request(method, uri, token, body), "203.0.113.7")
+}
+
+async fn audit(app: &common::TestApp, query: &str) -> Vec<serde_json::Value> {
+    let admin = token("admin_id", "Admin");
+    let (status, body) = send(
+        app,
+        request("GET", &format!("/audit?{query}"), &admin, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    body.as_array().unwrap().clone()
+}
+
+fn actions(events: &[serde_json::Value]) -> Vec<&str> {
+    events
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_user_role_changes_and_deletions_are_audited() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_user_role_changes_and_deletions_are_audited: {e}");
+            return;
+        }
+    };
+
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, name, email, password_hash, role) VALUES ($1, 'Audited', $2, 'x', 'Viewer')",
+    )
+    .bind(user_id)
+    .bind(format!("audited-{user_id}@example.com"))
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let admin_id = Uuid::new_v4().to_string();
+    let admin = token(&admin_id, "Admin");
+    let (status, _) = send(
+        &app,
+        request(
+            "POST",
+            "/user",
+            &admin,
+            Some(serde_json::json!({ "id": user_id, "name": "Renamed" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        audit(&app, &format!("target_id={user_id}"))
+            .await
+            .is_empty(),
+        "Only role changes are audited"
+    );
+
+    let (status, _) = send(
+        &app,
+        request(
+            "POST",
+            "/user",
+            &admin,
+            Some(serde_json::json!({ "id": user_id, "role": "Operator" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            "/user",
+            &admin,
+            Some(serde_json::json!({ "id": user_id })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let events = audit(&app, &format!("target_type=user&target_id={user_id}")).await;
+    assert_eq!(actions(&events), ["user.delete", "user.role_change"]);
+    let (deletion, role_change) = (&events[0], &events[1]);
+    assert_eq!(role_change["before"]["role"], "Viewer");
+    assert_eq!(role_change["after"]["role"], "Operator");
+    assert_eq!(role_change["actor_id"], admin_id.as_str());
+    assert_eq!(role_change["client_ip"], "203.0.113.7");
+    assert_eq!(deletion["before"]["name"], "Renamed");
+    assert_eq!(deletion["before"]["role"], "Operator");
+
+    // Filters and pagination
+    let query = format!("actor_id={admin_id}");
+    assert_eq!(
+        actions(&audit(&app, &format!("{query}&action=user")).await).len(),
+        2
+    );
+    assert_eq!(
+        actions(&audit(&app, &format!("{query}&action=user.delete")).await),
+        ["user.delete"]
+    );
+    assert!(audit(&app, &format!("{query}&action=use")).await.is_empty());
+    assert_eq!(
+        actions(&audit(&app, &format!("{query}&limit=1&offset=1")).await),
+        ["user.role_change"]
+    );
+
+    let viewer = token(&Uuid::new_v4().to_string(), "Viewer");
+    let (status, _) = send(&app, request("GET", "/audit", &viewer, None)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The table is append-only.
+    let update =
+        sqlx::query("UPDATE audit_events SET actor_name = 'someone else' WHERE actor_id = $1")
+            .bind(Uuid::parse_str(&admin_id).unwrap())
+            .execute(&app.db)
+            .await;
+    assert!(update.is_err());
+    let delete = sqlx::query("DELETE FROM audit_events WHERE actor_id = $1")
+        .bind(Uuid::parse_str(&admin_id).unwrap())
+        .execute(&app.db)
+        .await;
+    assert!(delete.is_err());
+}
+
+#[tokio::test]
+async fn test_lock_takeover_and_queue_deletion_are_audited() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_lock_takeover_and_queue_deletion_are_audited: {e}");
+            return;
+        }
+    };
+    let robot = app.robot().await;
+    *robot.last_state_update.write().await = Some(chrono::Utc::now());
+
+    let operator_id = Uuid::new_v4().to_string();
+    let admin_id = Uuid::new_v4().to_string();
+    let operator = token(&operator_id, "Operator");
+    let admin = token(&admin_id, "Admin");
+
+    let (_, body) = send(&app, request("POST", "/drive/lock", &operator, None)).await;
+    assert_eq!(body["status"], "success");
+    let (_, body) = send(&app, request("POST", "/drive/lock", &admin, None)).await;
+    assert_eq!(body["status"], "success");
+    let (_, body) = send(&app, request("DELETE", "/drive/lock", &admin, None)).await;
+    assert_eq!(body["status"], "success");
+
+    let events = audit(&app, &format!("actor_id={operator_id}")).await;
+    assert_eq!(actions(&events), ["lock.acquire"]);
+    assert_eq!(events[0]["robot_id"], "teletable");
+
+    let events = audit(&app, &format!("actor_id={admin_id}&action=lock")).await;
+    let mut seen = actions(&events);
+    seen.sort();
+    assert_eq!(seen, ["lock.acquire", "lock.release", "lock.revoke"]);
+    let revoke = events
+        .iter()
+        .find(|e| e["action"] == "lock.revoke")
+        .unwrap();
+    assert_eq!(revoke["target_id"], operator_id.as_str());
+    assert_eq!(revoke["before"]["holder_id"], operator_id.as_str());
+    assert_eq!(revoke["after"]["holder_id"], admin_id.as_str());
+
+    *robot.last_state_update.write().await = None;
+    let (status, route) = send(
+        &app,
+        request(
+            "POST",
+            "/routes",
+            &admin,
+            Some(serde_json::json!({ "start": "home", "destination": "kitchen" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let route_id = route["id"].as_str().unwrap();
+    let (status, _) = send(
+        &app,
+        request("DELETE", &format!("/routes/{route_id}"), &admin, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let events = audit(&app, &format!("action=route.delete&target_id={route_id}")).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["before"]["destination"], "kitchen");
+    assert_eq!(events[0]["actor_role"], "Admin");
+}
+
+/// Client IP recorded for a role change sent from `TEST_PROXY` with `headers`
+async fn audited_ip(app: &common::TestApp, headers: &[(&'static str, &'static str)]) -> String {
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, name, email, password_hash, role) VALUES ($1, 'Forwarded', $2, 'x', 'Viewer')",
+    )
+    .bind(user_id)
+    .bind(format!("forwarded-{user_id}@example.com"))
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let admin = token(&Uuid::new_v4().to_string(), "Admin");
+    let mut role_change = common::request(
+        "POST",
+        "/user",
+        &admin,
+        Some(serde_json::json!({ "id": user_id, "role": "Operator" })),
+    );
+    role_change
+        .extensions_mut()
+        .insert(axum::extract::ConnectInfo(common::TEST_PROXY));
+    for (name, value) in headers {
+        role_change
+            .headers_mut()
+            .insert(*name, HeaderValue::from_static(value));
+    }
+    let (status, _) = send(app, role_change).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let events = audit(app, &format!("target_id={user_id}")).await;
+    events[0]["client_ip"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_forwarded_client_ip_is_only_trusted_from_proxies() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_forwarded_client_ip_is_only_trusted_from_proxies: {e}");
+            return;
+        }
+    };
+
+    // Behind a trusted proxy: the last hop it did not add itself
+    let forwarded = [("X-Forwarded-For", "6.6.6.6, 198.51.100.9, 127.0.0.1")];
+    assert_eq!(audited_ip(&app, &forwarded).await, "198.51.100.9");
+    let real_ip = [("X-Real-IP", "203.0.113.7")];
+    assert_eq!(audited_ip(&app, &real_ip).await, "203.0.113.7");
+
+    let app = common::setup_test_app_with(|config| config.trusted_proxies.clear())
+        .await
+        .unwrap();
+    let peer = common::TEST_PROXY.ip().to_string();
+    assert_eq!(audited_ip(&app, &forwarded).await, peer);
+    assert_eq!(audited_ip(&app, &real_ip).await, peer);
+}
+
+#[tokio::test]
+async fn test_robot_key_issue_and_revoke_are_audited() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_robot_key_issue_and_revoke_are_audited: {e}");
+            return;
+        }
+    };
+
+    let admin = token(&Uuid::new_v4().to_string(), "Admin");
+    let robot_id = format!("robot-{}", Uuid::new_v4().simple());
+    let keys_uri = format!("/robots/{robot_id}/keys");
+    let label = serde_json::json!({ "label": "audited" });
+    let (status, issued) = send(&app, request("POST", &keys_uri, &admin, Some(label))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let key_id = issued["id"].as_str().unwrap();
+
+    let (status, _) = send(
+        &app,
+        request("DELETE", &format!("{keys_uri}/{key_id}"), &admin, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let events = audit(&app, &format!("robot_id={robot_id}")).await;
+    assert_eq!(actions(&events), ["robot.key_revoke", "robot.key_issue"]);
+    for event in &events {
+        assert_eq!(event["target_type"], "robot_key");
+        assert_eq!(event["target_id"], key_id);
+        assert_eq!(event["client_ip"], "203.0.113.7");
+    }
+    let (revoke, issue) = (&events[0], &events[1]);
+    assert_eq!(issue["after"]["key_prefix"], issued["key_prefix"]);
+    assert!(
+        issue["after"].get("api_key").is_none(),
+        "The secret is not logged"
+    );
+    assert!(!revoke["after"]["revoked_at"].is_null());
+}
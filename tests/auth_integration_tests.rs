@@ -122,3 +122,54 @@ async fn test_access_protected_route_without_token() {
 
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
+
+#[tokio::test]
+async fn test_emails_are_matched_case_insensitively() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_emails_are_matched_case_insensitively: {e}");
+            return;
+        }
+    };
+
+    let (status, user) = common::send(
+        &app,
+        common::post_json(
+            "/register",
+            serde_json::json!({
+                "name": "Mixed Case",
+                "email": "  Mixed.Case@Example.com ",
+                "password": "password123"
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(user["email"], "mixed.case@example.com");
+
+    let (status, body) = common::send(
+        &app,
+        common::post_json(
+            "/register",
+            serde_json::json!({
+                "name": "Shouting",
+                "email": "MIXED.CASE@EXAMPLE.COM",
+                "password": "password123"
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "User with this email already exists");
+
+    let (status, _) = common::send(
+        &app,
+        common::post_json(
+            "/login",
+            serde_json::json!({ "email": "MiXeD.cAsE@example.COM", "password": "password123" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,399 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::auth::models::{LoginResponse, RegisterRequest};
+use tokio::net::TcpListener;
+use tokio_tungstenite::connect_async;
+use uuid::Uuid;
+
+mod common;
+
+use common::{post_json, request, send};
+
+/// Register a fresh user and log in. Returns the user id, email and tokens.
+async fn register_and_login(app: &common::TestApp) -> (Uuid, String, LoginResponse) {
+    let email = format!("session-{}@example.com", Uuid::new_v4());
+    let register = RegisterRequest {
+        name: "Session User".into(),
+        email: email.clone(),
+        password: "password123".into(),
+        fingerprint_data: None,
+    };
+    let (status, user) = send(
+        app,
+        post_json("/register", serde_json::to_value(&register).unwrap()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let login = login(app, &email, "password123").await;
+    let user_id = Uuid::parse_str(user["id"].as_str().unwrap()).unwrap();
+    (user_id, email, login)
+}
+
+async fn login(app: &common::TestApp, email: &str, password: &str) -> LoginResponse {
+    let (status, body) = send(
+        app,
+        post_json(
+            "/login",
+            serde_json::json!({ "email": email, "password": password }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    serde_json::from_value(body).unwrap()
+}
+
+async fn refresh(app: &common::TestApp, refresh_token: &str) -> (StatusCode, serde_json::Value) {
+    send(
+        app,
+        post_json(
+            "/token/refresh",
+            serde_json::json!({ "refresh_token": refresh_token }),
+        ),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_refresh_token_rotation_and_reuse() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_refresh_token_rotation_and_reuse: {e}");
+            return;
+        }
+    };
+
+    let (_, _, first) = register_and_login(&app).await;
+    assert_eq!(first.expires_in, 15 * 60);
+    let (status, _) = send(&app, request("GET", "/me", &first.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = refresh(&app, &first.refresh_token).await;
+    assert_eq!(status, StatusCode::OK);
+    let second: LoginResponse = serde_json::from_value(body).unwrap();
+    assert_ne!(second.refresh_token, first.refresh_token);
+    let (status, _) = send(&app, request("GET", "/me", &second.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let stored: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM refresh_tokens WHERE token_hash = $1 OR token_hash = $2",
+    )
+    .bind(&first.refresh_token)
+    .bind(&second.refresh_token)
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(stored, 0, "Refresh tokens are only stored hashed");
+
+    // Replaying the rotated-out token revokes the whole session.
+    let (status, _) = refresh(&app, &first.refresh_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = send(&app, request("GET", "/me", &second.token, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "Session has been revoked");
+    let (status, _) = refresh(&app, &second.refresh_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = refresh(&app, "rt_unknown").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_logout_revokes_session() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_logout_revokes_session: {e}");
+            return;
+        }
+    };
+
+    let (user_id, email, session) = register_and_login(&app).await;
+    let other = login(&app, &email, "password123").await;
+
+    let (status, _) = send(&app, request("POST", "/logout", &session.token, None)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, request("GET", "/me", &session.token, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = refresh(&app, &session.refresh_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(&app, request("GET", "/me", &other.token, None)).await;
+    assert_eq!(status, StatusCode::OK, "Other sessions stay signed in");
+
+    let reason: Option<String> = sqlx::query_scalar(
+        "SELECT revoked_reason FROM sessions WHERE user_id = $1 AND revoked_at IS NOT NULL",
+    )
+    .bind(user_id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(reason.as_deref(), Some("logout"));
+
+    // The WebSocket token check consults the same revocation list.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = app.router.clone();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let err = connect_async(format!(
+        "ws://{addr}/ws/robot/events?token={}",
+        session.token
+    ))
+    .await
+    .expect_err("Revoked token must not open a socket");
+    match err {
+        tokio_tungstenite::tungstenite::Error::Http(response) => {
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED)
+        }
+        other => panic!("Unexpected error: {other}"),
+    }
+    assert!(
+        connect_async(format!("ws://{addr}/ws/robot/events?token={}", other.token))
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_password_change_revokes_all_sessions() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_password_change_revokes_all_sessions: {e}");
+            return;
+        }
+    };
+
+    let (user_id, email, session) = register_and_login(&app).await;
+
+    let admin_token = common::token("admin_id", "Admin User", "Admin");
+    let (status, _) = send(
+        &app,
+        Request::builder()
+            .uri("/user")
+            .method("POST")
+            .header("Authorization", format!("Bearer {admin_token}"))
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::json!({ "id": user_id, "password": "new-password" }).to_string(),
+            ))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&app, request("GET", "/me", &session.token, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = refresh(&app, &session.refresh_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let fresh = login(&app, &email, "new-password").await;
+    let (status, _) = send(&app, request("GET", "/me", &fresh.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Read from `socket` until the server closes it; returns the close code.
+async fn close_code<S>(socket: &mut S) -> Option<u16>
+where
+    S: futures::Stream<
+            Item = Result<
+                tokio_tungstenite::tungstenite::Message,
+                tokio_tungstenite::tungstenite::Error,
+            >,
+        > + Unpin,
+{
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let deadline = std::time::Duration::from_secs(5);
+    loop {
+        match tokio::time::timeout(deadline, socket.next()).await {
+            Ok(Some(Ok(Message::Close(frame)))) => return frame.map(|f| u16::from(f.code)),
+            Ok(Some(Ok(_))) => continue,
+            Ok(_) => return None,
+            Err(_) => panic!("Socket was not closed"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_list_and_terminate_own_sessions() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_list_and_terminate_own_sessions: {e}");
+            return;
+        }
+    };
+
+    let (_, email, laptop) = register_and_login(&app).await;
+    let phone = login(&app, &email, "password123").await;
+
+    let (status, body) = send(&app, request("GET", "/me/sessions", &laptop.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    let listed = body.as_array().unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed.iter().filter(|s| s["current"] == true).count(), 1);
+    let phone_id = listed.iter().find(|s| s["current"] == false).unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // A socket opened under the phone session closes when it is terminated.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = app.router.clone();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    let (mut socket, _) =
+        connect_async(format!("ws://{addr}/ws/robot/events?token={}", phone.token))
+            .await
+            .unwrap();
+
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            &format!("/me/sessions/{phone_id}"),
+            &laptop.token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(close_code(&mut socket).await, Some(1008));
+
+    let (status, _) = send(&app, request("GET", "/me", &phone.token, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = refresh(&app, &phone.refresh_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = send(&app, request("GET", "/me/sessions", &laptop.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            &format!("/me/sessions/{phone_id}"),
+            &laptop.token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Sessions of other users cannot be terminated.
+    let (_, _, stranger) = register_and_login(&app).await;
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            &format!("/me/sessions/{phone_id}"),
+            &stranger.token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, body) = send(&app, request("GET", "/me/sessions", &laptop.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    let laptop_id = body[0]["id"].as_str().unwrap();
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            &format!("/me/sessions/{laptop_id}"),
+            &stranger.token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, request("GET", "/me", &laptop.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_admin_terminates_user_sessions() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_admin_terminates_user_sessions: {e}");
+            return;
+        }
+    };
+
+    let (user_id, email, first) = register_and_login(&app).await;
+    let second = login(&app, &email, "password123").await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = app.router.clone();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    let (mut socket, _) = connect_async(format!(
+        "ws://{addr}/ws/drive/manual?token={}",
+        second.token
+    ))
+    .await
+    .unwrap();
+
+    let admin_token = common::token("admin_id", "Admin User", "Admin");
+    let (status, body) = send(
+        &app,
+        request(
+            "DELETE",
+            &format!("/users/{user_id}/sessions"),
+            &admin_token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["revoked"], 3,
+        "Both logins plus the session recorded at registration"
+    );
+    assert_eq!(close_code(&mut socket).await, Some(1008));
+
+    for token in [&first.token, &second.token] {
+        let (status, _) = send(&app, request("GET", "/me", token, None)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            &format!("/users/{}/sessions", Uuid::new_v4()),
+            &admin_token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let fresh = login(&app, &email, "password123").await;
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            &format!("/users/{user_id}/sessions"),
+            &fresh.token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
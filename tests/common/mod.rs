@@ -1,15 +1,157 @@
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{HeaderValue, Request, StatusCode},
+};
 use backend::{create_router, AppState, Config, SharedRobotState};
 use sqlx::{postgres::PgPoolOptions, PgPool};
-use std::{sync::Arc, time::Duration};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Socket address of the reverse proxy that `via_proxy` requests come through
+#[allow(dead_code)]
+pub const TEST_PROXY: SocketAddr =
+    SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST), 40000);
 
 #[allow(dead_code)]
 pub struct TestApp {
     pub router: axum::Router,
     pub db: PgPool,
     pub state: Arc<AppState>,
+    /// Directory the app's `FileMailer` writes to; unique per app
+    pub mail_dir: PathBuf,
+}
+
+impl TestApp {
+    /// Handle of the single robot in a default (non-fleet) test app
+    #[allow(dead_code)]
+    pub async fn robot(&self) -> backend::robot::state::RobotHandle {
+        self.state
+            .robot_state
+            .robot(backend::robot::state::DEFAULT_ROBOT_ID)
+            .await
+            .expect("default robot")
+    }
+
+    /// Body of the newest mail sent to `to`, if any
+    #[allow(dead_code)]
+    pub fn last_mail_to(&self, to: &str) -> Option<String> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&self.mail_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+        files.sort();
+        files.iter().rev().find_map(|path| {
+            let contents = std::fs::read_to_string(path).ok()?;
+            let (headers, body) = contents.split_once("\r\n\r\n")?;
+            headers
+                .lines()
+                .any(|line| line == format!("To: {to}"))
+                .then(|| body.to_string())
+        })
+    }
+}
+
+/// A user inserted straight into `users`, with a token for them
+#[allow(dead_code)]
+pub struct TestUser {
+    pub id: Uuid,
+    pub name: String,
+    pub token: String,
+}
+
+/// Access token for `user_id` on a fresh session id. The session has no row
+/// in `sessions`, so nothing can revoke it; production tokens come from a
+/// login.
+#[allow(dead_code)]
+pub fn token(user_id: &str, name: &str, role: &str) -> String {
+    backend::auth::security::create_access_token(
+        user_id,
+        name,
+        role,
+        &Uuid::new_v4().to_string(),
+        false,
+        "test_secret",
+        chrono::Duration::hours(1),
+    )
+    .unwrap()
+}
+
+/// Insert a user holding `role` and sign a token for them
+#[allow(dead_code)]
+pub async fn user_with_role(app: &TestApp, role: &str) -> TestUser {
+    let id = Uuid::new_v4();
+    let name = format!("{role} {id}");
+    sqlx::query(
+        "INSERT INTO users (id, name, email, password_hash, role) VALUES ($1, $2, $3, 'x', $4)",
+    )
+    .bind(id)
+    .bind(&name)
+    .bind(format!("user-{id}@example.com"))
+    .bind(role)
+    .execute(&app.db)
+    .await
+    .unwrap();
+    let token = token(&id.to_string(), &name, role);
+    TestUser { id, name, token }
+}
+
+/// Run `request` through the router. The body is parsed as JSON, `Null` if
+/// it is empty or not JSON.
+#[allow(dead_code)]
+pub async fn send(app: &TestApp, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+    let response = app.router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+/// JSON request authenticated with `token`
+#[allow(dead_code)]
+pub fn request(
+    method: &str,
+    uri: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Authorization", format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .unwrap()
+}
+
+/// Anonymous JSON `POST`
+#[allow(dead_code)]
+pub fn post_json(uri: &str, body: serde_json::Value) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
 }
 
-pub async fn spawn_app(pool: PgPool) -> Result<TestApp, String> {
+/// `request` as forwarded for `client_ip` by the trusted `TEST_PROXY`
+#[allow(dead_code)]
+pub fn via_proxy(mut request: Request<Body>, client_ip: &str) -> Request<Body> {
+    request.extensions_mut().insert(ConnectInfo(TEST_PROXY));
+    request
+        .headers_mut()
+        .insert("X-Real-IP", HeaderValue::from_str(client_ip).unwrap());
+    request
+}
+
+pub async fn spawn_app(
+    pool: PgPool,
+    robot_api_keys: Vec<(String, String)>,
+    configure: impl FnOnce(&mut Config),
+) -> Result<TestApp, String> {
     // Mock Redis or use a real one if available.
     // For tests, we might skip redis if it's only for specific features not tested here,
     // but AppState requires it.
@@ -26,17 +168,56 @@ pub async fn spawn_app(pool: PgPool) -> Result<TestApp, String> {
         .get_connection_manager()
         .await
         .map_err(|e| format!("Failed to connect to Redis: {e}"))?;
+    // As at startup: migrations may have granted new default permissions.
+    let _ = backend::cache::CacheService::invalidate_all_role_permissions(&mut redis.clone()).await;
 
-    let config = Config {
+    let mail_dir = std::env::temp_dir().join(format!("teletable-mail-{}", uuid::Uuid::new_v4()));
+
+    let mut config = Config {
         database_url: "postgres://...".to_string(), // Overridden by logic elsewhere
         redis_url: "redis://127.0.0.1/".to_string(),
         jwt_secret: "test_secret".to_string(),
-        jwt_expiry_hours: 24,
+        access_token_ttl_minutes: 15,
+        refresh_token_ttl_days: 30,
         server_address: "127.0.0.1:0".to_string(),
-        robot_api_key: "test_robot_api_key".to_string(),
+        robot_api_key: Some("test_robot_api_key".to_string()),
+        robot_api_keys,
+        allow_default_robot_key: false,
+        enforce_route_chaining: false,
+        app_base_url: "http://localhost".to_string(),
+        mail_transport: "file".to_string(),
+        smtp_url: None,
+        mail_from: "TeleTable <no-reply@localhost>".to_string(),
+        mail_dir: mail_dir.display().to_string(),
+        require_email_verification: false,
+        require_admin_2fa: false,
+        retain_diary_on_account_deletion: false,
+        rate_limit_routes: Vec::new(),
+        rate_limit_roles: Vec::new(),
+        // `TEST_PROXY`; requests without a peer address have an unknown IP
+        trusted_proxies: vec!["127.0.0.1".parse().unwrap()],
     };
+    configure(&mut config);
 
-    let robot_state = SharedRobotState::new();
+    let robot_ids: Vec<String> = config.robot_keys().into_iter().map(|(id, _)| id).collect();
+    let robot_state = SharedRobotState::with_robots(&robot_ids);
+    *robot_state.nodes.write().await = vec![
+        backend::robot::models::RobotNode {
+            id: "home".to_string(),
+            label: "Home".to_string(),
+            rfid_uuid: None,
+        },
+        backend::robot::models::RobotNode {
+            id: "kitchen".to_string(),
+            label: "Kitchen".to_string(),
+            rfid_uuid: None,
+        },
+        backend::robot::models::RobotNode {
+            id: "office".to_string(),
+            label: "Office".to_string(),
+            rfid_uuid: None,
+        },
+    ];
 
     // Create HTTP client for tests
     let http_client = reqwest::Client::builder()
@@ -46,26 +227,18 @@ pub async fn spawn_app(pool: PgPool) -> Result<TestApp, String> {
         .build()
         .expect("Failed to create HTTP client");
 
+    let mailer = Arc::new(backend::mail::FileMailer::new(
+        mail_dir.clone(),
+        &config.mail_from,
+    ));
+
     let state = Arc::new(AppState {
         db: pool.clone(),
         redis,
         config,
         robot_state: robot_state.clone(),
-        static_nodes: vec![
-            backend::robot::models::RobotNode {
-                id: "home".to_string(),
-                label: "Home".to_string(),
-            },
-            backend::robot::models::RobotNode {
-                id: "kitchen".to_string(),
-                label: "Kitchen".to_string(),
-            },
-            backend::robot::models::RobotNode {
-                id: "office".to_string(),
-                label: "Office".to_string(),
-            },
-        ],
         http_client,
+        mailer,
     });
 
     let router = create_router(state.clone());
@@ -74,10 +247,38 @@ pub async fn spawn_app(pool: PgPool) -> Result<TestApp, String> {
         router,
         db: pool,
         state,
+        mail_dir,
     })
 }
 
+#[allow(dead_code)]
 pub async fn setup_test_app() -> Result<TestApp, String> {
+    setup_app(Vec::new(), |_| {}).await
+}
+
+/// Default test app with `configure` applied to its config
+#[allow(dead_code)]
+pub async fn setup_test_app_with(configure: impl FnOnce(&mut Config)) -> Result<TestApp, String> {
+    setup_app(Vec::new(), configure).await
+}
+
+/// Test app whose fleet is the given `(robot_id, api_key)` pairs
+#[allow(dead_code)]
+pub async fn setup_fleet_test_app(robots: &[(&str, &str)]) -> Result<TestApp, String> {
+    setup_app(
+        robots
+            .iter()
+            .map(|(id, key)| (id.to_string(), key.to_string()))
+            .collect(),
+        |_| {},
+    )
+    .await
+}
+
+async fn setup_app(
+    robot_api_keys: Vec<(String, String)>,
+    configure: impl FnOnce(&mut Config),
+) -> Result<TestApp, String> {
     let database_url = std::env::var("TEST_DATABASE_URL")
         .or_else(|_| std::env::var("DATABASE_URL"))
         .map_err(|_| {
@@ -96,5 +297,5 @@ pub async fn setup_test_app() -> Result<TestApp, String> {
         .await
         .map_err(|e| format!("Failed to run migrations: {e}"))?;
 
-    spawn_app(pool).await
+    spawn_app(pool, robot_api_keys, configure).await
 }
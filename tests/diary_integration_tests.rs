@@ -141,6 +141,11 @@ async fn test_diary_crud() {
         id: None,
         working_minutes: 120,
         text: "Worked on robot".into(),
+        visibility: None,
+        work_date: None,
+        tags: None,
+        route_ids: None,
+        notification_ids: None,
     };
 
     let response = app
@@ -192,6 +197,11 @@ async fn test_diary_crud() {
         id: Some(created_diary.id),
         working_minutes: 150,
         text: "Worked on robot longer".into(),
+        visibility: None,
+        work_date: None,
+        tags: None,
+        route_ids: None,
+        notification_ids: None,
     };
 
     let response = app
@@ -251,6 +261,11 @@ async fn test_viewer_cannot_create_or_update_diary() {
         id: None,
         working_minutes: 30,
         text: "Viewer write attempt".into(),
+        visibility: None,
+        work_date: None,
+        tags: None,
+        route_ids: None,
+        notification_ids: None,
     };
 
     let response = app
@@ -275,6 +290,11 @@ async fn test_viewer_cannot_create_or_update_diary() {
         id: Some(uuid::Uuid::new_v4()),
         working_minutes: 45,
         text: "Viewer update attempt".into(),
+        visibility: None,
+        work_date: None,
+        tags: None,
+        route_ids: None,
+        notification_ids: None,
     };
 
     let response = app
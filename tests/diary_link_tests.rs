@@ -0,0 +1,219 @@
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+mod common;
+
+use common::{request, send, user_with_role};
+
+/// A finished route and a notification to link to
+async fn route_and_notification(app: &common::TestApp, message: &str) -> (Uuid, Uuid) {
+    let route_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO route_queue (id, start, destination, added_by, status, dispatched_at, finished_at)
+        VALUES ($1, 'Lab', 'Office', 'test', 'completed', NOW() - INTERVAL '5 minutes', NOW())
+        "#,
+    )
+    .bind(route_id)
+    .execute(&app.db)
+    .await
+    .unwrap();
+    let notification_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO robot_notifications (id, robot_id, priority, message) VALUES ($1, NULL, 'WARN', $2)",
+    )
+    .bind(notification_id)
+    .bind(message)
+    .execute(&app.db)
+    .await
+    .unwrap();
+    (route_id, notification_id)
+}
+
+#[tokio::test]
+async fn test_links_filter_and_show_notifications() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_links_filter_and_show_notifications: {e}");
+            return;
+        }
+    };
+
+    let token = user_with_role(&app, "Operator").await.token;
+    let marker = Uuid::new_v4().simple().to_string()[..12].to_string();
+    let message = format!("Bumper pressed {marker}");
+    let (route_id, notification_id) = route_and_notification(&app, &message).await;
+
+    let (status, body) = send(
+        &app,
+        request(
+            "POST",
+            "/diary",
+            &token,
+            Some(serde_json::json!({
+                "working_minutes": 30,
+                "text": "Cleared the corridor",
+                "route_ids": [Uuid::new_v4()],
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert!(body["error"].as_str().unwrap().starts_with("Unknown route"));
+
+    let (status, entry) = send(
+        &app,
+        request(
+            "POST",
+            "/diary",
+            &token,
+            Some(serde_json::json!({
+                "working_minutes": 30,
+                "text": "Cleared the corridor",
+                "work_date": "2026-10-01",
+                "tags": [format!(" Tag-{marker} "), format!("tag-{marker}"), "Lab"],
+                "route_ids": [route_id, route_id],
+                "notification_ids": [notification_id],
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{entry}");
+    assert_eq!(entry["work_date"], "2026-10-01");
+    assert_eq!(
+        entry["tags"],
+        serde_json::json!([format!("tag-{marker}"), "lab"])
+    );
+    assert_eq!(entry["route_ids"], serde_json::json!([route_id]));
+    assert_eq!(entry["notifications"][0]["message"], message.as_str());
+    let id = entry["id"].as_str().unwrap().to_string();
+
+    let (status, page) = send(
+        &app,
+        request("GET", &format!("/diary?tag=TAG-{marker}"), &token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["entries"][0]["id"], id.as_str());
+    assert_eq!(page["entries"][0]["notifications"][0]["priority"], "WARN");
+
+    let (_, page) = send(
+        &app,
+        request(
+            "GET",
+            &format!("/diary/all?route_id={route_id}"),
+            &token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["entries"][0]["id"], id.as_str());
+    assert_eq!(
+        page["entries"][0]["notifications"][0]["message"],
+        message.as_str()
+    );
+
+    // Omitted fields are kept, an explicit null clears the work date
+    let (status, updated) = send(
+        &app,
+        request(
+            "POST",
+            "/diary",
+            &token,
+            Some(serde_json::json!({
+                "id": id,
+                "working_minutes": 45,
+                "text": "Cleared the corridor twice",
+                "work_date": null,
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{updated}");
+    assert!(updated["work_date"].is_null());
+    assert_eq!(updated["tags"], entry["tags"]);
+    assert_eq!(
+        updated["notification_ids"],
+        serde_json::json!([notification_id])
+    );
+
+    let (_, revisions) = send(
+        &app,
+        request("GET", &format!("/diary/{id}/revisions"), &token, None),
+    )
+    .await;
+    assert_eq!(revisions[0]["work_date"], "2026-10-01");
+    assert_eq!(revisions[0]["route_ids"], serde_json::json!([route_id]));
+
+    // Without robot.view the links are listed but the messages are not
+    let writer = format!("Writer {}", Uuid::new_v4());
+    sqlx::query("INSERT INTO role_permissions (role, permission) VALUES ($1, 'diary.write')")
+        .bind(&writer)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    let writer_token = user_with_role(&app, &writer).await.token;
+    let (_, page) = send(
+        &app,
+        request(
+            "GET",
+            &format!("/diary/all?route_id={route_id}"),
+            &writer_token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(
+        page["entries"][0]["notification_ids"],
+        serde_json::json!([notification_id])
+    );
+    assert_eq!(page["entries"][0]["notifications"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_report_counts_entries_on_their_work_date() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_report_counts_entries_on_their_work_date: {e}");
+            return;
+        }
+    };
+
+    let token = user_with_role(&app, "Operator").await.token;
+    for (minutes, work_date) in [(40, Some("2020-03-10")), (25, None)] {
+        let (status, body) = send(
+            &app,
+            request(
+                "POST",
+                "/diary",
+                &token,
+                Some(serde_json::json!({
+                    "working_minutes": minutes,
+                    "text": "Backfilled",
+                    "work_date": work_date,
+                })),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED, "{body}");
+    }
+
+    let (status, report) = send(
+        &app,
+        request(
+            "GET",
+            "/diary/report?from=2020-03-01T00:00:00Z&to=2020-04-01T00:00:00Z&group_by=month",
+            &token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(report["total_minutes"], 40);
+    assert_eq!(report["rows"][0]["period_start"], "2020-03-01");
+    assert_eq!(report["rows"][0]["first_entry_at"], "2020-03-10T00:00:00Z");
+}
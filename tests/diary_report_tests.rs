@@ -0,0 +1,212 @@
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+async fn send(app: &common::TestApp, uri: &str, token: &str) -> (StatusCode, String, String) {
+    let response = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header("Authorization", format!("Bearer {token}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        content_type,
+        String::from_utf8(bytes.to_vec()).unwrap(),
+    )
+}
+
+/// Create a user holding `(created_at, working_minutes)` diary entries
+async fn user_with_entries(
+    app: &common::TestApp,
+    role: &str,
+    entries: &[(&str, i32)],
+) -> (Uuid, String) {
+    let user = common::user_with_role(app, role).await;
+
+    for (created_at, minutes) in entries {
+        sqlx::query(
+            "INSERT INTO diary_entries (id, owner, working_minutes, text, created_at) VALUES ($1, $2, $3, 'work', $4::timestamptz)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user.id)
+        .bind(minutes)
+        .bind(created_at)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    }
+
+    (user.id, user.token)
+}
+
+fn rows_of(report: &serde_json::Value, owner_id: Uuid) -> Vec<serde_json::Value> {
+    report["rows"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|r| r["owner_id"] == owner_id.to_string())
+        .cloned()
+        .collect()
+}
+
+const RANGE: &str = "from=2025-06-01T00:00:00Z&to=2025-07-01T00:00:00Z";
+
+#[tokio::test]
+async fn test_report_groups_minutes_by_user_week_and_month() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_report_groups_minutes_by_user_week_and_month: {e}");
+            return;
+        }
+    };
+
+    // 2025-06-02 and 2025-06-04 share an ISO week, 2025-06-09 starts the next;
+    // the July entry is outside the range.
+    let (operator_id, operator) = user_with_entries(
+        &app,
+        "Operator",
+        &[
+            ("2025-06-02T08:00:00Z", 90),
+            ("2025-06-04T08:00:00Z", 30),
+            ("2025-06-09T08:00:00Z", 45),
+            ("2025-07-01T08:00:00Z", 600),
+        ],
+    )
+    .await;
+    // A fresh role rather than `Admin`, whose permissions may still be cached
+    // from before the `diary.report` grant
+    let timekeeper = format!("Timekeeper {}", Uuid::new_v4());
+    sqlx::query("INSERT INTO role_permissions (role, permission) VALUES ($1, 'diary.report')")
+        .bind(&timekeeper)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    let (reporter_id, reporter) =
+        user_with_entries(&app, &timekeeper, &[("2025-06-10T08:00:00Z", 60)]).await;
+
+    let (status, _, body) = send(&app, &format!("/diary/report?{RANGE}"), &operator).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(report["group_by"], "user");
+    assert_eq!(report["total_minutes"], 165);
+    assert_eq!(report["total_entries"], 3);
+    assert_eq!(report["rows"].as_array().unwrap().len(), 1);
+    assert_eq!(rows_of(&report, operator_id)[0]["minutes"], 165);
+
+    let (_, _, body) = send(
+        &app,
+        &format!("/diary/report?{RANGE}&group_by=week"),
+        &operator,
+    )
+    .await;
+    let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let weeks = rows_of(&report, operator_id);
+    assert_eq!(weeks.len(), 2);
+    assert_eq!(weeks[0]["period_start"], "2025-06-02");
+    assert_eq!(weeks[0]["period_end"], "2025-06-09");
+    assert_eq!(weeks[0]["minutes"], 120);
+    assert_eq!(weeks[1]["period_start"], "2025-06-09");
+    assert_eq!(weeks[1]["minutes"], 45);
+
+    // Operators report on themselves only
+    let (status, _, _) = send(
+        &app,
+        &format!("/diary/report?owner_id={reporter_id}"),
+        &operator,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // `diary.report` covers everyone
+    let (status, _, body) = send(
+        &app,
+        &format!("/diary/report?{RANGE}&group_by=month"),
+        &reporter,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let months = rows_of(&report, operator_id);
+    assert_eq!(months.len(), 1);
+    assert_eq!(months[0]["period_start"], "2025-06-01");
+    assert_eq!(months[0]["period_end"], "2025-07-01");
+    assert_eq!(months[0]["minutes"], 165);
+    assert_eq!(rows_of(&report, reporter_id)[0]["minutes"], 60);
+}
+
+#[tokio::test]
+async fn test_report_exports_csv_and_icalendar() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_report_exports_csv_and_icalendar: {e}");
+            return;
+        }
+    };
+
+    let (operator_id, operator) = user_with_entries(
+        &app,
+        "Operator",
+        &[("2025-06-02T08:00:00Z", 90), ("2025-06-03T08:00:00Z", 45)],
+    )
+    .await;
+
+    let (status, content_type, csv) = send(
+        &app,
+        &format!("/diary/report?{RANGE}&group_by=week&format=csv"),
+        &operator,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+    assert_eq!(
+        lines[0],
+        "\u{feff}owner_id,owner,period_start,period_end,entries,minutes,hours"
+    );
+    assert_eq!(
+        lines[1],
+        format!("{operator_id},Operator {operator_id},2025-06-02,2025-06-09,2,135,2.25")
+    );
+
+    let (status, content_type, ics) = send(
+        &app,
+        &format!("/diary/report?{RANGE}&format=ics"),
+        &operator,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "text/calendar; charset=utf-8");
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.contains("DTSTART;VALUE=DATE:20250602\r\n"));
+    assert!(ics.contains("DTEND;VALUE=DATE:20250604\r\n"));
+    assert!(ics
+        .replace("\r\n ", "")
+        .contains(&format!("SUMMARY:Operator {operator_id}: 2h 15m\r\n")));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+
+    let (status, _, _) = send(&app, "/diary/report?group_by=year", &operator).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
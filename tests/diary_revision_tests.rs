@@ -0,0 +1,182 @@
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+mod common;
+
+use common::{request, send, user_with_role};
+
+async fn save(
+    app: &common::TestApp,
+    token: &str,
+    id: Option<&str>,
+    minutes: i32,
+    text: &str,
+) -> serde_json::Value {
+    let (status, entry) = send(
+        app,
+        request(
+            "POST",
+            "/diary",
+            token,
+            Some(serde_json::json!({ "id": id, "working_minutes": minutes, "text": text })),
+        ),
+    )
+    .await;
+    assert!(status.is_success(), "{status}: {entry}");
+    entry
+}
+
+#[tokio::test]
+async fn test_updates_keep_previous_versions() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_updates_keep_previous_versions: {e}");
+            return;
+        }
+    };
+
+    let token = user_with_role(&app, "Operator").await.token;
+    let entry = save(&app, &token, None, 30, "Draft").await;
+    let id = entry["id"].as_str().unwrap();
+
+    let (status, revisions) = send(
+        &app,
+        request("GET", &format!("/diary/{id}/revisions"), &token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(revisions, serde_json::json!([]));
+
+    save(&app, &token, Some(id), 45, "Second draft").await;
+    let current = save(&app, &token, Some(id), 60, "Final").await;
+    assert_eq!(current["text"], "Final");
+
+    let (status, revisions) = send(
+        &app,
+        request("GET", &format!("/diary/{id}/revisions"), &token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let revisions = revisions.as_array().unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0]["revision"], 2);
+    assert_eq!(revisions[0]["text"], "Second draft");
+    assert_eq!(revisions[0]["working_minutes"], 45);
+    assert_eq!(revisions[1]["revision"], 1);
+    assert_eq!(revisions[1]["text"], "Draft");
+    assert_eq!(revisions[1]["valid_from"], entry["updated_at"]);
+
+    let stranger = user_with_role(&app, "Operator").await.token;
+    let (status, _) = send(
+        &app,
+        request("GET", &format!("/diary/{id}/revisions"), &stranger, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_deleted_entries_can_be_restored_until_purged() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_deleted_entries_can_be_restored_until_purged: {e}");
+            return;
+        }
+    };
+
+    let token = user_with_role(&app, "Operator").await.token;
+    let entry = save(&app, &token, None, 30, "Oops").await;
+    let id = entry["id"].as_str().unwrap();
+    save(&app, &token, Some(id), 35, "Oops, edited").await;
+
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            "/diary",
+            &token,
+            Some(serde_json::json!({ "id": id })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(
+        &app,
+        request("GET", &format!("/diary?id={id}"), &token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, page) = send(&app, request("GET", "/diary", &token, None)).await;
+    assert_eq!(page["total"], 0);
+    let (_, trash) = send(&app, request("GET", "/diary?deleted=true", &token, None)).await;
+    assert_eq!(trash["total"], 1);
+    assert!(trash["entries"][0]["deleted_at"].is_string());
+
+    // Deleted entries cannot be edited or deleted again
+    let (status, _) = send(
+        &app,
+        request(
+            "POST",
+            "/diary",
+            &token,
+            Some(serde_json::json!({ "id": id, "working_minutes": 1, "text": "x" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, restored) = send(
+        &app,
+        request("POST", &format!("/diary/{id}/restore"), &token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(restored["text"], "Oops, edited");
+    assert!(restored.get("deleted_at").is_none());
+    let (status, _) = send(
+        &app,
+        request("POST", &format!("/diary/{id}/restore"), &token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Past the retention window the entry and its revisions are purged
+    send(
+        &app,
+        request(
+            "DELETE",
+            "/diary",
+            &token,
+            Some(serde_json::json!({ "id": id })),
+        ),
+    )
+    .await;
+    let id = Uuid::parse_str(id).unwrap();
+    sqlx::query("UPDATE diary_entries SET deleted_at = NOW() - INTERVAL '31 days' WHERE id = $1")
+        .bind(id)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    let purged = backend::diary::trash::purge(&app.db, chrono::Utc::now())
+        .await
+        .unwrap();
+    assert!(purged >= 1);
+
+    let remaining: i64 = sqlx::query_scalar(
+        "SELECT (SELECT COUNT(*) FROM diary_entries WHERE id = $1) + (SELECT COUNT(*) FROM diary_entry_revisions WHERE entry_id = $1)",
+    )
+    .bind(id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(remaining, 0);
+    let (status, _) = send(
+        &app,
+        request("POST", &format!("/diary/{id}/restore"), &token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,187 @@
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+mod common;
+
+use common::{request, send, user_with_role};
+
+/// Create an operator with `texts` as diary entries, one day apart and the
+/// first one oldest, starting on 2026-03-01.
+async fn operator_with_entries(app: &common::TestApp, texts: &[&str]) -> (Uuid, String, String) {
+    let user = user_with_role(app, "Operator").await;
+
+    for (day, text) in texts.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO diary_entries (id, owner, working_minutes, text, created_at) VALUES ($1, $2, 60, $3, TIMESTAMPTZ '2026-03-01T09:00:00Z' + make_interval(days => $4))",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user.id)
+        .bind(text)
+        .bind(day as i32)
+        .execute(&app.db)
+        .await
+        .unwrap();
+    }
+
+    (user.id, user.name, user.token)
+}
+
+fn texts(page: &serde_json::Value) -> Vec<&str> {
+    page["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["text"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_own_diary_is_paginated_with_cursor() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_own_diary_is_paginated_with_cursor: {e}");
+            return;
+        }
+    };
+
+    let (_, _, token) =
+        operator_with_entries(&app, &["first", "second", "third", "fourth", "fifth"]).await;
+
+    let (status, page) = send(&app, request("GET", "/diary?limit=2", &token, None)).await;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(page["total"], 5);
+    assert_eq!(texts(&page), ["fifth", "fourth"]);
+
+    let cursor = page["next_cursor"].as_str().unwrap();
+    let (_, page) = send(
+        &app,
+        request(
+            "GET",
+            &format!("/diary?limit=2&cursor={cursor}"),
+            &token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(texts(&page), ["third", "second"]);
+    assert_eq!(page["total"], 5);
+
+    let cursor = page["next_cursor"].as_str().unwrap();
+    let (_, page) = send(
+        &app,
+        request(
+            "GET",
+            &format!("/diary?limit=2&cursor={cursor}"),
+            &token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(texts(&page), ["first"]);
+    assert!(page["next_cursor"].is_null());
+
+    // `from` is inclusive, `to` exclusive
+    let (_, page) = send(
+        &app,
+        request(
+            "GET",
+            "/diary?from=2026-03-02T09:00:00Z&to=2026-03-04T09:00:00Z",
+            &token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(texts(&page), ["third", "second"]);
+    assert_eq!(page["total"], 2);
+
+    let (status, body) = send(
+        &app,
+        request("GET", "/diary?cursor=not-a-cursor", &token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Invalid cursor");
+}
+
+#[tokio::test]
+async fn test_all_diaries_filter_by_owner_and_search_text() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_all_diaries_filter_by_owner_and_search_text: {e}");
+            return;
+        }
+    };
+
+    let marker = Uuid::new_v4().simple().to_string();
+    let (alice_id, alice, token) = operator_with_entries(
+        &app,
+        &[
+            &format!("Calibrated the lidar {marker}"),
+            &format!("Replaced a wheel {marker}"),
+        ],
+    )
+    .await;
+    let (_, _, _) =
+        operator_with_entries(&app, &[&format!("Calibrated the camera {marker}")]).await;
+
+    let (status, page) = send(
+        &app,
+        request("GET", &format!("/diary/all?q={marker}"), &token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(page["total"], 3);
+
+    let (_, page) = send(
+        &app,
+        request(
+            "GET",
+            &format!("/diary/all?q=calibrated%20{marker}"),
+            &token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(page["total"], 2);
+
+    let (_, page) = send(
+        &app,
+        request(
+            "GET",
+            &format!(
+                "/diary/all?q={marker}&owner={}",
+                alice.to_uppercase().replace(' ', "%20")
+            ),
+            &token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(
+        texts(&page),
+        [
+            format!("Replaced a wheel {marker}"),
+            format!("Calibrated the lidar {marker}")
+        ]
+    );
+    assert!(page["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|e| e["owner"] == alice.as_str()));
+
+    let (_, page) = send(
+        &app,
+        request(
+            "GET",
+            &format!("/diary/all?q=calibrated%20{marker}&owner_id={alice_id}"),
+            &token,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(texts(&page), [format!("Calibrated the lidar {marker}")]);
+    assert_eq!(page["total"], 1);
+}
@@ -0,0 +1,222 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use uuid::Uuid;
+
+mod common;
+
+use common::{send, user_with_role};
+
+fn request(
+    method: &str,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Content-Type", "application/json");
+    if let Some(token) = token {
+        builder = builder.header("Authorization", format!("Bearer {token}"));
+    }
+    builder
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .unwrap()
+}
+
+async fn write_entry(app: &common::TestApp, token: &str, text: &str, visibility: Option<&str>) {
+    let (status, body) = send(
+        app,
+        request(
+            "POST",
+            "/diary",
+            Some(token),
+            Some(serde_json::json!({
+                "working_minutes": 30,
+                "text": text,
+                "visibility": visibility
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+    assert_eq!(body["visibility"], visibility.unwrap_or("team"));
+}
+
+async fn feed_texts(app: &common::TestApp, marker: &str, token: Option<&str>) -> Vec<String> {
+    let (status, page) = send(
+        app,
+        request("GET", &format!("/diary/all?q={marker}"), token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    let mut texts: Vec<String> = page["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["text"].as_str().unwrap().to_string())
+        .collect();
+    texts.sort();
+    texts
+}
+
+async fn set_public_feed(app: &common::TestApp, admin: &str, enabled: bool) {
+    let (status, body) = send(
+        app,
+        request(
+            "PUT",
+            "/diary/settings",
+            Some(admin),
+            Some(serde_json::json!({ "public_feed": enabled })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["public_feed"], enabled);
+}
+
+#[tokio::test]
+async fn test_feed_respects_entry_visibility() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_feed_respects_entry_visibility: {e}");
+            return;
+        }
+    };
+
+    let marker = Uuid::new_v4().simple().to_string();
+    let author = user_with_role(&app, "Operator").await.token;
+    let colleague = user_with_role(&app, "Viewer").await.token;
+    write_entry(&app, &author, &format!("private {marker}"), Some("private")).await;
+    write_entry(&app, &author, &format!("team {marker}"), None).await;
+    write_entry(&app, &author, &format!("public {marker}"), Some("public")).await;
+
+    assert_eq!(
+        feed_texts(&app, &marker, Some(&author)).await,
+        [
+            format!("private {marker}"),
+            format!("public {marker}"),
+            format!("team {marker}")
+        ]
+    );
+    assert_eq!(
+        feed_texts(&app, &marker, Some(&colleague)).await,
+        [format!("public {marker}"), format!("team {marker}")]
+    );
+
+    let (status, body) = send(
+        &app,
+        request(
+            "POST",
+            "/diary",
+            Some(&author),
+            Some(serde_json::json!({
+                "working_minutes": 30,
+                "text": "secret",
+                "visibility": "friends"
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Unknown visibility: friends");
+}
+
+#[tokio::test]
+async fn test_anonymous_feed_is_sanitized_and_can_be_disabled() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_anonymous_feed_is_sanitized_and_can_be_disabled: {e}");
+            return;
+        }
+    };
+
+    let marker = Uuid::new_v4().simple().to_string();
+    let admin = user_with_role(&app, "Admin").await.token;
+    let author = user_with_role(&app, "Operator").await.token;
+    write_entry(&app, &author, &format!("team {marker}"), None).await;
+    write_entry(&app, &author, &format!("public {marker}"), Some("public")).await;
+
+    // A previous run may have stopped with the feed switched off
+    set_public_feed(&app, &admin, true).await;
+
+    // Anonymous callers get only public entries, without owner details
+    let (status, page) = send(
+        &app,
+        request("GET", &format!("/diary/all?q={marker}"), None, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(page["total"], 1);
+    let entry = page["entries"][0].as_object().unwrap();
+    assert_eq!(entry["text"], format!("public {marker}").as_str());
+    let mut fields: Vec<&str> = entry.keys().map(String::as_str).collect();
+    fields.sort();
+    assert_eq!(
+        fields,
+        [
+            "created_at",
+            "id",
+            "tags",
+            "text",
+            "work_date",
+            "working_minutes"
+        ]
+    );
+
+    let (status, body) = send(&app, request("GET", "/diary/all?owner=Someone", None, None)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Owner filters require authentication");
+
+    let (status, _) = send(
+        &app,
+        request(
+            "PUT",
+            "/diary/settings",
+            Some(&author),
+            Some(serde_json::json!({ "public_feed": false })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    set_public_feed(&app, &admin, false).await;
+    let (status, body) = send(
+        &app,
+        request("GET", &format!("/diary/all?q={marker}"), None, None),
+    )
+    .await;
+    let (_, settings) = send(&app, request("GET", "/diary/settings", Some(&admin), None)).await;
+    let signed_in = feed_texts(&app, &marker, Some(&author)).await;
+    set_public_feed(&app, &admin, true).await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "The public diary feed is disabled");
+    assert_eq!(settings["public_feed"], false);
+    assert_eq!(
+        signed_in,
+        [format!("public {marker}"), format!("team {marker}")]
+    );
+    assert_eq!(
+        feed_texts(&app, &marker, None).await,
+        [format!("public {marker}")]
+    );
+
+    let (status, body) = send(
+        &app,
+        request(
+            "GET",
+            "/audit?action=diary.settings_change&limit=2",
+            Some(&admin),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body[0]["after"]["public_feed"], true);
+    assert_eq!(body[1]["after"]["public_feed"], false);
+}
@@ -8,13 +8,12 @@ mod common;
 
 /// Helper: create auth header with the test secret
 fn auth_header(role: &str) -> String {
-    let token =
-        backend::auth::security::create_jwt("user-1", "Test User", role, "test_secret", 1).unwrap();
+    let token = common::token("user-1", "Test User", role);
     format!("Bearer {token}")
 }
 
 fn auth_header_for(user_id: &str, name: &str, role: &str) -> String {
-    let token = backend::auth::security::create_jwt(user_id, name, role, "test_secret", 1).unwrap();
+    let token = common::token(user_id, name, role);
     format!("Bearer {token}")
 }
 
@@ -30,10 +29,11 @@ async fn test_status_hides_expired_lock() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Set a lock that expired 10 seconds ago
     {
-        let mut lock = app.state.robot_state.manual_lock.write().await;
+        let mut lock = robot.manual_lock.write().await;
         *lock = Some(backend::robot::state::LockInfo {
             holder_id: uuid::Uuid::new_v4(),
             holder_name: "Old User".to_string(),
@@ -41,7 +41,7 @@ async fn test_status_hides_expired_lock() {
         });
     }
 
-    let status = backend::robot::build_status_update(&app.state).await;
+    let status = backend::robot::build_status_update(&app.state, &robot).await;
 
     // The expired lock holder should NOT appear
     assert!(
@@ -63,9 +63,10 @@ async fn test_status_shows_active_lock() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     {
-        let mut lock = app.state.robot_state.manual_lock.write().await;
+        let mut lock = robot.manual_lock.write().await;
         *lock = Some(backend::robot::state::LockInfo {
             holder_id: uuid::Uuid::new_v4(),
             holder_name: "Active User".to_string(),
@@ -73,7 +74,7 @@ async fn test_status_shows_active_lock() {
         });
     }
 
-    let status = backend::robot::build_status_update(&app.state).await;
+    let status = backend::robot::build_status_update(&app.state, &robot).await;
     assert_eq!(
         status.manual_lock_holder_name,
         Some("Active User".to_string())
@@ -92,9 +93,10 @@ async fn test_status_robot_disconnected_when_no_updates() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // No state updates -> last_state_update is None
-    let status = backend::robot::build_status_update(&app.state).await;
+    let status = backend::robot::build_status_update(&app.state, &robot).await;
     assert!(!status.robot_connected);
 }
 
@@ -110,6 +112,7 @@ async fn test_status_robot_connected_after_update() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Simulate a robot state update via HTTP
     let payload = serde_json::json!({
@@ -137,7 +140,7 @@ async fn test_status_robot_connected_after_update() {
         .await
         .unwrap();
 
-    let status = backend::robot::build_status_update(&app.state).await;
+    let status = backend::robot::build_status_update(&app.state, &robot).await;
     assert!(status.robot_connected);
 }
 
@@ -153,14 +156,15 @@ async fn test_status_robot_disconnected_when_stale() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Set last update to 60 seconds ago (stale threshold is 30s)
     {
-        let mut last_update = app.state.robot_state.last_state_update.write().await;
+        let mut last_update = robot.last_state_update.write().await;
         *last_update = Some(chrono::Utc::now() - chrono::Duration::seconds(60));
     }
 
-    let status = backend::robot::build_status_update(&app.state).await;
+    let status = backend::robot::build_status_update(&app.state, &robot).await;
     assert!(!status.robot_connected);
 }
 
@@ -176,14 +180,15 @@ async fn test_check_robot_connection_detects_stale() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Set a robot URL but make the last update stale
     {
-        let mut url = app.state.robot_state.robot_url.write().await;
+        let mut url = robot.robot_url.write().await;
         *url = Some("http://10.0.0.99:8080".to_string());
     }
     {
-        let mut last_update = app.state.robot_state.last_state_update.write().await;
+        let mut last_update = robot.last_state_update.write().await;
         *last_update = Some(chrono::Utc::now() - chrono::Duration::seconds(60));
     }
 
@@ -222,18 +227,19 @@ async fn test_expired_lock_allows_new_acquisition() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     let old_user_id = uuid::Uuid::new_v4();
 
     // Simulate a connected robot before acquiring lock
     {
-        let mut state = app.state.robot_state.last_state_update.write().await;
+        let mut state = robot.last_state_update.write().await;
         *state = Some(chrono::Utc::now());
     }
 
     // Set an expired lock for a different user
     {
-        let mut lock = app.state.robot_state.manual_lock.write().await;
+        let mut lock = robot.manual_lock.write().await;
         *lock = Some(backend::robot::state::LockInfo {
             holder_id: old_user_id,
             holder_name: "Old User".to_string(),
@@ -272,7 +278,7 @@ async fn test_expired_lock_allows_new_acquisition() {
     );
 
     // Verify the new user holds the lock
-    let lock = app.state.robot_state.manual_lock.read().await;
+    let lock = robot.manual_lock.read().await;
     assert_eq!(lock.as_ref().unwrap().holder_name, "New User");
 }
 
@@ -288,10 +294,11 @@ async fn test_lock_renewal_extends_expiry() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Simulate a connected robot before acquiring lock
     {
-        let mut state = app.state.robot_state.last_state_update.write().await;
+        let mut state = robot.last_state_update.write().await;
         *state = Some(chrono::Utc::now());
     }
 
@@ -317,7 +324,7 @@ async fn test_lock_renewal_extends_expiry() {
         .unwrap();
 
     let first_expiry = {
-        let lock = app.state.robot_state.manual_lock.read().await;
+        let lock = robot.manual_lock.read().await;
         lock.as_ref().unwrap().expires_at
     };
 
@@ -339,7 +346,7 @@ async fn test_lock_renewal_extends_expiry() {
         .unwrap();
 
     let second_expiry = {
-        let lock = app.state.robot_state.manual_lock.read().await;
+        let lock = robot.manual_lock.read().await;
         lock.as_ref().unwrap().expires_at
     };
 
@@ -361,14 +368,15 @@ async fn test_process_queue_skipped_with_active_lock() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Make robot connected and IDLE
     {
-        let mut last_update = app.state.robot_state.last_state_update.write().await;
+        let mut last_update = robot.last_state_update.write().await;
         *last_update = Some(chrono::Utc::now());
     }
     {
-        let mut state = app.state.robot_state.current_state.write().await;
+        let mut state = robot.current_state.write().await;
         *state = Some(backend::robot::models::RobotState {
             system_health: "OK".to_string(),
             battery_level: 100,
@@ -396,12 +404,13 @@ async fn test_process_queue_skipped_with_active_lock() {
             destination: "B".to_string(),
             added_at: chrono::Utc::now(),
             added_by: "test".to_string(),
+            robot_id: None,
         });
     }
 
     // Set an active (non-expired) lock
     {
-        let mut lock = app.state.robot_state.manual_lock.write().await;
+        let mut lock = robot.manual_lock.write().await;
         *lock = Some(backend::robot::state::LockInfo {
             holder_id: uuid::Uuid::new_v4(),
             holder_name: "Lock Holder".to_string(),
@@ -410,7 +419,7 @@ async fn test_process_queue_skipped_with_active_lock() {
     }
 
     // Subscribe before processing to check if any command is sent
-    let mut rx = app.state.robot_state.command_sender.subscribe();
+    let mut rx = robot.command_sender.subscribe();
 
     // Process queue — should NOT dispatch because lock is active
     backend::robot::process_queue(&app.state).await;
@@ -442,14 +451,15 @@ async fn test_process_queue_proceeds_with_expired_lock() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Make robot connected and IDLE
     {
-        let mut last_update = app.state.robot_state.last_state_update.write().await;
+        let mut last_update = robot.last_state_update.write().await;
         *last_update = Some(chrono::Utc::now());
     }
     {
-        let mut state = app.state.robot_state.current_state.write().await;
+        let mut state = robot.current_state.write().await;
         *state = Some(backend::robot::models::RobotState {
             system_health: "OK".to_string(),
             battery_level: 100,
@@ -469,7 +479,7 @@ async fn test_process_queue_proceeds_with_expired_lock() {
     }
 
     // Subscribe before queueing
-    let mut rx = app.state.robot_state.command_sender.subscribe();
+    let mut rx = robot.command_sender.subscribe();
 
     // Add a route to the queue
     {
@@ -480,12 +490,13 @@ async fn test_process_queue_proceeds_with_expired_lock() {
             destination: "B".to_string(),
             added_at: chrono::Utc::now(),
             added_by: "test".to_string(),
+            robot_id: None,
         });
     }
 
     // Set an expired lock
     {
-        let mut lock = app.state.robot_state.manual_lock.write().await;
+        let mut lock = robot.manual_lock.write().await;
         *lock = Some(backend::robot::state::LockInfo {
             holder_id: uuid::Uuid::new_v4(),
             holder_name: "Old User".to_string(),
@@ -529,14 +540,15 @@ async fn test_process_queue_skipped_when_robot_stale() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Robot is stale (last update 60s ago)
     {
-        let mut last_update = app.state.robot_state.last_state_update.write().await;
+        let mut last_update = robot.last_state_update.write().await;
         *last_update = Some(chrono::Utc::now() - chrono::Duration::seconds(60));
     }
     {
-        let mut state = app.state.robot_state.current_state.write().await;
+        let mut state = robot.current_state.write().await;
         *state = Some(backend::robot::models::RobotState {
             system_health: "OK".to_string(),
             battery_level: 100,
@@ -564,6 +576,7 @@ async fn test_process_queue_skipped_when_robot_stale() {
             destination: "Y".to_string(),
             added_at: chrono::Utc::now(),
             added_by: "test".to_string(),
+            robot_id: None,
         });
     }
 
@@ -583,7 +596,7 @@ async fn test_process_queue_skipped_when_robot_stale() {
 // ---------------------------------------------------------------------------
 #[tokio::test]
 async fn test_clear_expired_lock() {
-    let robot_state = backend::SharedRobotState::new();
+    let robot_state = backend::robot::state::RobotHandle::new("teletable");
 
     // Set an expired lock
     {
@@ -610,7 +623,7 @@ async fn test_clear_expired_lock() {
 // ---------------------------------------------------------------------------
 #[tokio::test]
 async fn test_clear_expired_lock_preserves_active() {
-    let robot_state = backend::SharedRobotState::new();
+    let robot_state = backend::robot::state::RobotHandle::new("teletable");
 
     {
         let mut lock = robot_state.manual_lock.write().await;
@@ -633,7 +646,7 @@ async fn test_clear_expired_lock_preserves_active() {
 // ---------------------------------------------------------------------------
 #[tokio::test]
 async fn test_is_robot_connected_false_when_no_updates() {
-    let robot_state = backend::SharedRobotState::new();
+    let robot_state = backend::robot::state::RobotHandle::new("teletable");
     assert!(!robot_state.is_robot_connected().await);
 }
 
@@ -642,7 +655,7 @@ async fn test_is_robot_connected_false_when_no_updates() {
 // ---------------------------------------------------------------------------
 #[tokio::test]
 async fn test_is_robot_connected_true_after_fresh_update() {
-    let robot_state = backend::SharedRobotState::new();
+    let robot_state = backend::robot::state::RobotHandle::new("teletable");
     {
         let mut last_update = robot_state.last_state_update.write().await;
         *last_update = Some(chrono::Utc::now());
@@ -655,7 +668,7 @@ async fn test_is_robot_connected_true_after_fresh_update() {
 // ---------------------------------------------------------------------------
 #[tokio::test]
 async fn test_is_robot_connected_false_when_stale() {
-    let robot_state = backend::SharedRobotState::new();
+    let robot_state = backend::robot::state::RobotHandle::new("teletable");
     {
         let mut last_update = robot_state.last_state_update.write().await;
         *last_update = Some(chrono::Utc::now() - chrono::Duration::seconds(60));
@@ -675,10 +688,11 @@ async fn test_robot_state_update_records_timestamp() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Verify no timestamp initially
     {
-        let last_update = app.state.robot_state.last_state_update.read().await;
+        let last_update = robot.last_state_update.read().await;
         assert!(last_update.is_none());
     }
 
@@ -713,7 +727,7 @@ async fn test_robot_state_update_records_timestamp() {
 
     assert_eq!(response.status(), StatusCode::OK);
 
-    let last_update = app.state.robot_state.last_state_update.read().await;
+    let last_update = robot.last_state_update.read().await;
     let ts = last_update.expect("last_state_update should be set after state update");
     assert!(ts >= before && ts <= after, "Timestamp should be recent");
 }
@@ -730,16 +744,18 @@ async fn test_active_route_clears_on_idle() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Set an active route
     {
-        let mut active = app.state.robot_state.active_route.write().await;
+        let mut active = robot.active_route.write().await;
         *active = Some(backend::robot::models::QueuedRoute {
             id: uuid::Uuid::new_v4(),
             start: "A".to_string(),
             destination: "B".to_string(),
             added_at: chrono::Utc::now(),
             added_by: "test".to_string(),
+            robot_id: None,
         });
     }
 
@@ -769,7 +785,7 @@ async fn test_active_route_clears_on_idle() {
         .await
         .unwrap();
 
-    let active = app.state.robot_state.active_route.read().await;
+    let active = robot.active_route.read().await;
     assert!(
         active.is_none(),
         "Active route should be cleared when robot reports IDLE"
@@ -788,15 +804,17 @@ async fn test_active_route_persists_when_driving() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     {
-        let mut active = app.state.robot_state.active_route.write().await;
+        let mut active = robot.active_route.write().await;
         *active = Some(backend::robot::models::QueuedRoute {
             id: uuid::Uuid::new_v4(),
             start: "A".to_string(),
             destination: "B".to_string(),
             added_at: chrono::Utc::now(),
             added_by: "test".to_string(),
+            robot_id: None,
         });
     }
 
@@ -826,7 +844,7 @@ async fn test_active_route_persists_when_driving() {
         .await
         .unwrap();
 
-    let active = app.state.robot_state.active_route.read().await;
+    let active = robot.active_route.read().await;
     assert!(
         active.is_some(),
         "Active route should persist when robot is still driving"
@@ -877,10 +895,11 @@ async fn test_select_route_blocked_by_active_lock() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // Set active lock
     {
-        let mut lock = app.state.robot_state.manual_lock.write().await;
+        let mut lock = robot.manual_lock.write().await;
         *lock = Some(backend::robot::state::LockInfo {
             holder_id: uuid::Uuid::new_v4(),
             holder_name: "Locker".to_string(),
@@ -931,12 +950,13 @@ async fn test_only_holder_can_release_lock() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     let holder_id = "44444444-4444-4444-4444-444444444444";
 
     // Set lock for the holder
     {
-        let mut lock = app.state.robot_state.manual_lock.write().await;
+        let mut lock = robot.manual_lock.write().await;
         *lock = Some(backend::robot::state::LockInfo {
             holder_id: uuid::Uuid::parse_str(holder_id).unwrap(),
             holder_name: "Holder".to_string(),
@@ -975,6 +995,279 @@ async fn test_only_holder_can_release_lock() {
     );
 
     // Verify lock is still held
-    let lock = app.state.robot_state.manual_lock.read().await;
+    let lock = robot.manual_lock.read().await;
     assert!(lock.is_some(), "Lock should still be held");
 }
+
+// ---------------------------------------------------------------------------
+// 23. Housekeeping clears an expired lock
+// ---------------------------------------------------------------------------
+#[tokio::test]
+async fn test_housekeeping_clears_expired_lock() {
+    let app = match common::setup_test_app().await {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Skipping: {e}");
+            return;
+        }
+    };
+    let robot = app.robot().await;
+
+    {
+        let mut lock = robot.manual_lock.write().await;
+        *lock = Some(backend::robot::state::LockInfo {
+            holder_id: uuid::Uuid::new_v4(),
+            holder_name: "Expired User".to_string(),
+            expires_at: chrono::Utc::now() - chrono::Duration::seconds(5),
+        });
+    }
+
+    let changed = backend::robot::housekeeping::run_housekeeping_cycle(&app.state).await;
+    assert!(changed, "Clearing an expired lock should count as a change");
+
+    let lock = robot.manual_lock.read().await;
+    assert!(
+        lock.is_none(),
+        "Expired lock should be cleared by housekeeping"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// 24. Housekeeping re-queues the active route and forgets a stale robot
+// ---------------------------------------------------------------------------
+#[tokio::test]
+async fn test_housekeeping_requeues_route_when_robot_stale() {
+    let app = match common::setup_test_app().await {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Skipping: {e}");
+            return;
+        }
+    };
+    let robot = app.robot().await;
+
+    let route_id = uuid::Uuid::new_v4();
+    {
+        let mut active = robot.active_route.write().await;
+        *active = Some(backend::robot::models::QueuedRoute {
+            id: route_id,
+            start: "home".to_string(),
+            destination: "kitchen".to_string(),
+            added_at: chrono::Utc::now(),
+            added_by: "test".to_string(),
+            robot_id: None,
+        });
+    }
+    {
+        let mut robot_url = robot.robot_url.write().await;
+        *robot_url = Some("http://127.0.0.1:9".to_string());
+    }
+    {
+        let mut last_update = robot.last_state_update.write().await;
+        *last_update = Some(chrono::Utc::now() - chrono::Duration::seconds(60));
+    }
+
+    let changed = backend::robot::housekeeping::run_housekeeping_cycle(&app.state).await;
+    assert!(changed);
+
+    assert!(robot.active_route.read().await.is_none());
+    assert!(robot.robot_url.read().await.is_none());
+
+    let queue = app.state.robot_state.queue.read().await;
+    assert_eq!(queue.len(), 1, "Stale active route should be re-queued");
+    assert_eq!(queue[0].id, route_id);
+}
+
+// ---------------------------------------------------------------------------
+// 25. Housekeeping leaves a connected robot untouched
+// ---------------------------------------------------------------------------
+#[tokio::test]
+async fn test_housekeeping_noop_when_robot_connected() {
+    let app = match common::setup_test_app().await {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Skipping: {e}");
+            return;
+        }
+    };
+    let robot = app.robot().await;
+
+    {
+        let mut robot_url = robot.robot_url.write().await;
+        *robot_url = Some("http://127.0.0.1:9".to_string());
+    }
+    {
+        let mut last_update = robot.last_state_update.write().await;
+        *last_update = Some(chrono::Utc::now());
+    }
+
+    let changed = backend::robot::housekeeping::run_housekeeping_cycle(&app.state).await;
+    assert!(
+        !changed,
+        "Nothing should change while the robot is connected"
+    );
+    assert!(robot.robot_url.read().await.is_some());
+
+    let handle = backend::robot::housekeeping::spawn(app.state.clone());
+    tokio::time::timeout(std::time::Duration::from_secs(2), handle.shutdown())
+        .await
+        .expect("Housekeeping task should stop promptly on shutdown");
+}
+
+// ---------------------------------------------------------------------------
+// 26. select_route rejects unknown nodes with 422 and lists valid ids
+// ---------------------------------------------------------------------------
+#[tokio::test]
+async fn test_select_route_rejects_unknown_node() {
+    let app = match common::setup_test_app().await {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Skipping: {e}");
+            return;
+        }
+    };
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/routes/select")
+                .method("POST")
+                .header("Authorization", auth_header("Operator"))
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({"start": "home", "destination": "garage"}).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(result["code"], "unknown_node");
+    assert_eq!(result["field"], "destination");
+    assert_eq!(
+        result["valid_nodes"],
+        serde_json::json!(["home", "kitchen", "office"])
+    );
+    assert!(app.state.robot_state.queue.read().await.is_empty());
+}
+
+// ---------------------------------------------------------------------------
+// 27. add_route rejects start == destination
+// ---------------------------------------------------------------------------
+#[tokio::test]
+async fn test_add_route_rejects_same_start_and_destination() {
+    let app = match common::setup_test_app().await {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Skipping: {e}");
+            return;
+        }
+    };
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/routes")
+                .method("POST")
+                .header("Authorization", auth_header("Admin"))
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({"start": "kitchen", "destination": "kitchen"}).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(result["status"], "error");
+    assert_eq!(result["code"], "same_start_and_destination");
+}
+
+// ---------------------------------------------------------------------------
+// 28. Route chaining expects the queue tail, then the robot position
+// ---------------------------------------------------------------------------
+#[tokio::test]
+async fn test_route_chaining_expected_start() {
+    let app = match common::setup_test_app().await {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Skipping: {e}");
+            return;
+        }
+    };
+    let robot_state = &app.state.robot_state;
+    let robot = app.robot().await;
+
+    {
+        let mut state = robot.current_state.write().await;
+        *state = Some(backend::robot::models::RobotState {
+            system_health: "OK".to_string(),
+            battery_level: 100,
+            drive_mode: "IDLE".to_string(),
+            cargo_status: "EMPTY".to_string(),
+            current_position: "UNKNOWN".to_string(),
+            last_node: Some("office".to_string()),
+            target_node: None,
+            gyroscope: None,
+            last_read_uuid: None,
+            lux: None,
+            infrared: None,
+            voltage_v: None,
+            current_a: None,
+            power_w: None,
+        });
+    }
+
+    // Robot position falls back to last_node when current_position is not a node.
+    let expected = backend::robot::route_validation::next_queued_start(robot_state, None).await;
+    assert_eq!(expected.as_deref(), Some("office"));
+
+    robot_state
+        .queue
+        .write()
+        .await
+        .push_back(backend::robot::models::QueuedRoute {
+            id: uuid::Uuid::new_v4(),
+            start: "office".to_string(),
+            destination: "kitchen".to_string(),
+            added_at: chrono::Utc::now(),
+            added_by: "Test User".to_string(),
+            robot_id: None,
+        });
+
+    let expected = backend::robot::route_validation::next_queued_start(robot_state, None).await;
+    assert_eq!(expected.as_deref(), Some("kitchen"));
+
+    let err = backend::robot::route_validation::validate_route(
+        robot_state,
+        "home",
+        "office",
+        expected.as_deref(),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.code(), "start_mismatch");
+
+    assert!(backend::robot::route_validation::validate_route(
+        robot_state,
+        "kitchen",
+        "office",
+        expected.as_deref(),
+    )
+    .await
+    .is_ok());
+}
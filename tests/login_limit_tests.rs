@@ -0,0 +1,203 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::auth::limiter::{self, Limiter};
+use backend::auth::models::RegisterRequest;
+use uuid::Uuid;
+
+mod common;
+
+use common::send;
+
+async fn register(app: &common::TestApp) -> String {
+    let email = format!("limit-{}@example.com", Uuid::new_v4());
+    let register = RegisterRequest {
+        name: "Limit User".into(),
+        email: email.clone(),
+        password: "password123".into(),
+        fingerprint_data: None,
+    };
+    let request = Request::builder()
+        .uri("/register")
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .body(Body::from(serde_json::to_string(&register).unwrap()))
+        .unwrap();
+    let (status, _) = send(app, request).await;
+    assert_eq!(status, StatusCode::CREATED);
+    email
+}
+
+async fn login_from(
+    app: &common::TestApp,
+    ip: &str,
+    email: &str,
+    password: &str,
+) -> (StatusCode, serde_json::Value) {
+    let request = common::post_json(
+        "/login",
+        serde_json::json!({ "email": email, "password": password }),
+    );
+    send(app, common::via_proxy(request, ip)).await
+}
+
+/// A client IP no other test run has used
+fn random_ip() -> String {
+    let bytes = Uuid::new_v4().into_bytes();
+    format!("10.{}.{}.{}", bytes[0], bytes[1], bytes[2])
+}
+
+fn admin_token() -> String {
+    common::token("admin_id", "Admin User", "Admin")
+}
+
+async fn lockouts(app: &common::TestApp) -> Vec<serde_json::Value> {
+    let request = Request::builder()
+        .uri("/lockouts")
+        .header("Authorization", format!("Bearer {}", admin_token()))
+        .body(Body::empty())
+        .unwrap();
+    let (status, body) = send(app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    body.as_array().unwrap().clone()
+}
+
+async fn clear_lockout(app: &common::TestApp, scope: &str, subject: &str) -> StatusCode {
+    let request = Request::builder()
+        .uri("/lockouts")
+        .method("DELETE")
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", admin_token()))
+        .body(Body::from(
+            serde_json::json!({ "scope": scope, "subject": subject }).to_string(),
+        ))
+        .unwrap();
+    send(app, request).await.0
+}
+
+#[tokio::test]
+async fn test_failed_logins_slow_down_the_account() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_failed_logins_slow_down_the_account: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let ip = random_ip();
+
+    for _ in 0..4 {
+        let (status, _) = login_from(&app, &ip, &email, "wrong").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    // The fourth failure starts a delay; even the right password has to wait.
+    let (status, body) = login_from(&app, &random_ip(), &email, "password123").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(body["retry_after_seconds"].as_i64().unwrap() > 0);
+
+    let subject = limiter::account_subject(&email);
+    let lockout = lockouts(&app)
+        .await
+        .into_iter()
+        .find(|l| l["subject"] == subject.as_str())
+        .expect("account is listed");
+    assert_eq!(lockout["scope"], "login");
+    assert_eq!(lockout["reason"], "backoff");
+
+    assert_eq!(
+        clear_lockout(&app, "login", &subject).await,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        clear_lockout(&app, "login", &subject).await,
+        StatusCode::NOT_FOUND
+    );
+
+    let (status, _) = login_from(&app, &ip, &email, "password123").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_account_locks_after_repeated_failures() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_account_locks_after_repeated_failures: {e}");
+            return;
+        }
+    };
+
+    // Waiting out every delay would take minutes, so seed earlier misses.
+    let email = format!("nobody-{}@example.com", Uuid::new_v4());
+    let subject = limiter::account_subject(&email);
+    let mut redis = app.state.redis.clone();
+    for _ in 0..9 {
+        Limiter::new("login", 900)
+            .record(&mut redis, &subject)
+            .await
+            .unwrap();
+    }
+
+    // Unknown addresses are limited like real ones.
+    let (status, _) = login_from(&app, &random_ip(), &email.to_uppercase(), "guess").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = login_from(&app, &random_ip(), &email, "guess").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(body["retry_after_seconds"].as_i64().unwrap() > 60);
+
+    let lockout = lockouts(&app)
+        .await
+        .into_iter()
+        .find(|l| l["subject"] == subject.as_str())
+        .expect("account is listed");
+    assert_eq!(lockout["reason"], "account_locked");
+}
+
+#[tokio::test]
+async fn test_failed_logins_time_out_the_ip() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_failed_logins_time_out_the_ip: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let ip = random_ip();
+
+    // Spread over many accounts, so no single account is slowed down.
+    for _ in 0..20 {
+        let guess = format!("spray-{}@example.com", Uuid::new_v4());
+        let (status, _) = login_from(&app, &ip, &guess, "password123").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    let (status, body) = login_from(&app, &ip, &email, "password123").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        body["error"],
+        "Too many failed login attempts from this IP. Please try again later."
+    );
+
+    let (status, _) = login_from(&app, &random_ip(), &email, "password123").await;
+    assert_eq!(status, StatusCode::OK, "Other IPs are unaffected");
+
+    let subject = limiter::ip_subject(&ip);
+    assert_eq!(
+        clear_lockout(&app, "login", &subject).await,
+        StatusCode::NO_CONTENT
+    );
+    let (status, _) = login_from(&app, &ip, &email, "password123").await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(
+        clear_lockout(&app, "nope", &subject).await,
+        StatusCode::BAD_REQUEST
+    );
+}
@@ -0,0 +1,224 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::robot::models::{QueuedRoute, RouteStatus};
+use backend::robot::route_store;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+fn admin_header() -> String {
+    let token = common::token("admin_id", "Admin User", "Admin");
+    format!("Bearer {token}")
+}
+
+fn route(start: &str, destination: &str, added_at: chrono::DateTime<chrono::Utc>) -> QueuedRoute {
+    QueuedRoute {
+        id: Uuid::new_v4(),
+        start: start.to_string(),
+        destination: destination.to_string(),
+        added_at,
+        added_by: "Admin User".to_string(),
+        robot_id: None,
+    }
+}
+
+async fn send(
+    app: &common::TestApp,
+    method: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Authorization", admin_header());
+    let body = match body {
+        Some(json) => {
+            builder = builder.header("Content-Type", "application/json");
+            Body::from(json.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(builder.body(body).unwrap())
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_edge_crud_and_path_cost() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_edge_crud_and_path_cost: {e}");
+            return;
+        }
+    };
+
+    let (status, edges) = send(
+        &app,
+        "PUT",
+        "/graph/edges",
+        Some(serde_json::json!({
+            "from": "home",
+            "to": "office",
+            "cost_seconds": 42.0,
+            "bidirectional": true
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(edges.as_array().unwrap().len(), 2);
+    assert_eq!(edges[0]["source"], "manual");
+
+    let (status, path) = send(&app, "GET", "/graph/path?from=office&to=home", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(path["cost_seconds"], 42.0);
+    assert_eq!(path["path"], serde_json::json!(["office", "home"]));
+
+    let (status, _) = send(
+        &app,
+        "PUT",
+        "/graph/edges",
+        Some(serde_json::json!({ "from": "home", "to": "nowhere", "cost_seconds": 1.0 })),
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::BAD_REQUEST,
+        "Unknown nodes are rejected"
+    );
+
+    let (status, _) = send(
+        &app,
+        "PUT",
+        "/graph/edges",
+        Some(serde_json::json!({ "from": "home", "to": "office", "cost_seconds": -1.0 })),
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::BAD_REQUEST,
+        "Negative costs are rejected"
+    );
+
+    let (status, _) = send(&app, "DELETE", "/graph/edges/office/home", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, "DELETE", "/graph/edges/office/home", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, "GET", "/graph/path?from=office&to=home", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "Edges are directed");
+
+    send(&app, "DELETE", "/graph/edges/home/office", None).await;
+}
+
+#[tokio::test]
+async fn test_optimize_uses_graph_costs() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_optimize_uses_graph_costs: {e}");
+            return;
+        }
+    };
+
+    // From the kitchen, home is close and the office is far away.
+    for (to, cost) in [("home", 5.0), ("office", 100.0)] {
+        let (status, _) = send(
+            &app,
+            "PUT",
+            "/graph/edges",
+            Some(serde_json::json!({ "from": "kitchen", "to": to, "cost_seconds": cost })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let now = chrono::Utc::now();
+    let first = route("home", "kitchen", now - chrono::Duration::seconds(30));
+    let from_office = route("office", "home", now - chrono::Duration::seconds(20));
+    let from_home = route("home", "office", now - chrono::Duration::seconds(10));
+
+    {
+        let mut queue = app.state.robot_state.queue.write().await;
+        for r in [&first, &from_office, &from_home] {
+            route_store::enqueue_back(&app.db, r).await.unwrap();
+            queue.push_back(r.clone());
+        }
+    }
+
+    let (status, _) = send(&app, "POST", "/routes/optimize", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let order: Vec<Uuid> = app
+        .state
+        .robot_state
+        .queue
+        .read()
+        .await
+        .iter()
+        .map(|r| r.id)
+        .collect();
+    assert_eq!(
+        order,
+        vec![first.id, from_home.id, from_office.id],
+        "The cheap kitchen -> home transition should be taken first"
+    );
+
+    send(&app, "DELETE", "/graph/edges/kitchen/home", None).await;
+    send(&app, "DELETE", "/graph/edges/kitchen/office", None).await;
+}
+
+#[tokio::test]
+async fn test_learn_edge_weights_from_completed_routes() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_learn_edge_weights_from_completed_routes: {e}");
+            return;
+        }
+    };
+
+    let start = format!("learn-start-{}", Uuid::new_v4());
+    let destination = format!("learn-destination-{}", Uuid::new_v4());
+
+    for _ in 0..3 {
+        let trip = route(&start, &destination, chrono::Utc::now());
+        route_store::enqueue_back(&app.db, &trip).await.unwrap();
+        route_store::set_status(&app.db, trip.id, RouteStatus::Dispatched)
+            .await
+            .unwrap();
+        route_store::set_status(&app.db, trip.id, RouteStatus::Completed)
+            .await
+            .unwrap();
+    }
+
+    let (status, body) = send(&app, "POST", "/graph/learn", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["updated"].as_u64().unwrap() >= 1);
+
+    let (status, edges) = send(&app, "GET", "/graph/edges", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let learned = edges
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["from_node"] == start && e["to_node"] == destination)
+        .expect("Pair with enough completed trips should be learned");
+    assert_eq!(learned["source"], "learned");
+    assert!(learned["cost_seconds"].as_f64().unwrap() >= 0.0);
+}
@@ -0,0 +1,233 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+fn auth_header(role: &str) -> String {
+    let token = common::token("user_id", &format!("{role} User"), role);
+    format!("Bearer {token}")
+}
+
+async fn send(
+    app: &common::TestApp,
+    method: &str,
+    uri: &str,
+    role: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Authorization", auth_header(role));
+    let body = match body {
+        Some(json) => {
+            builder = builder.header("Content-Type", "application/json");
+            Body::from(json.to_string())
+        }
+        None => Body::empty(),
+    };
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(builder.body(body).unwrap())
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+fn node_ids(nodes: &serde_json::Value) -> Vec<String> {
+    nodes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_node_lifecycle_is_reflected_live() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_node_lifecycle_is_reflected_live: {e}");
+            return;
+        }
+    };
+
+    let id = format!("room-{}", Uuid::new_v4());
+    let rfid = format!("tag-{}", Uuid::new_v4());
+    let mut status_rx = app.state.robot_state.status_sender.subscribe();
+
+    let (status, created) = send(
+        &app,
+        "POST",
+        "/nodes",
+        "Admin",
+        Some(serde_json::json!({ "id": id, "label": "Room", "rfid_uuid": rfid })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["enabled"], true);
+
+    let update = status_rx.try_recv().expect("Node change should broadcast");
+    assert!(update.nodes.iter().any(|n| n.id == id));
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/nodes",
+        "Admin",
+        Some(serde_json::json!({ "id": id, "label": "Duplicate" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, renamed) = send(
+        &app,
+        "PATCH",
+        &format!("/nodes/{id}"),
+        "Admin",
+        Some(serde_json::json!({ "label": "Renamed Room" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(renamed["label"], "Renamed Room");
+    assert_eq!(renamed["rfid_uuid"], rfid, "Omitted fields are unchanged");
+
+    let (_, nodes) = send(&app, "GET", "/nodes", "Viewer", None).await;
+    let listed = nodes["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|n| n["id"] == id)
+        .expect("New node should be listed");
+    assert_eq!(listed["label"], "Renamed Room");
+
+    let (status, _) = send(
+        &app,
+        "PATCH",
+        &format!("/nodes/{id}"),
+        "Admin",
+        Some(serde_json::json!({ "enabled": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, nodes) = send(&app, "GET", "/nodes", "Viewer", None).await;
+    assert!(
+        !node_ids(&nodes["nodes"]).contains(&id),
+        "Disabled nodes are hidden from GET /nodes"
+    );
+
+    let (_, all) = send(&app, "GET", "/nodes/all", "Admin", None).await;
+    assert!(
+        node_ids(&all).contains(&id),
+        "Admins still see disabled nodes"
+    );
+
+    // Reordering requires the complete node list.
+    let mut order = node_ids(&all);
+    order.reverse();
+    let (status, _) = send(
+        &app,
+        "PUT",
+        "/nodes/order",
+        "Admin",
+        Some(serde_json::json!({ "ids": order[1..] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, reordered) = send(
+        &app,
+        "PUT",
+        "/nodes/order",
+        "Admin",
+        Some(serde_json::json!({ "ids": order })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(node_ids(&reordered), order);
+
+    let (status, _) = send(
+        &app,
+        "PATCH",
+        "/nodes/missing-node",
+        "Admin",
+        Some(serde_json::json!({ "label": "x" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_rfid_tag_locates_robot() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_rfid_tag_locates_robot: {e}");
+            return;
+        }
+    };
+    let robot = app.robot().await;
+
+    // Seeded directly: creating nodes through the API here would race with
+    // the reorder check in test_node_lifecycle_is_reflected_live.
+    let id = "kitchen".to_string();
+    let rfid = format!("tag-{}", Uuid::new_v4());
+    app.state.robot_state.nodes.write().await[1].rfid_uuid = Some(rfid.clone());
+
+    *robot.current_state.write().await = Some(backend::robot::models::RobotState {
+        system_health: "OK".to_string(),
+        battery_level: 100,
+        drive_mode: "IDLE".to_string(),
+        cargo_status: "EMPTY".to_string(),
+        current_position: "UNKNOWN".to_string(),
+        last_node: None,
+        target_node: None,
+        gyroscope: None,
+        last_read_uuid: Some(rfid.clone()),
+        lux: None,
+        infrared: None,
+        voltage_v: None,
+        current_a: None,
+        power_w: None,
+    });
+
+    let update = backend::robot::build_status_update(&app.state, &robot).await;
+    assert_eq!(update.position, id);
+
+    let (_, debug) = send(&app, "GET", "/robot/debug", "Admin", None).await;
+    assert_eq!(debug["sensors"]["rfid"]["nodeId"], id);
+}
+
+#[tokio::test]
+async fn test_node_management_requires_admin() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_node_management_requires_admin: {e}");
+            return;
+        }
+    };
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/nodes",
+        "Operator",
+        Some(serde_json::json!({ "id": "operator-node", "label": "Nope" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
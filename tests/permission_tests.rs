@@ -0,0 +1,395 @@
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+mod common;
+
+use common::{request, send};
+
+fn token(role: &str) -> String {
+    common::token(&Uuid::new_v4().to_string(), &format!("{role} user"), role)
+}
+
+#[tokio::test]
+async fn test_default_roles_keep_previous_access() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_default_roles_keep_previous_access: {e}");
+            return;
+        }
+    };
+
+    let (status, body) = send(&app, request("GET", "/permissions", &token("Admin"), None)).await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = body["permissions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    for name in ["robot.drive", "robot.led", "queue.manage", "diary.write"] {
+        assert!(names.contains(&name), "missing {name}");
+    }
+    assert_eq!(body["roles"]["Viewer"], serde_json::json!(["robot.view"]));
+    assert!(body["roles"]["Operator"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("robot.drive")));
+
+    let route = serde_json::json!({ "start": "home", "destination": "kitchen" });
+    let (status, body) = send(
+        &app,
+        request("POST", "/routes", &token("Operator"), Some(route.clone())),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["permission"], "queue.manage");
+
+    let diary = serde_json::json!({ "working_minutes": 30, "text": "Checked the robot" });
+    let (status, body) = send(
+        &app,
+        request("POST", "/diary", &token("Viewer"), Some(diary)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], "Insufficient permissions");
+
+    let (status, _) = send(
+        &app,
+        request("GET", "/robot/notifications", &token("Viewer"), None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(
+        &app,
+        request("GET", "/robot/notifications", &token("Guest"), None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "Unknown roles hold nothing");
+
+    let (status, _) = send(
+        &app,
+        request(
+            "PUT",
+            "/roles/Viewer/permissions",
+            &token("Operator"),
+            Some(serde_json::json!({ "permissions": [] })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_admin_grants_permissions_to_custom_role() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_admin_grants_permissions_to_custom_role: {e}");
+            return;
+        }
+    };
+
+    // A role no other test run uses
+    let role = format!("Dispatcher-{}", Uuid::new_v4().simple());
+    let dispatcher = token(&role);
+    let admin = token("Admin");
+    let route = serde_json::json!({ "start": "home", "destination": "kitchen" });
+
+    let (status, _) = send(
+        &app,
+        request("POST", "/routes", &dispatcher, Some(route.clone())),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let uri = format!("/roles/{role}/permissions");
+    let (status, body) = send(
+        &app,
+        request(
+            "PUT",
+            &uri,
+            &admin,
+            Some(serde_json::json!({ "permissions": ["queue.manage", "robot.fly"] })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Unknown permission: robot.fly");
+
+    let (status, body) = send(
+        &app,
+        request(
+            "PUT",
+            &uri,
+            &admin,
+            Some(serde_json::json!({ "permissions": ["queue.manage", "robot.view", "queue.manage"] })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, serde_json::json!(["queue.manage", "robot.view"]));
+
+    let (status, created) = send(
+        &app,
+        request("POST", "/routes", &dispatcher, Some(route.clone())),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = send(&app, request("POST", "/drive/lock", &dispatcher, None)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "robot.drive was not granted");
+
+    // The role can be assigned to users; unknown roles cannot.
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, name, email, password_hash, role) VALUES ($1, 'Dispatcher', $2, 'x', 'Viewer')",
+    )
+    .bind(user_id)
+    .bind(format!("dispatcher-{user_id}@example.com"))
+    .execute(&app.db)
+    .await
+    .unwrap();
+    let (status, body) = send(
+        &app,
+        request(
+            "POST",
+            "/user",
+            &admin,
+            Some(serde_json::json!({ "id": user_id, "role": "Nobody" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Unknown role: Nobody");
+    let (status, _) = send(
+        &app,
+        request(
+            "POST",
+            "/user",
+            &admin,
+            Some(serde_json::json!({ "id": user_id, "role": role })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let stale_token = common::token(&user_id.to_string(), "Dispatcher", "Viewer");
+    let (status, _) = send(
+        &app,
+        request("POST", "/routes/optimize", &stale_token, None),
+    )
+    .await;
+    assert_ne!(status, StatusCode::FORBIDDEN, "The database role applies");
+
+    let (_, body) = send(&app, request("GET", "/permissions", &admin, None)).await;
+    assert_eq!(
+        body["roles"][&role],
+        serde_json::json!(["queue.manage", "robot.view"])
+    );
+
+    // Revoking applies to the next request.
+    let (status, _) = send(
+        &app,
+        request(
+            "PUT",
+            &uri,
+            &admin,
+            Some(serde_json::json!({ "permissions": ["robot.view"] })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let route_id = created["id"].as_str().unwrap();
+    let (status, _) = send(
+        &app,
+        request("DELETE", &format!("/routes/{route_id}"), &dispatcher, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(
+        &app,
+        request("DELETE", &format!("/routes/{route_id}"), &admin, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, events) = send(
+        &app,
+        request(
+            "GET",
+            &format!("/audit?action=role.permissions_change&target_id={role}"),
+            &admin,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let events = events.as_array().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events[0]["before"],
+        serde_json::json!(["queue.manage", "robot.view"])
+    );
+    assert_eq!(events[0]["after"], serde_json::json!(["robot.view"]));
+}
+
+#[tokio::test]
+async fn test_user_and_audit_permissions_work_without_admin_role() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_user_and_audit_permissions_work_without_admin_role: {e}");
+            return;
+        }
+    };
+
+    let role = format!("Support-{}", Uuid::new_v4().simple());
+    let (status, _) = send(
+        &app,
+        request(
+            "PUT",
+            &format!("/roles/{role}/permissions"),
+            &token("Admin"),
+            Some(serde_json::json!({ "permissions": ["users.manage", "audit.view"] })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let support = token(&role);
+
+    for uri in ["/users", "/user", "/lockouts", "/audit?limit=1"] {
+        let (status, body) = send(&app, request("GET", uri, &support, None)).await;
+        assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+    }
+    let (status, _) = send(&app, request("GET", "/audit", &token("Operator"), None)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The mapping itself stays admin-only
+    let (status, body) = send(&app, request("GET", "/permissions", &support, None)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], "Admin access required");
+
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, name, email, password_hash, role) VALUES ($1, 'Supported', $2, 'x', 'Viewer')",
+    )
+    .bind(user_id)
+    .bind(format!("supported-{user_id}@example.com"))
+    .execute(&app.db)
+    .await
+    .unwrap();
+    let (status, body) = send(
+        &app,
+        request(
+            "POST",
+            "/user",
+            &support,
+            Some(serde_json::json!({ "id": user_id, "role": "Operator" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["role"], "Operator");
+
+    // Admin accounts and the Admin role need an admin
+    let (status, body) = send(
+        &app,
+        request(
+            "POST",
+            "/user",
+            &support,
+            Some(serde_json::json!({ "id": user_id, "role": "Admin" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], "Admin access required");
+
+    let admin_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, name, email, password_hash, role) VALUES ($1, 'Other Admin', $2, 'x', 'Admin')",
+    )
+    .bind(admin_id)
+    .bind(format!("other-admin-{admin_id}@example.com"))
+    .execute(&app.db)
+    .await
+    .unwrap();
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            "/user",
+            &support,
+            Some(serde_json::json!({ "id": admin_id })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            "/user",
+            &support,
+            Some(serde_json::json!({ "id": user_id })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_former_admin_routes_are_granted_by_permission() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_former_admin_routes_are_granted_by_permission: {e}");
+            return;
+        }
+    };
+
+    let endpoints = [
+        ("nodes.manage", "/nodes/all"),
+        ("nodes.manage", "/graph/edges"),
+        ("robot.keys", "/robots/teletable/keys"),
+        ("robot.debug", "/robot/debug"),
+        ("routes.history", "/routes/history"),
+        ("routes.history", "/routes/stats"),
+        ("diary.settings", "/diary/settings"),
+    ];
+
+    let role = format!("Maintainer-{}", Uuid::new_v4().simple());
+    let maintainer = token(&role);
+    for (permission, uri) in endpoints {
+        let (status, body) = send(&app, request("GET", uri, &maintainer, None)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{uri}");
+        assert_eq!(body["permission"], permission, "{uri}");
+    }
+
+    let granted: Vec<&str> = endpoints.iter().map(|(p, _)| *p).collect();
+    let (status, _) = send(
+        &app,
+        request(
+            "PUT",
+            &format!("/roles/{role}/permissions"),
+            &token("Admin"),
+            Some(serde_json::json!({ "permissions": granted })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    for (_, uri) in endpoints {
+        let (status, body) = send(&app, request("GET", uri, &maintainer, None)).await;
+        assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+    }
+
+    // Telemetry follows the notification history
+    let (status, body) = send(&app, request("GET", "/robot/telemetry", &maintainer, None)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["permission"], "robot.view");
+    let (status, _) = send(
+        &app,
+        request("GET", "/robot/telemetry", &token("Viewer"), None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,347 @@
+use axum::http::StatusCode;
+use backend::auth::models::{LoginResponse, RegisterRequest};
+use uuid::Uuid;
+
+mod common;
+
+use common::{post_json, request, send};
+
+/// Register a fresh account and return its email
+async fn register(app: &common::TestApp) -> String {
+    let email = format!("profile-{}@example.com", Uuid::new_v4());
+    let register = RegisterRequest {
+        name: "Profile User".into(),
+        email: email.clone(),
+        password: "password123".into(),
+        fingerprint_data: None,
+    };
+    let (status, _) = send(
+        app,
+        post_json("/register", serde_json::to_value(&register).unwrap()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    email
+}
+
+async fn login(app: &common::TestApp, email: &str, password: &str) -> Option<LoginResponse> {
+    let (status, body) = send(
+        app,
+        post_json(
+            "/login",
+            serde_json::json!({ "email": email, "password": password }),
+        ),
+    )
+    .await;
+    (status == StatusCode::OK).then(|| serde_json::from_value(body).unwrap())
+}
+
+#[tokio::test]
+async fn test_update_profile_reverifies_new_email() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_update_profile_reverifies_new_email: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let session = login(&app, &email, "password123").await.unwrap();
+    sqlx::query("UPDATE users SET email_verified_at = NOW() WHERE email = $1")
+        .bind(&email)
+        .execute(&app.db)
+        .await
+        .unwrap();
+
+    let (status, user) = send(
+        &app,
+        request(
+            "PATCH",
+            "/me",
+            &session.token,
+            Some(serde_json::json!({ "name": "Renamed Myself" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["name"], "Renamed Myself");
+    assert_eq!(user["email_verified"], true);
+
+    let (status, body) = send(
+        &app,
+        request(
+            "PATCH",
+            "/me",
+            &session.token,
+            Some(serde_json::json!({ "name": "  " })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Name cannot be empty");
+
+    let taken = register(&app).await;
+    let (status, body) = send(
+        &app,
+        request(
+            "PATCH",
+            "/me",
+            &session.token,
+            Some(serde_json::json!({ "email": taken, "current_password": "password123" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "User with this email already exists");
+
+    let new_email = format!("moved-{}@example.com", Uuid::new_v4());
+    let (status, body) = send(
+        &app,
+        request(
+            "PATCH",
+            "/me",
+            &session.token,
+            Some(serde_json::json!({ "email": new_email })),
+        ),
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::FORBIDDEN,
+        "A token alone cannot move the account"
+    );
+    assert_eq!(
+        body["error"],
+        "Current password is required to change the email address"
+    );
+
+    let (status, body) = send(
+        &app,
+        request(
+            "PATCH",
+            "/me",
+            &session.token,
+            Some(serde_json::json!({ "email": new_email, "current_password": "wrong" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], "Current password is incorrect");
+
+    let (status, user) = send(
+        &app,
+        request(
+            "PATCH",
+            "/me",
+            &session.token,
+            Some(serde_json::json!({ "email": new_email, "current_password": "password123" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["email"], new_email.as_str());
+    assert_eq!(user["email_verified"], false);
+    let notice = app.last_mail_to(&email).expect("notice to the old address");
+    assert!(notice.contains(&new_email), "{notice}");
+
+    let mail = app.last_mail_to(&new_email).expect("verification mail");
+    let link = "http://localhost/verify-email?token=";
+    let start = mail.find(link).unwrap() + link.len();
+    let token = mail[start..].split_whitespace().next().unwrap();
+    let (status, user) = send(
+        &app,
+        post_json("/email/verify", serde_json::json!({ "token": token })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["email_verified"], true);
+
+    let (status, me) = send(&app, request("GET", "/me", &session.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(me["email"], new_email.as_str());
+}
+
+#[tokio::test]
+async fn test_change_password_revokes_other_sessions() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_change_password_revokes_other_sessions: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let current = login(&app, &email, "password123").await.unwrap();
+    let other = login(&app, &email, "password123").await.unwrap();
+
+    let (status, body) = send(
+        &app,
+        request(
+            "POST",
+            "/me/password",
+            &current.token,
+            Some(serde_json::json!({
+                "current_password": "wrong-password",
+                "new_password": "new-password456"
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"], "Current password is incorrect");
+
+    let (status, _) = send(
+        &app,
+        request(
+            "POST",
+            "/me/password",
+            &current.token,
+            Some(serde_json::json!({
+                "current_password": "password123",
+                "new_password": "new-password456"
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, request("GET", "/me", &current.token, None)).await;
+    assert_eq!(
+        status,
+        StatusCode::OK,
+        "The changing session stays signed in"
+    );
+    let (status, body) = send(&app, request("GET", "/me", &other.token, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "Session has been revoked");
+    let (status, _) = send(
+        &app,
+        post_json(
+            "/token/refresh",
+            serde_json::json!({ "refresh_token": other.refresh_token }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    assert!(login(&app, &email, "password123").await.is_none());
+    assert!(login(&app, &email, "new-password456").await.is_some());
+}
+
+async fn create_diary_entry(app: &common::TestApp, email: &str, text: &str) {
+    sqlx::query(
+        "INSERT INTO diary_entries (id, owner, working_minutes, text) SELECT $1, id, 30, $2 FROM users WHERE email = $3",
+    )
+    .bind(Uuid::new_v4())
+    .bind(text)
+    .bind(email)
+    .execute(&app.db)
+    .await
+    .unwrap();
+}
+
+async fn diary_entries_with_text(app: &common::TestApp, text: &str) -> Vec<Option<Uuid>> {
+    sqlx::query_scalar("SELECT owner FROM diary_entries WHERE text = $1")
+        .bind(text)
+        .fetch_all(&app.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_delete_account_deletes_diary_entries() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_delete_account_deletes_diary_entries: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let session = login(&app, &email, "password123").await.unwrap();
+    let text = format!("entry of {email}");
+    create_diary_entry(&app, &email, &text).await;
+
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            "/me",
+            &session.token,
+            Some(serde_json::json!({ "password": "wrong-password" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            "/me",
+            &session.token,
+            Some(serde_json::json!({ "password": "password123" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    assert!(diary_entries_with_text(&app, &text).await.is_empty());
+    assert!(login(&app, &email, "password123").await.is_none());
+    let (status, _) = send(&app, request("GET", "/me", &session.token, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_delete_account_can_retain_diary_entries() {
+    let app = match common::setup_test_app_with(|config| {
+        config.retain_diary_on_account_deletion = true;
+    })
+    .await
+    {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_delete_account_can_retain_diary_entries: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let session = login(&app, &email, "password123").await.unwrap();
+    let text = format!("entry of {email}");
+    create_diary_entry(&app, &email, &text).await;
+
+    let (status, _) = send(
+        &app,
+        request(
+            "DELETE",
+            "/me",
+            &session.token,
+            Some(serde_json::json!({ "password": "password123" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(diary_entries_with_text(&app, &text).await, [None]);
+
+    // Retained entries keep their visibility, which defaults to `team`
+    let colleague = login(&app, &register(&app).await, "password123")
+        .await
+        .unwrap();
+    let (status, all) = send(
+        &app,
+        request("GET", "/diary/all?limit=200", &colleague.token, None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let entry = all["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["text"] == text.as_str())
+        .expect("retained entry is listed");
+    assert_eq!(entry["owner"], "Deleted user");
+}
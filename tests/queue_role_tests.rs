@@ -17,9 +17,7 @@ async fn test_admin_can_manage_queue() {
     };
 
     // 1. Create Admin Token
-    let admin_token =
-        backend::auth::security::create_jwt("admin_id", "Admin User", "Admin", "test_secret", 1)
-            .unwrap();
+    let admin_token = common::token("admin_id", "Admin User", "Admin");
     let auth_header = format!("Bearer {admin_token}");
 
     // 2. Add Route to Queue (POST /routes)
@@ -127,9 +125,7 @@ async fn test_operator_cannot_manage_queue() {
         }
     };
 
-    let operator_token =
-        backend::auth::security::create_jwt("op_id", "Operator User", "Operator", "test_secret", 1)
-            .unwrap();
+    let operator_token = common::token("op_id", "Operator User", "Operator");
     let auth_header = format!("Bearer {operator_token}");
 
     // Try to Add Route
@@ -155,3 +151,133 @@ async fn test_operator_cannot_manage_queue() {
 
     assert_eq!(response.status(), StatusCode::FORBIDDEN);
 }
+
+#[tokio::test]
+async fn test_queue_changes_are_persisted() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_queue_changes_are_persisted: {e}");
+            return;
+        }
+    };
+
+    let admin_token = common::token("admin_id", "Admin User", "Admin");
+    let auth_header = format!("Bearer {admin_token}");
+
+    let payload = serde_json::json!({
+        "start": "home",
+        "destination": "office"
+    });
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/routes")
+                .method("POST")
+                .header("Authorization", &auth_header)
+                .header("Content-Type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let queued_route: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let route_id = uuid::Uuid::parse_str(queued_route["id"].as_str().unwrap()).unwrap();
+
+    let status: String = sqlx::query_scalar("SELECT status FROM route_queue WHERE id = $1")
+        .bind(route_id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(status, "queued");
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/routes/{route_id}"))
+                .method("DELETE")
+                .header("Authorization", &auth_header)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let status: String = sqlx::query_scalar("SELECT status FROM route_queue WHERE id = $1")
+        .bind(route_id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(status, "cancelled");
+}
+
+#[tokio::test]
+async fn test_rehydrate_restores_queue_and_active_route() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_rehydrate_restores_queue_and_active_route: {e}");
+            return;
+        }
+    };
+
+    let queued = backend::robot::models::QueuedRoute {
+        id: uuid::Uuid::new_v4(),
+        start: "kitchen".to_string(),
+        destination: "office".to_string(),
+        added_at: chrono::Utc::now(),
+        added_by: "Admin User".to_string(),
+        robot_id: None,
+    };
+    let active = backend::robot::models::QueuedRoute {
+        id: uuid::Uuid::new_v4(),
+        start: "home".to_string(),
+        destination: "kitchen".to_string(),
+        added_at: chrono::Utc::now(),
+        added_by: "Admin User".to_string(),
+        robot_id: None,
+    };
+
+    backend::robot::route_store::enqueue_back(&app.db, &queued)
+        .await
+        .unwrap();
+    backend::robot::route_store::insert_dispatched(
+        &app.db,
+        &active,
+        backend::robot::state::DEFAULT_ROBOT_ID,
+    )
+    .await
+    .unwrap();
+
+    let restored_state = backend::SharedRobotState::new();
+    backend::robot::route_store::rehydrate(&app.db, &restored_state)
+        .await
+        .unwrap();
+
+    let queue = restored_state.queue.read().await;
+    assert!(
+        queue.iter().any(|r| r.id == queued.id),
+        "Queued route should be restored into the in-memory queue"
+    );
+
+    let restored_robot = restored_state
+        .robot(backend::robot::state::DEFAULT_ROBOT_ID)
+        .await
+        .unwrap();
+    let restored_active = restored_robot.active_route.read().await;
+    assert!(
+        restored_active.is_some(),
+        "Dispatched route should be restored"
+    );
+}
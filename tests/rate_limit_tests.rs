@@ -0,0 +1,199 @@
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use backend::rate_limit;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+async fn send(
+    app: &common::TestApp,
+    request: Request<Body>,
+) -> (StatusCode, Option<String>, serde_json::Value) {
+    let response = app.router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        retry_after,
+        serde_json::from_slice(&bytes).unwrap_or_default(),
+    )
+}
+
+fn get_from(uri: &str, ip: &str) -> Request<Body> {
+    common::via_proxy(Request::builder().uri(uri).body(Body::empty()).unwrap(), ip)
+}
+
+fn request_as(method: &str, uri: &str, token: &str) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Authorization", format!("Bearer {token}"))
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// A token for a user no other test run has used
+fn token(role: &str) -> String {
+    common::token(&Uuid::new_v4().to_string(), "Limited User", role)
+}
+
+/// A client IP no other test run has used
+fn random_ip() -> String {
+    let bytes = Uuid::new_v4().into_bytes();
+    format!("10.{}.{}.{}", bytes[0], bytes[1], bytes[2])
+}
+
+#[tokio::test]
+async fn test_route_budget_per_client_ip() {
+    let app = match common::setup_test_app_with(|config| {
+        config.rate_limit_routes = rate_limit::parse_route_budgets("GET /diary/all=3/60");
+    })
+    .await
+    {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_route_budget_per_client_ip: {e}");
+            return;
+        }
+    };
+
+    let ip = random_ip();
+    for _ in 0..3 {
+        let (status, _, _) = send(&app, get_from("/diary/all", &ip)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, retry_after, body) = send(&app, get_from("/diary/all", &ip)).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: i64 = retry_after.expect("Retry-After header").parse().unwrap();
+    assert!((1..=20).contains(&retry_after));
+    assert_eq!(body["retry_after_seconds"], retry_after);
+    assert_eq!(body["error"], "Too many requests. Please try again later.");
+
+    let (status, _, _) = send(&app, get_from("/diary/all", &random_ip())).await;
+    assert_eq!(status, StatusCode::OK, "Other IPs are unaffected");
+
+    let (status, _, _) = send(&app, get_from("/", &ip)).await;
+    assert_eq!(status, StatusCode::OK, "Other routes are unaffected");
+}
+
+#[tokio::test]
+async fn test_forwarded_headers_from_untrusted_peers_do_not_reset_the_budget() {
+    let app = match common::setup_test_app_with(|config| {
+        config.rate_limit_routes = rate_limit::parse_route_budgets("GET /diary/all=2/60");
+    })
+    .await
+    {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!(
+                "Skipping test_forwarded_headers_from_untrusted_peers_do_not_reset_the_budget: {e}"
+            );
+            return;
+        }
+    };
+
+    // A direct client that claims a new address on every request
+    let peer: std::net::IpAddr = random_ip().parse().unwrap();
+    let spoofed = |header: &str| {
+        Request::builder()
+            .uri("/diary/all")
+            .header(header, random_ip())
+            .extension(axum::extract::ConnectInfo(std::net::SocketAddr::new(
+                peer, 50000,
+            )))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    for header in ["X-Real-IP", "X-Forwarded-For"] {
+        let (status, _, _) = send(&app, spoofed(header)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, _, _) = send(&app, spoofed("X-Forwarded-For")).await;
+    assert_eq!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS,
+        "Charged to the socket address"
+    );
+}
+
+#[tokio::test]
+async fn test_role_budget_is_shared_across_routes() {
+    let app = match common::setup_test_app_with(|config| {
+        config.rate_limit_roles = rate_limit::parse_role_budgets("Viewer=2/60,Admin=100/60");
+    })
+    .await
+    {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_role_budget_is_shared_across_routes: {e}");
+            return;
+        }
+    };
+
+    let viewer = token("Viewer");
+    let (status, _, _) = send(&app, request_as("GET", "/nodes", &viewer)).await;
+    assert_ne!(status, StatusCode::TOO_MANY_REQUESTS);
+    let (status, _, _) = send(&app, request_as("GET", "/robots", &viewer)).await;
+    assert_ne!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    // WebSocket upgrades identify the caller by the query token.
+    let request = Request::builder()
+        .uri(format!("/ws/robot/events?token={viewer}"))
+        .body(Body::empty())
+        .unwrap();
+    let (status, retry_after, _) = send(&app, request).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(retry_after.is_some());
+
+    let admin = token("Admin");
+    for _ in 0..5 {
+        let (status, _, _) = send(&app, request_as("GET", "/nodes", &admin)).await;
+        assert_ne!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    // No `anonymous` budget: callers without a token are not limited.
+    let ip = random_ip();
+    for _ in 0..5 {
+        let (status, _, _) = send(&app, get_from("/diary/all", &ip)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_route_rules_match_path_templates() {
+    let app = match common::setup_test_app_with(|config| {
+        config.rate_limit_routes = rate_limit::parse_route_budgets("DELETE /routes/{id}=1/60");
+    })
+    .await
+    {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_route_rules_match_path_templates: {e}");
+            return;
+        }
+    };
+
+    let operator = token("Operator");
+    let (status, _, _) = send(&app, request_as("DELETE", "/routes/1", &operator)).await;
+    assert_ne!(status, StatusCode::TOO_MANY_REQUESTS);
+    let (status, _, _) = send(&app, request_as("DELETE", "/routes/2", &operator)).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    let (status, _, _) = send(&app, request_as("DELETE", "/routes/1", &token("Operator"))).await;
+    assert_ne!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS,
+        "Budgets are per user"
+    );
+}
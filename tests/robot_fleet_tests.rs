@@ -0,0 +1,243 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::robot::models::RobotCommand;
+
+mod common;
+
+use common::send;
+
+const FLEET: &[(&str, &str)] = &[("alpha", "alpha_key"), ("bravo", "bravo_key")];
+
+fn auth_header(role: &str) -> String {
+    let token = common::token(
+        &uuid::Uuid::new_v4().to_string(),
+        &format!("{role} User"),
+        role,
+    );
+    format!("Bearer {token}")
+}
+
+fn user_request(method: &str, uri: &str, body: Option<serde_json::Value>) -> Request<Body> {
+    let builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Authorization", auth_header("Admin"))
+        .header("Content-Type", "application/json");
+    match body {
+        Some(json) => builder.body(Body::from(json.to_string())).unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    }
+}
+
+fn robot_request(uri: &str, api_key: &str, body: serde_json::Value) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .header("X-Api-Key", api_key)
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn idle_state(position: &str) -> serde_json::Value {
+    serde_json::json!({
+        "systemHealth": "OK",
+        "batteryLevel": 90,
+        "driveMode": "IDLE",
+        "cargoStatus": "EMPTY",
+        "currentPosition": position,
+    })
+}
+
+#[tokio::test]
+async fn test_robot_api_keys_identify_robots() {
+    let app = match common::setup_fleet_test_app(FLEET).await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_robot_api_keys_identify_robots: {e}");
+            return;
+        }
+    };
+
+    let (status, _) = send(
+        &app,
+        robot_request("/table/state", "bravo_key", idle_state("kitchen")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(
+        &app,
+        robot_request("/table/state", "test_robot_api_key", idle_state("home")),
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::UNAUTHORIZED,
+        "The legacy key is unused once ROBOT_API_KEYS is set"
+    );
+
+    let alpha = app.state.robot_state.robot("alpha").await.unwrap();
+    let bravo = app.state.robot_state.robot("bravo").await.unwrap();
+    assert!(alpha.current_state.read().await.is_none());
+    assert_eq!(
+        bravo
+            .current_state
+            .read()
+            .await
+            .as_ref()
+            .unwrap()
+            .current_position,
+        "kitchen"
+    );
+
+    let (status, body) = send(
+        &app,
+        robot_request(
+            "/table/event",
+            "alpha_key",
+            serde_json::json!({ "priority": "INFO", "message": "Door open" }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["notification"]["robotId"], "alpha");
+
+    let (status, robots) = send(&app, user_request("GET", "/robots", None)).await;
+    assert_eq!(status, StatusCode::OK);
+    let robots = robots.as_array().unwrap();
+    assert_eq!(robots.len(), 2);
+    assert_eq!(robots[0]["id"], "alpha");
+    assert_eq!(robots[0]["connected"], false);
+    assert_eq!(robots[1]["id"], "bravo");
+    assert_eq!(robots[1]["connected"], true);
+    assert_eq!(robots[1]["position"], "kitchen");
+}
+
+#[tokio::test]
+async fn test_per_robot_endpoints_require_robot_id_in_fleet() {
+    let app = match common::setup_fleet_test_app(FLEET).await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_per_robot_endpoints_require_robot_id_in_fleet: {e}");
+            return;
+        }
+    };
+
+    let (status, _) = send(&app, user_request("GET", "/robot/debug", None)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(
+        &app,
+        user_request("GET", "/robot/debug?robot_id=charlie", None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, debug) = send(
+        &app,
+        user_request("GET", "/robot/debug?robot_id=bravo", None),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(debug["robotId"], "bravo");
+
+    let (status, body) = send(
+        &app,
+        user_request(
+            "POST",
+            "/routes",
+            Some(serde_json::json!({
+                "start": "home",
+                "destination": "kitchen",
+                "robot_id": "charlie"
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "unknown_robot");
+}
+
+#[tokio::test]
+async fn test_queue_dispatches_to_pinned_and_idle_robots() {
+    let app = match common::setup_fleet_test_app(FLEET).await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_queue_dispatches_to_pinned_and_idle_robots: {e}");
+            return;
+        }
+    };
+
+    let alpha = app.state.robot_state.robot("alpha").await.unwrap();
+    let bravo = app.state.robot_state.robot("bravo").await.unwrap();
+    let mut alpha_rx = alpha.command_sender.subscribe();
+    let mut bravo_rx = bravo.command_sender.subscribe();
+
+    for (robot_id, key) in FLEET {
+        let (status, _) = send(&app, robot_request("/table/state", key, idle_state("home"))).await;
+        assert_eq!(status, StatusCode::OK, "{robot_id} state update");
+    }
+
+    // Pinned to bravo: alpha must not take it even though it sorts first.
+    let (status, pinned) = send(
+        &app,
+        user_request(
+            "POST",
+            "/routes",
+            Some(serde_json::json!({
+                "start": "home",
+                "destination": "kitchen",
+                "robot_id": "bravo"
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(pinned["robot_id"], "bravo");
+
+    assert!(alpha_rx.try_recv().is_err());
+    match bravo_rx.try_recv() {
+        Ok(RobotCommand::Navigate { destination, .. }) => assert_eq!(destination, "kitchen"),
+        other => panic!("Expected bravo to receive NAVIGATE, got {other:?}"),
+    }
+
+    // Unpinned: goes to the remaining idle robot.
+    let (status, _) = send(
+        &app,
+        user_request(
+            "POST",
+            "/routes",
+            Some(serde_json::json!({ "start": "home", "destination": "office" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    match alpha_rx.try_recv() {
+        Ok(RobotCommand::Navigate { destination, .. }) => assert_eq!(destination, "office"),
+        other => panic!("Expected alpha to receive NAVIGATE, got {other:?}"),
+    }
+    assert!(app.state.robot_state.queue.read().await.is_empty());
+
+    let dispatched_to: Option<String> =
+        sqlx::query_scalar("SELECT dispatched_robot_id FROM route_queue WHERE id = $1")
+            .bind(uuid::Uuid::parse_str(pinned["id"].as_str().unwrap()).unwrap())
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(dispatched_to.as_deref(), Some("bravo"));
+
+    let (_, history) = send(
+        &app,
+        user_request("GET", "/routes/history?robot_id=bravo&limit=500", None),
+    )
+    .await;
+    assert!(history
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|r| r["id"] == pinned["id"]));
+}
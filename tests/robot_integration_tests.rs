@@ -3,12 +3,15 @@ use axum::{
     http::{Request, StatusCode},
 };
 use chrono::{Duration as ChronoDuration, Utc};
-use futures::SinkExt;
+use futures::{SinkExt, StreamExt};
 use tokio::{
     net::TcpListener,
     time::{timeout, Duration},
 };
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, Message},
+};
 use tower::ServiceExt;
 use uuid::Uuid;
 use wiremock::matchers::{method, path};
@@ -54,18 +57,16 @@ async fn insert_test_user(
 }
 
 #[tokio::test]
-async fn test_get_nodes_returns_static_app_state_nodes() {
+async fn test_get_nodes_returns_registry_nodes() {
     let app = match common::setup_test_app().await {
         Ok(app) => app,
         Err(e) => {
-            eprintln!("Skipping test_get_nodes_returns_static_app_state_nodes: {e}");
+            eprintln!("Skipping test_get_nodes_returns_registry_nodes: {e}");
             return;
         }
     };
 
-    let token =
-        backend::auth::security::create_jwt("user_id", "Test User", "user", "test_secret", 1)
-            .unwrap();
+    let token = common::token("user_id", "Test User", "user");
     let auth_header = format!("Bearer {token}");
 
     let response = app
@@ -105,6 +106,7 @@ async fn test_update_robot_state_webhook() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     // 1. Prepare payload
     let payload = serde_json::json!({
@@ -137,7 +139,7 @@ async fn test_update_robot_state_webhook() {
     assert_eq!(response.status(), StatusCode::OK);
 
     // 3. Verify state in memory
-    let current_state = app.state.robot_state.current_state.read().await;
+    let current_state = robot.current_state.read().await;
     assert!(current_state.is_some());
     let s = current_state.as_ref().unwrap();
     assert_eq!(s.battery_level, 85);
@@ -152,28 +154,15 @@ async fn test_get_robot_debug_requires_admin_and_merges_robot_status() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     let admin_id = Uuid::new_v4();
     let viewer_id = Uuid::new_v4();
     insert_test_user(&app, admin_id, "Admin").await.unwrap();
     insert_test_user(&app, viewer_id, "Viewer").await.unwrap();
 
-    let admin_token = backend::auth::security::create_jwt(
-        &admin_id.to_string(),
-        "Admin User",
-        "Admin",
-        "test_secret",
-        1,
-    )
-    .unwrap();
-    let viewer_token = backend::auth::security::create_jwt(
-        &viewer_id.to_string(),
-        "Viewer User",
-        "Viewer",
-        "test_secret",
-        1,
-    )
-    .unwrap();
+    let admin_token = common::token(&admin_id.to_string(), "Admin User", "Admin");
+    let viewer_token = common::token(&viewer_id.to_string(), "Viewer User", "Viewer");
 
     let mock_server = MockServer::start().await;
     Mock::given(method("GET"))
@@ -189,11 +178,11 @@ async fn test_get_robot_debug_requires_admin_and_merges_robot_status() {
         .await;
 
     {
-        let mut robot_url = app.state.robot_state.robot_url.write().await;
+        let mut robot_url = robot.robot_url.write().await;
         *robot_url = Some(mock_server.uri());
     }
     {
-        let mut current_state = app.state.robot_state.current_state.write().await;
+        let mut current_state = robot.current_state.write().await;
         *current_state = Some(backend::robot::models::RobotState {
             system_health: "OK".to_string(),
             battery_level: 92,
@@ -212,7 +201,7 @@ async fn test_get_robot_debug_requires_admin_and_merges_robot_status() {
         });
     }
     {
-        let mut last_update = app.state.robot_state.last_state_update.write().await;
+        let mut last_update = robot.last_state_update.write().await;
         *last_update = Some(Utc::now());
     }
 
@@ -278,25 +267,19 @@ async fn test_get_robot_debug_degrades_when_robot_status_unreachable() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     let admin_id = Uuid::new_v4();
     insert_test_user(&app, admin_id, "Admin").await.unwrap();
 
-    let admin_token = backend::auth::security::create_jwt(
-        &admin_id.to_string(),
-        "Admin User",
-        "Admin",
-        "test_secret",
-        1,
-    )
-    .unwrap();
+    let admin_token = common::token(&admin_id.to_string(), "Admin User", "Admin");
 
     {
-        let mut robot_url = app.state.robot_state.robot_url.write().await;
+        let mut robot_url = robot.robot_url.write().await;
         *robot_url = Some("http://127.0.0.1:9".to_string());
     }
     {
-        let mut current_state = app.state.robot_state.current_state.write().await;
+        let mut current_state = robot.current_state.write().await;
         *current_state = Some(backend::robot::models::RobotState {
             system_health: "OK".to_string(),
             battery_level: 76,
@@ -355,10 +338,11 @@ async fn test_manual_ws_operator_forwards_set_manual_speed_cap() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     let operator_id = Uuid::new_v4();
     {
-        let mut lock = app.state.robot_state.manual_lock.write().await;
+        let mut lock = robot.manual_lock.write().await;
         *lock = Some(backend::robot::state::LockInfo {
             holder_id: operator_id,
             holder_name: "Operator User".to_string(),
@@ -366,16 +350,9 @@ async fn test_manual_ws_operator_forwards_set_manual_speed_cap() {
         });
     }
 
-    let token = backend::auth::security::create_jwt(
-        &operator_id.to_string(),
-        "Operator User",
-        "Operator",
-        "test_secret",
-        1,
-    )
-    .unwrap();
+    let token = common::token(&operator_id.to_string(), "Operator User", "Operator");
 
-    let mut command_rx = app.state.robot_state.command_sender.subscribe();
+    let mut command_rx = robot.command_sender.subscribe();
     let ws_base = spawn_router_server(app.router.clone()).await;
     let (mut socket, _) = connect_async(format!("{ws_base}/ws/drive/manual?token={token}"))
         .await
@@ -417,18 +394,12 @@ async fn test_manual_ws_viewer_cannot_send_set_manual_speed_cap() {
             return;
         }
     };
+    let robot = app.robot().await;
 
     let viewer_id = Uuid::new_v4();
-    let token = backend::auth::security::create_jwt(
-        &viewer_id.to_string(),
-        "Viewer User",
-        "Viewer",
-        "test_secret",
-        1,
-    )
-    .unwrap();
+    let token = common::token(&viewer_id.to_string(), "Viewer User", "Viewer");
 
-    let mut command_rx = app.state.robot_state.command_sender.subscribe();
+    let mut command_rx = robot.command_sender.subscribe();
     let ws_base = spawn_router_server(app.router.clone()).await;
     let (mut socket, _) = connect_async(format!("{ws_base}/ws/drive/manual?token={token}"))
         .await
@@ -454,3 +425,172 @@ async fn test_manual_ws_viewer_cannot_send_set_manual_speed_cap() {
 
     let _ = socket.close(None).await;
 }
+
+#[tokio::test]
+async fn test_manual_ws_admin_navigate_rejects_unknown_nodes() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_manual_ws_admin_navigate_rejects_unknown_nodes: {e}");
+            return;
+        }
+    };
+    let robot = app.robot().await;
+
+    let token = common::token(&Uuid::new_v4().to_string(), "Admin User", "Admin");
+
+    let mut command_rx = robot.command_sender.subscribe();
+    let ws_base = spawn_router_server(app.router.clone()).await;
+    let (mut socket, _) = connect_async(format!("{ws_base}/ws/drive/manual?token={token}"))
+        .await
+        .unwrap();
+
+    for (start, destination) in [("home", "mars"), ("home", "kitchen")] {
+        socket
+            .send(Message::Text(
+                serde_json::json!({
+                    "command": "NAVIGATE",
+                    "start": start,
+                    "destination": destination
+                })
+                .to_string()
+                .into(),
+            ))
+            .await
+            .unwrap();
+    }
+
+    let forwarded = timeout(Duration::from_secs(2), command_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        forwarded,
+        backend::robot::models::RobotCommand::Navigate {
+            start: "home".to_string(),
+            destination: "kitchen".to_string(),
+        },
+        "The invalid NAVIGATE should be dropped before reaching the robot"
+    );
+
+    let _ = socket.close(None).await;
+}
+
+#[tokio::test]
+async fn test_robot_control_ws_rejects_unauthenticated_peers() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_robot_control_ws_rejects_unauthenticated_peers: {e}");
+            return;
+        }
+    };
+    let robot = app.robot().await;
+
+    let ws_base = spawn_router_server(app.router.clone()).await;
+    let url = format!("{ws_base}/ws/robot/control");
+
+    let err = connect_async(url.as_str())
+        .await
+        .expect_err("Connection without a key must be refused");
+    match err {
+        tokio_tungstenite::tungstenite::Error::Http(response) => {
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED)
+        }
+        other => panic!("Unexpected error: {other}"),
+    }
+
+    let mut wrong_key = url.as_str().into_client_request().unwrap();
+    wrong_key
+        .headers_mut()
+        .insert("X-Api-Key", "wrong_key".parse().unwrap());
+    assert!(connect_async(wrong_key).await.is_err());
+
+    // Header credential: the socket receives broadcast commands.
+    let mut with_header = url.as_str().into_client_request().unwrap();
+    with_header
+        .headers_mut()
+        .insert("X-Api-Key", "test_robot_api_key".parse().unwrap());
+    let (mut socket, _) = connect_async(with_header).await.unwrap();
+
+    // Give the server a moment to subscribe before broadcasting.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    robot
+        .command_sender
+        .send(backend::robot::models::RobotCommand::Cancel)
+        .unwrap();
+    let frame = timeout(Duration::from_secs(2), socket.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(frame.to_text().unwrap().contains("CANCEL"));
+    let _ = socket.close(None).await;
+
+    // Subprotocol credential for clients that cannot set headers.
+    let mut with_protocol = url.as_str().into_client_request().unwrap();
+    with_protocol.headers_mut().insert(
+        "Sec-WebSocket-Protocol",
+        "robot, test_robot_api_key".parse().unwrap(),
+    );
+    let (socket, response) = connect_async(with_protocol).await.unwrap();
+    assert_eq!(
+        response.headers().get("Sec-WebSocket-Protocol").unwrap(),
+        "robot"
+    );
+    drop(socket);
+}
+
+#[tokio::test]
+async fn test_register_robot_requires_api_key() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_register_robot_requires_api_key: {e}");
+            return;
+        }
+    };
+    let robot = app.robot().await;
+
+    let register = |api_key: Option<&str>| {
+        let mut builder = Request::builder()
+            .uri("/table/register")
+            .method("POST")
+            .header("Content-Type", "application/json");
+        if let Some(key) = api_key {
+            builder = builder.header("X-Api-Key", key);
+        }
+        let request = builder
+            .body(Body::from(serde_json::json!({ "port": 8080 }).to_string()))
+            .unwrap();
+        common::via_proxy(request, "10.0.0.42")
+    };
+
+    let response = app.router.clone().oneshot(register(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(register(Some("wrong_key")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(
+        robot.robot_url.read().await.is_none(),
+        "Rejected registrations must not change robot_url"
+    );
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(register(Some("test_robot_api_key")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        robot.robot_url.read().await.as_deref(),
+        Some("http://10.0.0.42:8080")
+    );
+}
@@ -0,0 +1,223 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::Config;
+use uuid::Uuid;
+
+mod common;
+
+use common::send;
+
+fn auth_header(role: &str) -> String {
+    let token = common::token(&Uuid::new_v4().to_string(), &format!("{role} User"), role);
+    format!("Bearer {token}")
+}
+
+fn admin_request(method: &str, uri: &str, role: &str) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Authorization", auth_header(role))
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"label":"bench unit"}"#))
+        .unwrap()
+}
+
+fn state_update(api_key: &str) -> Request<Body> {
+    let payload = serde_json::json!({
+        "systemHealth": "OK",
+        "batteryLevel": 75,
+        "driveMode": "IDLE",
+        "cargoStatus": "EMPTY",
+        "currentPosition": "home",
+    });
+    Request::builder()
+        .uri("/table/state")
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .header("X-Api-Key", api_key)
+        .body(Body::from(payload.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_issued_key_authenticates_robot() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_issued_key_authenticates_robot: {e}");
+            return;
+        }
+    };
+
+    let robot_id = format!("robot-{}", Uuid::new_v4().simple());
+    let (status, issued) = send(
+        &app,
+        admin_request("POST", &format!("/robots/{robot_id}/keys"), "Admin"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let api_key = issued["api_key"].as_str().unwrap().to_string();
+    assert!(api_key.starts_with(issued["key_prefix"].as_str().unwrap()));
+    assert_eq!(issued["label"], "bench unit");
+
+    let stored: String =
+        sqlx::query_scalar("SELECT key_hash FROM robot_api_keys WHERE id = $1::uuid")
+            .bind(issued["id"].as_str().unwrap())
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_ne!(stored, api_key, "Only the hash is stored");
+    assert_eq!(stored, backend::robot::key_store::hash_key(&api_key));
+
+    let (status, _) = send(&app, state_update(&api_key)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let robot = app
+        .state
+        .robot_state
+        .robot(&robot_id)
+        .await
+        .expect("Issuing a key adds the robot to the fleet");
+    assert_eq!(
+        robot
+            .current_state
+            .read()
+            .await
+            .as_ref()
+            .unwrap()
+            .battery_level,
+        75
+    );
+    assert!(
+        app.robot().await.current_state.read().await.is_none(),
+        "The default robot is untouched"
+    );
+
+    let (status, keys) = send(
+        &app,
+        admin_request("GET", &format!("/robots/{robot_id}/keys"), "Admin"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let keys = keys.as_array().unwrap();
+    assert_eq!(keys.len(), 1);
+    assert!(!keys[0]["last_used_at"].is_null());
+    assert!(keys[0].get("api_key").is_none());
+    assert!(keys[0].get("key_hash").is_none());
+}
+
+#[tokio::test]
+async fn test_robot_key_rotation() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_robot_key_rotation: {e}");
+            return;
+        }
+    };
+
+    let robot_id = format!("robot-{}", Uuid::new_v4().simple());
+    let keys_uri = format!("/robots/{robot_id}/keys");
+
+    let (_, old) = send(&app, admin_request("POST", &keys_uri, "Admin")).await;
+    let (status, new) = send(&app, admin_request("POST", &keys_uri, "Admin")).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, _) = send(&app, admin_request("POST", &keys_uri, "Admin")).await;
+    assert_eq!(status, StatusCode::CONFLICT, "At most two active keys");
+
+    let old_key = old["api_key"].as_str().unwrap();
+    let new_key = new["api_key"].as_str().unwrap();
+    for key in [old_key, new_key] {
+        let (status, _) = send(&app, state_update(key)).await;
+        assert_eq!(status, StatusCode::OK, "Both keys work during rotation");
+    }
+
+    let revoke_uri = format!("{keys_uri}/{}", old["id"].as_str().unwrap());
+    let (status, _) = send(&app, admin_request("DELETE", &revoke_uri, "Admin")).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, state_update(old_key)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send(&app, state_update(new_key)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&app, admin_request("DELETE", &revoke_uri, "Admin")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, admin_request("POST", &keys_uri, "Admin")).await;
+    assert_eq!(
+        status,
+        StatusCode::CREATED,
+        "Revoking frees a slot for the next key"
+    );
+}
+
+#[tokio::test]
+async fn test_robot_key_management_validation() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_robot_key_management_validation: {e}");
+            return;
+        }
+    };
+
+    let (status, _) = send(
+        &app,
+        admin_request("POST", "/robots/teletable/keys", "Operator"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send(
+        &app,
+        admin_request("POST", "/robots/bad%20id/keys", "Admin"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(&app, state_update("rk_not_a_real_key")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn test_default_robot_key_requires_dev_flag() {
+    let mut config = Config {
+        database_url: String::new(),
+        redis_url: String::new(),
+        jwt_secret: "test_secret".to_string(),
+        access_token_ttl_minutes: 15,
+        refresh_token_ttl_days: 30,
+        server_address: "127.0.0.1:0".to_string(),
+        robot_api_key: Some(backend::config::DEFAULT_ROBOT_API_KEY.to_string()),
+        robot_api_keys: Vec::new(),
+        allow_default_robot_key: false,
+        enforce_route_chaining: false,
+        app_base_url: "http://localhost".to_string(),
+        mail_transport: "file".to_string(),
+        smtp_url: None,
+        mail_from: "TeleTable <no-reply@localhost>".to_string(),
+        mail_dir: "./mail".to_string(),
+        require_email_verification: false,
+        require_admin_2fa: false,
+        retain_diary_on_account_deletion: false,
+        rate_limit_routes: Vec::new(),
+        rate_limit_roles: Vec::new(),
+        trusted_proxies: Vec::new(),
+    };
+    assert!(config.check_robot_keys().is_err());
+
+    config.allow_default_robot_key = true;
+    assert!(config.check_robot_keys().is_ok());
+
+    config.allow_default_robot_key = false;
+    config.robot_api_key = Some("a-real-secret".to_string());
+    assert!(config.check_robot_keys().is_ok());
+
+    config.robot_api_key = None;
+    assert!(config.robot_keys().is_empty());
+    assert!(config.check_robot_keys().is_ok());
+}
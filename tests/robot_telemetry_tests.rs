@@ -0,0 +1,264 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::robot::models::{RobotInfraredReading, RobotState};
+use backend::robot::telemetry_store;
+use chrono::{Duration, DurationRound, Utc};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+use common::send;
+
+fn auth_header() -> String {
+    let token = common::token(&Uuid::new_v4().to_string(), "Viewer User", "Viewer");
+    format!("Bearer {token}")
+}
+
+fn telemetry_request(uri: &str) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .method("GET")
+        .header("Authorization", auth_header())
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn sample(battery_level: u8, ir_front: bool) -> RobotState {
+    RobotState {
+        system_health: "OK".to_string(),
+        battery_level,
+        drive_mode: "DRIVING".to_string(),
+        cargo_status: "EMPTY".to_string(),
+        current_position: "home".to_string(),
+        last_node: None,
+        target_node: None,
+        gyroscope: None,
+        last_read_uuid: None,
+        lux: None,
+        infrared: Some(RobotInfraredReading {
+            front: Some(ir_front),
+            left: None,
+            right: None,
+        }),
+        voltage_v: Some(12.0),
+        current_a: Some(1.5),
+        power_w: Some(18.0),
+    }
+}
+
+fn unique_robot() -> (String, String) {
+    let id = format!("telemetry-{}", Uuid::new_v4().simple());
+    let key = format!("{id}-key");
+    (id, key)
+}
+
+#[tokio::test]
+async fn test_state_updates_are_sampled() {
+    let (robot_id, key) = unique_robot();
+    let app = match common::setup_fleet_test_app(&[(&robot_id, &key)]).await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_state_updates_are_sampled: {e}");
+            return;
+        }
+    };
+
+    for battery in [80, 79] {
+        let payload = serde_json::json!({
+            "systemHealth": "OK",
+            "batteryLevel": battery,
+            "driveMode": "IDLE",
+            "cargoStatus": "EMPTY",
+            "currentPosition": "home",
+            "powerW": 4.5,
+        });
+        let (status, _) = send(
+            &app,
+            Request::builder()
+                .uri("/table/state")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header("X-Api-Key", &key)
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let stored: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM robot_telemetry WHERE robot_id = $1")
+            .bind(&robot_id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(
+        stored, 1,
+        "Updates within the sample interval are not stored"
+    );
+
+    let (status, body) = send(
+        &app,
+        telemetry_request(&format!(
+            "/robot/telemetry?robot_id={robot_id}&fields=battery_level,power_w,lux&bucket=60"
+        )),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["robot_id"], robot_id.as_str());
+    assert_eq!(body["bucket_seconds"], 60);
+
+    let battery = body["series"]["battery_level"].as_array().unwrap();
+    assert_eq!(battery.len(), 1);
+    assert_eq!(battery[0]["avg"], 80.0);
+    assert_eq!(battery[0]["samples"], 1);
+    assert_eq!(body["series"]["power_w"][0]["max"], 4.5);
+    assert_eq!(
+        body["series"]["lux"],
+        serde_json::json!([]),
+        "Requested metrics without readings are empty"
+    );
+    assert!(body["series"].get("voltage_v").is_none());
+}
+
+#[tokio::test]
+async fn test_compaction_rolls_up_old_samples() {
+    let (robot_id, key) = unique_robot();
+    let app = match common::setup_fleet_test_app(&[(&robot_id, &key)]).await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_compaction_rolls_up_old_samples: {e}");
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    let old_minute = (now - Duration::days(3))
+        .duration_trunc(Duration::minutes(1))
+        .unwrap();
+    let recent = now - Duration::minutes(5);
+
+    for (at, battery, ir) in [
+        (old_minute, 90, true),
+        (old_minute + Duration::seconds(10), 80, false),
+        (recent, 70, false),
+    ] {
+        telemetry_store::record(&app.db, &robot_id, at, &sample(battery, ir))
+            .await
+            .unwrap();
+    }
+
+    telemetry_store::compact(&app.db, now).await.unwrap();
+
+    let raw: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM robot_telemetry WHERE robot_id = $1")
+        .bind(&robot_id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(raw, 1, "Only the recent sample stays raw");
+
+    let rollups: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM robot_telemetry_rollups WHERE robot_id = $1 AND metric = 'battery_level'",
+    )
+    .bind(&robot_id)
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(rollups, 1);
+
+    let from = (now - Duration::days(4)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let (status, body) = send(
+        &app,
+        telemetry_request(&format!(
+            "/robot/telemetry?robot_id={robot_id}&from={from}&fields=battery_level,ir_front&bucket=3600"
+        )),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let battery = body["series"]["battery_level"].as_array().unwrap();
+    assert_eq!(
+        battery.len(),
+        2,
+        "Rolled-up and raw samples form one series"
+    );
+    assert_eq!(battery[0]["samples"], 2);
+    assert_eq!(battery[0]["avg"], 85.0);
+    assert_eq!(battery[0]["min"], 80.0);
+    assert_eq!(battery[0]["max"], 90.0);
+    assert_eq!(battery[1]["avg"], 70.0);
+    assert_eq!(body["series"]["ir_front"][0]["avg"], 0.5);
+
+    // Rollups past their own retention are purged.
+    telemetry_store::record(
+        &app.db,
+        &robot_id,
+        now - Duration::days(telemetry_store::TELEMETRY_ROLLUP_RETENTION_DAYS + 1),
+        &sample(50, false),
+    )
+    .await
+    .unwrap();
+    telemetry_store::compact(&app.db, now).await.unwrap();
+    let rollups: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM robot_telemetry_rollups WHERE robot_id = $1")
+            .bind(&robot_id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(
+        rollups, 5,
+        "Only the three-day-old minute is kept, one row per reported metric"
+    );
+}
+
+#[tokio::test]
+async fn test_telemetry_query_validation() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_telemetry_query_validation: {e}");
+            return;
+        }
+    };
+
+    for uri in [
+        "/robot/telemetry?fields=battery_level,altitude",
+        "/robot/telemetry?fields=",
+        "/robot/telemetry?from=2026-10-17T12:00:00Z&to=2026-10-17T11:00:00Z",
+        "/robot/telemetry?bucket=0",
+        "/robot/telemetry?from=2026-01-01T00:00:00Z&to=2026-10-01T00:00:00Z&bucket=60",
+    ] {
+        let (status, _) = send(&app, telemetry_request(uri)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+    }
+
+    let (status, _) = send(&app, telemetry_request("/robot/telemetry?robot_id=nope")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = send(&app, telemetry_request("/robot/telemetry")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["bucket_seconds"], 288,
+        "24 hours in about 300 buckets by default"
+    );
+    assert_eq!(
+        body["series"].as_object().unwrap().len(),
+        telemetry_store::TELEMETRY_METRICS.len()
+    );
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/robot/telemetry")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
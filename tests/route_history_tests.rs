@@ -0,0 +1,169 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::robot::models::{QueuedRoute, RouteStatus};
+use backend::robot::route_store;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+fn admin_header() -> String {
+    let token = common::token("admin_id", "Admin User", "Admin");
+    format!("Bearer {token}")
+}
+
+fn route(start: &str, destination: &str, added_by: &str) -> QueuedRoute {
+    QueuedRoute {
+        id: Uuid::new_v4(),
+        start: start.to_string(),
+        destination: destination.to_string(),
+        added_at: chrono::Utc::now(),
+        added_by: added_by.to_string(),
+        robot_id: None,
+    }
+}
+
+async fn get_json(app: &common::TestApp, uri: &str, auth: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .method("GET")
+                .header("Authorization", auth)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_route_history_records_outcomes_and_filters_by_user() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_route_history_records_outcomes_and_filters_by_user: {e}");
+            return;
+        }
+    };
+
+    let user = format!("history-{}", Uuid::new_v4());
+    let completed = route("home", "kitchen", &user);
+    let cancelled = route("kitchen", "office", &user);
+    let pending = route("office", "home", &user);
+
+    route_store::enqueue_back(&app.db, &completed)
+        .await
+        .unwrap();
+    route_store::set_status(&app.db, completed.id, RouteStatus::Dispatched)
+        .await
+        .unwrap();
+    route_store::set_status(&app.db, completed.id, RouteStatus::Completed)
+        .await
+        .unwrap();
+
+    route_store::enqueue_back(&app.db, &cancelled)
+        .await
+        .unwrap();
+    route_store::cancel(&app.db, cancelled.id, "Admin User")
+        .await
+        .unwrap();
+
+    route_store::enqueue_back(&app.db, &pending).await.unwrap();
+
+    let (status, history) = get_json(
+        &app,
+        &format!("/routes/history?user={user}"),
+        &admin_header(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let entries = history.as_array().unwrap();
+    assert_eq!(entries.len(), 2, "Queued routes are not part of history");
+
+    let completed_entry = entries
+        .iter()
+        .find(|e| e["id"] == completed.id.to_string())
+        .unwrap();
+    assert_eq!(completed_entry["status"], "completed");
+    assert!(completed_entry["dispatched_at"].is_string());
+    assert!(completed_entry["finished_at"].is_string());
+    assert!(completed_entry["duration_seconds"].is_number());
+
+    let cancelled_entry = entries
+        .iter()
+        .find(|e| e["id"] == cancelled.id.to_string())
+        .unwrap();
+    assert_eq!(cancelled_entry["status"], "cancelled");
+    assert_eq!(cancelled_entry["cancelled_by"], "Admin User");
+
+    let (_, filtered) = get_json(
+        &app,
+        &format!("/routes/history?user={user}&status=cancelled"),
+        &admin_header(),
+    )
+    .await;
+    assert_eq!(filtered.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_route_stats_aggregates_completed_trips() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_route_stats_aggregates_completed_trips: {e}");
+            return;
+        }
+    };
+
+    let start = format!("stats-start-{}", Uuid::new_v4());
+    let trip = route(&start, "stats-destination", "Admin User");
+    route_store::enqueue_back(&app.db, &trip).await.unwrap();
+    route_store::set_status(&app.db, trip.id, RouteStatus::Dispatched)
+        .await
+        .unwrap();
+    route_store::set_status(&app.db, trip.id, RouteStatus::Completed)
+        .await
+        .unwrap();
+
+    let (status, stats) = get_json(&app, "/routes/stats", &admin_header()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(stats["completed"].as_i64().unwrap() >= 1);
+    assert!(stats["cancellation_rate"].is_number());
+    assert!(!stats["deliveries_per_day"].as_array().unwrap().is_empty());
+
+    let pair = stats["pairs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["start"] == start.as_str())
+        .expect("completed pair should appear in stats");
+    assert_eq!(pair["destination"], "stats-destination");
+    assert_eq!(pair["trips"], 1);
+}
+
+#[tokio::test]
+async fn test_route_history_requires_admin() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_route_history_requires_admin: {e}");
+            return;
+        }
+    };
+
+    let token = common::token("op_id", "Operator User", "Operator");
+    let (status, _) = get_json(&app, "/routes/history", &format!("Bearer {token}")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,387 @@
+use axum::http::StatusCode;
+use backend::auth::models::{
+    LoginResponse, RecoveryCodesResponse, RegisterRequest, TwoFactorChallenge,
+    TwoFactorSetupResponse,
+};
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+mod common;
+
+use common::{post_json, request, send};
+
+async fn register(app: &common::TestApp) -> String {
+    let email = format!("2fa-{}@example.com", Uuid::new_v4());
+    let register = RegisterRequest {
+        name: "Two Factor User".into(),
+        email: email.clone(),
+        password: "password123".into(),
+        fingerprint_data: None,
+    };
+    let (status, _) = send(
+        app,
+        post_json("/register", serde_json::to_value(&register).unwrap()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    email
+}
+
+async fn login(app: &common::TestApp, email: &str) -> (StatusCode, serde_json::Value) {
+    send(
+        app,
+        post_json(
+            "/login",
+            serde_json::json!({ "email": email, "password": "password123" }),
+        ),
+    )
+    .await
+}
+
+async fn login_challenge(app: &common::TestApp, email: &str) -> String {
+    let (status, body) = login(app, email).await;
+    assert_eq!(status, StatusCode::OK);
+    let challenge: TwoFactorChallenge =
+        serde_json::from_value(body).expect("login asks for the second factor");
+    assert!(challenge.two_factor_required);
+    challenge.challenge_token
+}
+
+async fn second_factor(
+    app: &common::TestApp,
+    challenge_token: &str,
+    code: &str,
+) -> (StatusCode, serde_json::Value) {
+    send(
+        app,
+        post_json(
+            "/login/2fa",
+            serde_json::json!({ "challenge_token": challenge_token, "code": code }),
+        ),
+    )
+    .await
+}
+
+/// The TOTP code for `steps` 30 second steps from now
+fn code(secret: &str, steps: i64) -> String {
+    let totp = TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        30,
+        Secret::Encoded(secret.to_string()).to_bytes().unwrap(),
+        None,
+        String::new(),
+    )
+    .unwrap();
+    let now = chrono::Utc::now().timestamp() + steps * 30;
+    totp.generate(now as u64)
+}
+
+/// Enroll the logged-in user; returns the secret and recovery codes.
+async fn enroll(app: &common::TestApp, token: &str) -> (String, Vec<String>) {
+    let (status, body) = send(
+        app,
+        request("POST", "/me/2fa/setup", token, Some(serde_json::json!({}))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let setup: TwoFactorSetupResponse = serde_json::from_value(body).unwrap();
+    assert!(setup.otpauth_url.starts_with("otpauth://totp/TeleTable"));
+
+    let (status, body) = send(
+        app,
+        request(
+            "POST",
+            "/me/2fa/verify",
+            token,
+            Some(serde_json::json!({ "code": code(&setup.secret, 0) })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let codes: RecoveryCodesResponse = serde_json::from_value(body).unwrap();
+    (setup.secret, codes.recovery_codes)
+}
+
+#[tokio::test]
+async fn test_two_factor_enrollment_and_login() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_two_factor_enrollment_and_login: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let (status, body) = login(&app, &email).await;
+    assert_eq!(status, StatusCode::OK);
+    let session: LoginResponse = serde_json::from_value(body).unwrap();
+
+    let (status, _) = send(
+        &app,
+        request(
+            "POST",
+            "/me/2fa/verify",
+            &session.token,
+            Some(serde_json::json!({ "code": "123456" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "Nothing to verify yet");
+
+    let (secret, recovery_codes) = enroll(&app, &session.token).await;
+    assert_eq!(recovery_codes.len(), 10);
+
+    let stored: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM recovery_codes WHERE code_hash = ANY($1)")
+            .bind(&recovery_codes)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(stored, 0, "Recovery codes are only stored hashed");
+
+    let (status, _) = send(
+        &app,
+        request(
+            "POST",
+            "/me/2fa/setup",
+            &session.token,
+            Some(serde_json::json!({})),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Password alone no longer yields a session.
+    let challenge = login_challenge(&app, &email).await;
+    let (status, _) = second_factor(&app, &challenge, "000000").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let next_code = code(&secret, 1);
+    let (status, body) = second_factor(&app, &challenge, &next_code).await;
+    assert_eq!(status, StatusCode::OK);
+    let second: LoginResponse = serde_json::from_value(body).unwrap();
+    let (status, _) = send(&app, request("GET", "/me", &second.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = second_factor(&app, &challenge, &next_code).await;
+    assert_eq!(
+        status,
+        StatusCode::UNAUTHORIZED,
+        "Challenges are single-use"
+    );
+
+    let challenge = login_challenge(&app, &email).await;
+    let (status, _) = second_factor(&app, &challenge, &next_code).await;
+    assert_eq!(
+        status,
+        StatusCode::UNAUTHORIZED,
+        "TOTP codes cannot be replayed"
+    );
+
+    // Recovery codes work once, regardless of case.
+    let (status, _) = second_factor(&app, &challenge, &recovery_codes[0].to_uppercase()).await;
+    assert_eq!(status, StatusCode::OK);
+    let challenge = login_challenge(&app, &email).await;
+    let (status, _) = second_factor(&app, &challenge, &recovery_codes[0]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(
+        &app,
+        request(
+            "POST",
+            "/me/2fa/disable",
+            &second.token,
+            Some(serde_json::json!({ "code": "wrong-code" })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(
+        &app,
+        request(
+            "POST",
+            "/me/2fa/disable",
+            &second.token,
+            Some(serde_json::json!({ "code": recovery_codes[1] })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = login(&app, &email).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        serde_json::from_value::<LoginResponse>(body).is_ok(),
+        "Login is one step again"
+    );
+}
+
+#[tokio::test]
+async fn test_login_challenge_attempt_limit() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_login_challenge_attempt_limit: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let (_, body) = login(&app, &email).await;
+    let session: LoginResponse = serde_json::from_value(body).unwrap();
+    let (_, recovery_codes) = enroll(&app, &session.token).await;
+
+    let challenge = login_challenge(&app, &email).await;
+    for attempt in 1..=5 {
+        if attempt == 5 {
+            // Wait out the account backoff the fourth miss started
+            tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        }
+        let (status, _) = second_factor(&app, &challenge, "not-a-code").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    let (status, body) = second_factor(&app, &challenge, &recovery_codes[0]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "Invalid or expired login challenge");
+}
+
+#[tokio::test]
+async fn test_admin_routes_require_two_factor() {
+    let app = match common::setup_test_app_with(|config| {
+        config.require_admin_2fa = true;
+    })
+    .await
+    {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_admin_routes_require_two_factor: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    sqlx::query("UPDATE users SET role = 'Admin' WHERE email = $1")
+        .bind(&email)
+        .execute(&app.db)
+        .await
+        .unwrap();
+
+    let (_, body) = login(&app, &email).await;
+    let session: LoginResponse = serde_json::from_value(body).unwrap();
+
+    let (status, body) = send(&app, request("GET", "/permissions", &session.token, None)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(
+        body["error"],
+        "Two-factor authentication required for admin access"
+    );
+
+    // Enrolling upgrades the current session from its next refresh on.
+    enroll(&app, &session.token).await;
+    let (status, body) = send(
+        &app,
+        post_json(
+            "/token/refresh",
+            serde_json::json!({ "refresh_token": session.refresh_token }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let refreshed: LoginResponse = serde_json::from_value(body).unwrap();
+
+    let (status, _) = send(&app, request("GET", "/permissions", &refreshed.token, None)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_privileged_permissions_require_two_factor() {
+    let app = match common::setup_test_app_with(|config| {
+        config.require_admin_2fa = true;
+    })
+    .await
+    {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_privileged_permissions_require_two_factor: {e}");
+            return;
+        }
+    };
+
+    let admin = common::user_with_role(&app, "Admin").await;
+    let victim = common::user_with_role(&app, "Viewer").await;
+    let delete = serde_json::json!({ "id": victim.id });
+
+    // `mfa: false`
+    for (method, uri, body) in [
+        ("GET", "/users", None),
+        ("GET", "/audit", None),
+        ("DELETE", "/user", Some(delete.clone())),
+    ] {
+        let (status, body) = send(&app, request(method, uri, &admin.token, body)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{method} {uri}");
+        assert_eq!(
+            body["error"],
+            "Two-factor authentication required for admin access"
+        );
+    }
+
+    let two_factor = backend::auth::security::create_access_token(
+        &admin.id.to_string(),
+        &admin.name,
+        "Admin",
+        &Uuid::new_v4().to_string(),
+        true,
+        "test_secret",
+        chrono::Duration::hours(1),
+    )
+    .unwrap();
+    let (status, _) = send(&app, request("GET", "/audit", &two_factor, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, request("DELETE", "/user", &two_factor, Some(delete))).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_wrong_codes_count_towards_account_lockout() {
+    let app = match common::setup_test_app().await {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("Skipping test_wrong_codes_count_towards_account_lockout: {e}");
+            return;
+        }
+    };
+
+    let email = register(&app).await;
+    let (_, body) = login(&app, &email).await;
+    let session: LoginResponse = serde_json::from_value(body).unwrap();
+    let (secret, _) = enroll(&app, &session.token).await;
+
+    // The password step alone does not clear earlier misses: three wrong
+    // codes plus a fourth start the backoff, on a fresh challenge each time.
+    for _ in 0..4 {
+        let challenge = login_challenge(&app, &email).await;
+        let (status, _) = second_factor(&app, &challenge, "000000").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    let (status, body) = login(&app, &email).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{body}");
+
+    // Once the backoff has passed, the right code completes the login and
+    // resets the count.
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    let challenge = login_challenge(&app, &email).await;
+    let (status, _) = second_factor(&app, &challenge, &code(&secret, 1)).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut redis = app.state.redis.clone();
+    let blocked = backend::auth::limiter::Limiter::new("login", 900)
+        .blocked_for(&mut redis, &backend::auth::limiter::account_subject(&email))
+        .await
+        .unwrap();
+    assert_eq!(blocked, None);
+}